@@ -5,16 +5,19 @@
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet};
 use std::ffi::OsStr;
-use std::path::PathBuf;
-use std::process::{Child, Command, Stdio};
-use std::{env, io, iter};
+use std::io::{IsTerminal, Write};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{self, AtomicBool};
+use std::{env, io, iter, thread};
 
 use clap::ColorChoice;
 use color_eyre::Section;
 use color_eyre::eyre::{self, Result, WrapErr, eyre};
-use log::Level;
-use regex::Regex;
 use semver::Version;
+use serde::Serialize;
 
 use crate::cli::BinstallChoice;
 use crate::config::DetailedPackageReq;
@@ -26,19 +29,24 @@ use crate::config::DetailedPackageReq;
 /// variable as it is set by Cargo when it calls an external subcommand's
 /// corresponding program. See the [Cargo reference] for more details.
 ///
+/// See [`run_install_cmd`] for the meaning of `capture`.
+///
 /// [Cargo reference]: https://doc.rust-lang.org/cargo/reference/external-tools.html#custom-subcommands
 #[expect(
     clippy::too_many_lines,
     reason = "This is just a long list of options to apply."
 )]
+#[expect(clippy::too_many_arguments, reason = "Plumbing.")]
 fn install(
     pkg_name: &str,
     pkg_req: &DetailedPackageReq,
     force: bool,
     dry_run: bool,
     target: Option<&str>,
+    root: Option<&Path>,
     color: ColorChoice,
     verbosity: i8,
+    capture: bool,
 ) -> Result<()> {
     let mut cmd = Command::new(env_var()?);
 
@@ -51,12 +59,12 @@ fn install(
     add_verbosity_arg(&mut cmd, verbosity);
     cmd.args(["install", "--version", &pkg_req.version.to_string()]);
 
-    if !pkg_req.default_features {
+    if pkg_req.default_features == Some(false) {
         cmd.arg("--no-default-features");
         log::trace!("`--no-default-features` arg added.");
     }
 
-    if pkg_req.all_features {
+    if pkg_req.all_features.unwrap_or_default() {
         cmd.arg("--all-features");
         log::trace!("`--all-features` arg added.");
     }
@@ -71,6 +79,11 @@ fn install(
         log::trace!("`--target {target}` args added.");
     }
 
+    if let Some(root) = root {
+        cmd.arg("--root").arg(root);
+        log::trace!("`--root {root:?}` args added.");
+    }
+
     if let Some(index) = pkg_req.index.as_deref() {
         cmd.args(["--index", index]);
         log::trace!("`--index {index}` args added.");
@@ -111,7 +124,7 @@ fn install(
         log::trace!("`--bin {bin}` args added.");
     }
 
-    if pkg_req.all_bins {
+    if pkg_req.all_bins.unwrap_or_default() {
         cmd.arg("--bins");
         log::trace!("`--bins` arg added.");
     }
@@ -121,12 +134,12 @@ fn install(
         log::trace!("`--example {example}` args added.");
     }
 
-    if pkg_req.all_examples {
+    if pkg_req.all_examples.unwrap_or_default() {
         cmd.arg("--examples");
         log::trace!("`--examples` arg added.");
     }
 
-    if pkg_req.ignore_rust_version {
+    if pkg_req.ignore_rust_version.unwrap_or_default() {
         cmd.arg("--ignore-rust-version");
         log::trace!("`--ignore-rust-version` arg added.");
     }
@@ -136,17 +149,17 @@ fn install(
         log::trace!("`--force` arg added.");
     }
 
-    if pkg_req.frozen {
+    if pkg_req.frozen.unwrap_or_default() {
         cmd.arg("--frozen");
         log::trace!("`--frozen` arg added.");
     }
 
-    if pkg_req.locked {
+    if pkg_req.locked.unwrap_or_default() {
         cmd.arg("--locked");
         log::trace!("`--locked` arg added.");
     }
 
-    if pkg_req.offline {
+    if pkg_req.offline.unwrap_or_default() {
         cmd.arg("--offline");
         log::trace!("`--offline` arg added.");
     }
@@ -168,8 +181,7 @@ fn install(
         // TODO: Don't forget to check status here as well when passing `--dry-run`.
         Ok(())
     } else {
-        let status = cmd
-            .status()
+        let status = run_install_cmd(&mut cmd, capture)
             .wrap_err("Failed to execute Cargo.")
             .note(
                 "This can happen for many reasons, but it should not happen easily at this point.",
@@ -183,6 +195,29 @@ fn install(
     }
 }
 
+/// Runs the given installation command and returns its exit status.
+///
+/// When `capture` is `true`, the outputs of the command are captured and only
+/// written to the standard error output at once when it is done, in order for
+/// them not to interleave with the ones of other concurrent commands.
+/// Otherwise, they are inherited as usual.
+fn run_install_cmd(cmd: &mut Command, capture: bool) -> io::Result<ExitStatus> {
+    if capture {
+        let out = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()?;
+        let mut stderr = io::stderr().lock();
+        stderr.write_all(&out.stdout)?;
+        stderr.write_all(&out.stderr)?;
+        stderr.flush()?;
+        Ok(out.status)
+    } else {
+        cmd.status()
+    }
+}
+
 /// Adds the adequate verbosity argument to the given Cargo [`Command`].
 fn add_verbosity_arg(cmd: &mut Command, verbosity: i8) {
     match verbosity.cmp(&0) {
@@ -202,13 +237,16 @@ fn add_verbosity_arg(cmd: &mut Command, verbosity: i8) {
 }
 
 /// Equivalent of [`install`] using `cargo-binstall` as a backend.
+#[expect(clippy::too_many_arguments, reason = "Plumbing.")]
 fn binstall(
     pkg_name: &str,
     pkg_req: &DetailedPackageReq,
     force: bool,
     dry_run: bool,
     target: Option<&str>,
+    root: Option<&Path>,
     verbosity: i8,
+    capture: bool,
 ) -> Result<()> {
     let mut cmd = Command::new(env_var()?);
     // The tool emits to stdout by default and does not have an option to
@@ -250,6 +288,11 @@ fn binstall(
         log::trace!("`--targets {target}` args added.");
     }
 
+    if let Some(root) = root {
+        cmd.arg("--root").arg(root);
+        log::trace!("`--root {root:?}` args added.");
+    }
+
     for bin in &pkg_req.bins {
         cmd.args(["--bin", bin]);
         log::trace!("`--bin {bin}` args added.");
@@ -282,7 +325,7 @@ fn binstall(
         log::info!("Bump verbosity if additional details are desired.");
     }
 
-    if pkg_req.locked {
+    if pkg_req.locked.unwrap_or_default() {
         cmd.arg("--locked");
         log::trace!("`--locked` arg added.");
     }
@@ -294,12 +337,11 @@ fn binstall(
     }
 
     // Synchronize any new arguments added here with the
-    // `pkg_req_is_compatible_with_binstall` function below.
+    // `binstall_unsupported_options` function below.
     cmd.args(["--", pkg_name]);
     log_cmd(&cmd);
 
-    let status = cmd
-        .status()
+    let status = run_install_cmd(&mut cmd, capture)
         .wrap_err("Failed to execute Cargo.")
         .note("This can happen for many reasons, but it should not happen easily at this point.")
         .suggestion("Read the underlying error message.")?;
@@ -316,6 +358,12 @@ fn binstall(
 ///  * `true`: fully compatible, all options passed;
 ///  * `false`: some options would be skipped;
 fn pkg_req_is_compatible_with_binstall(pkg_req: &DetailedPackageReq) -> bool {
+    binstall_unsupported_options(pkg_req).is_empty()
+}
+
+/// Returns the configuration names of the options set in the given package
+/// requirement that `cargo-binstall` does not support and would ignore.
+pub fn binstall_unsupported_options(pkg_req: &DetailedPackageReq) -> Vec<&'static str> {
     // Full destructuring to avoid forgetting to update this function.
     let DetailedPackageReq {
         version: _,
@@ -344,31 +392,41 @@ fn pkg_req_is_compatible_with_binstall(pkg_req: &DetailedPackageReq) -> bool {
         no_fail_fast: _,
         target: _,
         binstall: _,
+        template: _,
+        groups: _,
     } = pkg_req;
-    // Work by double negation: not incompatible.
-    !(!*default_features
-        || *all_features
-        || !features.is_empty()
-        || branch.is_some()
-        || tag.is_some()
-        || rev.is_some()
-        || path.is_some()
-        || *all_bins
-        || !examples.is_empty()
-        || *all_examples
-        || *ignore_rust_version
-        || *frozen
-        || *offline)
+    [
+        ("default-features", *default_features == Some(false)),
+        ("all-features", all_features.unwrap_or_default()),
+        ("features", !features.is_empty()),
+        ("branch", branch.is_some()),
+        ("tag", tag.is_some()),
+        ("rev", rev.is_some()),
+        ("path", path.is_some()),
+        ("all-bins", all_bins.unwrap_or_default()),
+        ("examples", !examples.is_empty()),
+        ("all-examples", all_examples.unwrap_or_default()),
+        (
+            "ignore-rust-version",
+            ignore_rust_version.unwrap_or_default(),
+        ),
+        ("frozen", frozen.unwrap_or_default()),
+        ("offline", offline.unwrap_or_default()),
+    ]
+    .into_iter()
+    .filter_map(|(name, is_set)| is_set.then_some(name))
+    .collect()
 }
 
 /// Heuristically determines whether `cargo-binstall` is installed or not.
 ///
 /// Currently, it first tries to see if it is among the installed packages if
 /// they have been retrieved previously, and tries to call the program directly
-/// with some additional validation if not.
-fn binstall_is_available(installed: &BTreeSet<String>) -> bool {
+/// with some additional validation if not or if they are the ones of another
+/// installation root than the default one.
+fn binstall_is_available(installed: &BTreeSet<String>, root: Option<&Path>) -> bool {
     // When `--skip-check` is used.
-    let res = if installed.is_empty() {
+    let res = if installed.is_empty() || root.is_some() {
         binstall_version()
             .inspect(|ver| log::debug!("cargo-binstall successfully reports: {ver:?}"))
             .inspect_err(|err| log::debug!("cargo-binstall automatic detection failed: {err:?}"))
@@ -425,9 +483,11 @@ fn install_one(
     force: bool,
     dry_run: bool,
     target: Option<&str>,
+    root: Option<&Path>,
     binstall: BinstallChoice,
     color: ColorChoice,
     verbosity: i8,
+    capture: bool,
 ) -> Result<()> {
     // HACK: disable the auto mode under testing to easily avoid bothersome
     // hacks and maintenance there in order to avoid failing when the tool is
@@ -435,14 +495,16 @@ fn install_one(
     // to explicitly enable the feature through the `always` setting.
     if binstall == BinstallChoice::Always
         || binstall == BinstallChoice::Auto
-            && !context_seems_testing(false)
-            && binstall_is_available(installed)
+            && !context_seems_testing()
+            && binstall_is_available(installed, root)
     {
         // See #31: avoid using Binstall in cases where incompatible arguments
         // would not be forwarded but still be important for overall success.
         if binstall == BinstallChoice::Always || pkg_req_is_compatible_with_binstall(pkg_req) {
             log::debug!("Using `cargo-binstall` as the installation method.");
-            return self::binstall(pkg_name, pkg_req, force, dry_run, target, verbosity);
+            return self::binstall(
+                pkg_name, pkg_req, force, dry_run, target, root, verbosity, capture,
+            );
         }
 
         log::warn!(
@@ -452,14 +514,23 @@ fn install_one(
     }
 
     log::debug!("Using `cargo install` as the installation method.");
-    install(pkg_name, pkg_req, force, dry_run, target, color, verbosity)
+    install(
+        pkg_name, pkg_req, force, dry_run, target, root, color, verbosity, capture,
+    )
 }
 
 /// Runs `cargo install` or `binstall` for all packages listed in the given
 /// user configuration and returns a per-package installation report.
 ///
-/// Returns `Ok(report)` when `no_fail_fast` is `true`, otherwise `Err(err)` of
-/// the first error `err` encountered.
+/// Up to `jobs` packages are installed in parallel, with their outputs then
+/// captured in order to avoid interleaving them; unset means one at a time.
+/// Captured outputs are colored when the automatic color choice would color
+/// the current standard error.
+///
+/// When `no_fail_fast` is `false`, no package is started after the first error
+/// encountered, which is then the one reported, but the packages installed in
+/// the meantime by the other jobs are still part of the report. Otherwise, all
+/// errors are aggregated.
 #[expect(clippy::too_many_arguments, reason = "Plumbing.")]
 pub fn install_all(
     packages: &BTreeMap<String, DetailedPackageReq>,
@@ -468,71 +539,120 @@ pub fn install_all(
     force: bool,
     dry_run: bool,
     target: Option<&str>,
+    root: Option<&Path>,
     binstall: BinstallChoice,
+    jobs: Option<NonZeroUsize>,
     color: ColorChoice,
     verbosity: i8,
-) -> Result<InstallReport> {
+) -> InstallReport {
+    let workers = jobs.map_or(1, NonZeroUsize::get).min(packages.len()).max(1);
+    // Cargo cannot see the terminal its captured output is forwarded to.
+    let color = match color {
+        ColorChoice::Auto if workers > 1 => {
+            if io::stderr().is_terminal() {
+                ColorChoice::Always
+            } else {
+                ColorChoice::Never
+            }
+        }
+        color => color,
+    };
+    // Packages left to install, shared among workers.
+    let queue = Mutex::new(packages.iter());
+    // Results of the installations, whatever their order of completion.
+    let results = Mutex::new(BTreeMap::new());
+    // Stops the workers from picking new packages up after a fail-fast error.
+    let abort = AtomicBool::new(false);
+
+    log::debug!("Installing with {workers} job(s)...");
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                while !abort.load(atomic::Ordering::Relaxed) {
+                    // Release the lock before installing anything.
+                    let Some((pkg_name, pkg)) = queue.lock().unwrap().next() else {
+                        break;
+                    };
+                    let is_installed = installed.contains(pkg_name);
+                    log::info!(
+                        "{}ing `{pkg_name}`...",
+                        if is_installed { "Updat" } else { "Install" }
+                    );
+
+                    let res = install_one(
+                        installed,
+                        pkg_name,
+                        pkg,
+                        force || pkg.force.unwrap_or_default(),
+                        dry_run,
+                        pkg.target.as_deref().or(target),
+                        root,
+                        // FIXME: this lets the per-package configuration have
+                        // precedence over the global defaults, but over the
+                        // CLI as well; optionals should be introduced in order
+                        // to re-order things properly instead.
+                        pkg.binstall.unwrap_or(binstall),
+                        color,
+                        verbosity,
+                        workers > 1,
+                    )
+                    .wrap_err_with(|| {
+                        format!(
+                            "Failed to {} {pkg_name:?}.",
+                            if is_installed { "update" } else { "install" }
+                        )
+                    });
+
+                    if res.is_err() && !(no_fail_fast || pkg.no_fail_fast.unwrap_or_default()) {
+                        abort.store(true, atomic::Ordering::Relaxed);
+                    }
+                    results
+                        .lock()
+                        .unwrap()
+                        .insert(pkg_name, (pkg, is_installed, res));
+                }
+            });
+        }
+    });
+
     // Returned installation report.
     let mut rep = BTreeMap::new();
     // Aggregation of errors when `no_fail_fast` is enabled.
     let mut err_rep = None::<eyre::Report>;
-
-    for (pkg_name, pkg) in packages {
-        let is_installed = installed.contains(pkg_name);
-        log::info!(
-            "{}ing `{pkg_name}`...",
-            if is_installed { "Updat" } else { "Install" }
+    // First error that stopped the installations otherwise.
+    let mut fail_fast_err = None::<eyre::Report>;
+
+    // Packages are visited in order, so the result is the same as if they had
+    // been installed sequentially.
+    for (pkg_name, (pkg, is_installed, res)) in results.into_inner().unwrap() {
+        rep.insert(
+            pkg_name.clone(),
+            match (&res, is_installed) {
+                (Err(_), _) => InstallStatus::Failed,
+                (Ok(()), true) => InstallStatus::Updated,
+                (Ok(()), false) => InstallStatus::Installed,
+            },
         );
 
-        if let Err(err) = install_one(
-            installed,
-            pkg_name,
-            pkg,
-            force || pkg.force,
-            dry_run,
-            pkg.target.as_deref().or(target),
-            // FIXME: this lets the per-package configuration have precedence
-            // over the global defaults, but over the CLI as well; optionals
-            // should be introduced in order to re-order things properly instead.
-            pkg.binstall.unwrap_or(binstall),
-            color,
-            verbosity,
-        )
-        .inspect(|()| {
-            rep.insert(
-                pkg_name.clone(),
-                if is_installed {
-                    InstallStatus::Updated
-                } else {
-                    InstallStatus::Installed
-                },
-            );
-        })
-        .wrap_err_with(|| {
-            rep.insert(pkg_name.clone(), InstallStatus::Failed);
-            format!(
-                "Failed to {} {pkg_name:?}.",
-                if is_installed { "update" } else { "install" }
-            )
-        }) {
-            if no_fail_fast || pkg.no_fail_fast {
+        if let Err(err) = res {
+            if no_fail_fast || pkg.no_fail_fast.unwrap_or_default() {
                 // Can't use `Option::map_or` for ownership reasons.
                 err_rep = Some(match err_rep {
                     Some(err_rep) => err_rep.wrap_err(err),
                     None => err,
                 });
-            } else {
-                return Err(err.suggestion(
+            } else if fail_fast_err.is_none() {
+                fail_fast_err = Some(err.suggestion(
                     "Use `ship --no-fail-fast` to ignore this and continue on with other packages.",
                 ));
             }
         }
     }
 
-    Ok(InstallReport {
+    InstallReport {
         package_statuses: rep,
-        error_report: err_rep,
-    })
+        error_report: fail_fast_err.or(err_rep),
+    }
 }
 
 /// Result of [`install_all`].
@@ -546,7 +666,8 @@ pub struct InstallReport {
 }
 
 /// Result of [`install`] for some package.
-#[derive(Debug)]
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
 pub enum InstallStatus {
     /// The package was newly installed with success.
     Installed,
@@ -556,12 +677,24 @@ pub enum InstallStatus {
     Failed,
 }
 
-/// Runs `cargo uninstall` with the given package name.
-fn uninstall(pkg_name: &str, dry_run: bool, color: ColorChoice, verbosity: i8) -> Result<()> {
+/// Runs `cargo uninstall` with the given package name, from the given
+/// installation root if any.
+fn uninstall(
+    pkg_name: &str,
+    root: Option<&Path>,
+    dry_run: bool,
+    color: ColorChoice,
+    verbosity: i8,
+) -> Result<()> {
     let mut cmd = Command::new(env_var()?);
     cmd.args(["--color", &color.to_string()]);
     add_verbosity_arg(&mut cmd, verbosity);
-    cmd.args(["uninstall", "--", pkg_name]);
+    cmd.arg("uninstall");
+    if let Some(root) = root {
+        cmd.arg("--root").arg(root);
+        log::trace!("`--root {root:?}` args added.");
+    }
+    cmd.args(["--", pkg_name]);
     log_cmd(&cmd);
 
     if dry_run {
@@ -582,9 +715,11 @@ fn uninstall(pkg_name: &str, dry_run: bool, color: ColorChoice, verbosity: i8) -
     })
 }
 
-/// Uninstalls all the packages given by name.
+/// Uninstalls all the packages given by name, from the given installation root
+/// if any.
 pub fn uninstall_all(
     pkg_names: impl IntoIterator<Item = impl AsRef<str>>,
+    root: Option<&Path>,
     no_fail_fast: bool,
     dry_run: bool,
     color: ColorChoice,
@@ -597,7 +732,7 @@ pub fn uninstall_all(
         let pkg_name = pkg_name.as_ref();
         log::info!("Uninstalling {pkg_name:?}...");
 
-        if let Err(err) = uninstall(pkg_name, dry_run, color, verbosity)
+        if let Err(err) = uninstall(pkg_name, root, dry_run, color, verbosity)
             .wrap_err_with(|| format!("Failed to uninstall {pkg_name:?}."))
         {
             if no_fail_fast {
@@ -616,119 +751,6 @@ pub fn uninstall_all(
     err_rep.map_or(Ok(()), Err)
 }
 
-/// Spawns `cargo search` for the given package with only stdout piped and
-/// returns the corresponding child process handle to be used with
-/// [`finish_search_exact`].
-fn spawn_search_exact(pkg: &str) -> Result<Child> {
-    // HACK: detect the test context in order to adapt the execution to it.
-    let is_test = context_seems_testing(true);
-    let mut cmd = Command::new(env_var()?);
-
-    cmd.stdin(Stdio::null());
-    cmd.stderr(if is_test || log::log_enabled!(Level::Debug) {
-        Stdio::inherit()
-    } else {
-        Stdio::null()
-    });
-    cmd.stdout(Stdio::piped());
-    cmd.args(["--color=never", "search"]);
-
-    if is_test {
-        cmd.arg("--registry=dummy-registry");
-    }
-
-    cmd.args(["--limit=1", "--", pkg]);
-
-    log_cmd(&cmd);
-    cmd.spawn()
-        .wrap_err("Failed to spawn Cargo.")
-        .note("This can happen for many reasons, but it should not happen easily at this point.")
-        .suggestion("Read the underlying error message.")
-}
-
-/// Waits for the given child process as spawned by [`spawn_search_exact`] to
-/// finish and extract the received package version from the output.
-fn finish_search_exact(pkg: &str, proc: Child) -> Result<Version> {
-    let out = proc
-        .wait_with_output()
-        .wrap_err_with(|| format!("Failed to wait for the Cargo child process for {pkg:?}."))
-        .note("This can happen for many reasons, but it should not happen easily at this point.")
-        .suggestion("Read the underlying error message.")?;
-
-    if !out.status.success() {
-        eyre::bail!(
-            "Search for {:?} failed on {:?} with stderr: {:?}",
-            pkg,
-            out.status.code(),
-            String::from_utf8(out.stderr),
-        );
-    }
-
-    let stdout = String::from_utf8(out.stdout)
-        .wrap_err("Failed to decode the standard output.")
-        .note("This really should not happen.")
-        .suggestion(crate::OPEN_ISSUE_MSG)?;
-    log::trace!("Search for {pkg:?} got: {stdout:?}");
-
-    // See https://semver.org/#backusnaur-form-grammar-for-valid-semver-versions.
-    let ver = Regex::new(&format!(r#"{pkg}\s=\s"([0-9a-zA-Z.+-]+)"\s+#.*"#))
-        .wrap_err_with(|| format!("Failed to build the search regex for {pkg:?}."))
-        .note("This can happen if the package name contains some special characters.")
-        .suggestion("Check that the name is the intended one or open an issue.")?
-        .captures(
-            stdout
-                .lines()
-                .next()
-                .ok_or_else(|| eyre!("Not at least one line in search output for {pkg:#?}."))
-                .suggestion("Check that the package does indeed exist.")?,
-        )
-        .ok_or_else(|| eyre!("No regex capture while parsing search output for {pkg:#?}."))
-        .suggestion("Check that the package does indeed exist.")?
-        .get(1)
-        .ok_or_else(|| eyre!("Version not captured by regex matching search output for {pkg:#?}."))
-        .note(
-            "This should not easily happen at this point: the search returned the wanted package.",
-        )
-        .suggestion(crate::OPEN_ISSUE_MSG)?
-        .as_str()
-        .parse::<Version>()
-        .wrap_err_with(|| format!("Failed to parse the version received for {pkg:?}."))
-        .note(
-            "This should not easily happen at this point: the search returned the wanted package.",
-        )
-        .suggestion(crate::OPEN_ISSUE_MSG)?;
-    log::trace!("Parsed version is: {ver:#?}.");
-
-    Ok(ver)
-}
-
-/// Runs `*_search_exact` for all packages in the given list and returns the
-/// thus fetched versions in the collected map.
-pub fn search_exact_all(pkgs: &[impl AsRef<str>]) -> Result<BTreeMap<String, Version>> {
-    log::info!("Fetching latest package versions...");
-    let mut procs = Vec::new();
-    let mut vers = BTreeMap::new();
-
-    log::debug!("Spawning search child processes in parallel...");
-    for pkg in pkgs.iter().map(AsRef::as_ref) {
-        procs.push(
-            spawn_search_exact(pkg)
-                .wrap_err_with(|| format!("Failed to spawn search for {pkg:?}."))?,
-        );
-    }
-
-    log::debug!("Waiting for each search child processes to finish...");
-    for (pkg, proc) in pkgs.iter().map(AsRef::as_ref).zip(procs) {
-        vers.insert(
-            pkg.to_owned(),
-            finish_search_exact(pkg, proc)
-                .wrap_err_with(|| format!("Failed to finish search for {pkg:?}."))?,
-        );
-    }
-
-    Ok(vers)
-}
-
 /// Runs `cargo config get` with the given configuration key and returns the
 /// collected string value.
 pub fn config_get(key: &str) -> Result<String> {
@@ -736,6 +758,8 @@ pub fn config_get(key: &str) -> Result<String> {
     // HACK: get access to nightly features.
     // FIXME: remove when `config` gets stabilized.
     cmd.env("RUSTC_BOOTSTRAP", "1");
+    // HACK: the testing environment forces the stable channel otherwise.
+    cmd.env_remove("__CARGO_TEST_CHANNEL_OVERRIDE_DO_NOT_USE_THIS");
     cmd.args([
         "--color=never",
         "-Zunstable-options",
@@ -768,6 +792,59 @@ pub fn config_get(key: &str) -> Result<String> {
     Ok(out_str.trim_end().trim_matches('"').to_owned())
 }
 
+/// Runs `rustc` with the given arguments and returns its standard output.
+fn rustc_output(args: &[&str]) -> Result<String> {
+    let mut cmd = Command::new(env::var_os("RUSTC").unwrap_or_else(|| "rustc".into()));
+    cmd.stdin(Stdio::null());
+    cmd.stderr(Stdio::null());
+    cmd.args(args);
+
+    log_cmd(&cmd);
+    let out = cmd
+        .output()
+        .wrap_err("Failed to execute `rustc`.")
+        .note("This can happen for many reasons, but it should not happen easily at this point.")
+        .suggestion("Read the underlying error message.")?;
+    out.status.success().then_some(()).ok_or_else(|| {
+        eyre!(
+            "Command failed with status: {:#?} and stderr: {:#?}.",
+            out.status,
+            String::from_utf8(out.stderr),
+        )
+    })?;
+
+    let out_str = String::from_utf8(out.stdout)
+        .wrap_err("Failed to decode the standard output.")
+        .note("This really should not happen.")
+        .suggestion(crate::OPEN_ISSUE_MSG)?;
+    log::trace!("Got: {out_str:#?}.");
+    Ok(out_str)
+}
+
+/// Runs `rustc --version` and returns the parsed version of the toolchain
+/// currently active, as it would be used by `cargo install`.
+pub fn rustc_version() -> Result<Version> {
+    let out_str = rustc_output(&["--version"])?;
+    // Expected format: `rustc <version> (<hash> <date>)`.
+    out_str
+        .split_whitespace()
+        .nth(1)
+        .ok_or_else(|| eyre!("Unexpected output format: {out_str:?}."))?
+        .parse::<Version>()
+        .wrap_err("Failed to parse the version of `rustc`.")
+}
+
+/// Runs `rustc -vV` and returns the host target triple of the toolchain
+/// currently active, i.e. the default target of `cargo install`.
+pub fn rustc_host() -> Result<String> {
+    let out_str = rustc_output(&["-vV"])?;
+    out_str
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| eyre!("No host found in output: {out_str:?}."))
+}
+
 /// Wrapper around [`home::cargo_home`] with additional reporting context.
 pub fn home() -> Result<PathBuf> {
     home::cargo_home()
@@ -789,10 +866,9 @@ fn env_var() -> Result<String> {
 /// It currently uses some environment variable that `cargo-test-support`'s
 /// `cargo_test` macro sets for invoked Cargo instances. Set
 /// `__THIS_IS_REALLY_TESTING_BUT_HUSH` to any value to make this always return
-/// `false`. `version_checking` should be `true` when calling this from the
-/// `cargo search` version checking.
-fn context_seems_testing(version_checking: bool) -> bool {
-    (version_checking || env::var_os("__THIS_IS_REALLY_TESTING_BUT_HUSH").is_none())
+/// `false`.
+fn context_seems_testing() -> bool {
+    env::var_os("__THIS_IS_REALLY_TESTING_BUT_HUSH").is_none()
         && env::var_os("__CARGO_TEST_ROOT").is_some_and(|var| {
             let path = PathBuf::from(var);
             path.is_absolute() && path.is_dir()
@@ -800,7 +876,7 @@ fn context_seems_testing(version_checking: bool) -> bool {
 }
 
 /// Logs the program and arguments of the given command to DEBUG.
-fn log_cmd(cmd: &Command) {
+pub fn log_cmd(cmd: &Command) {
     log::debug!(
         "Running {:#?} with arguments {:#?}...",
         cmd.get_program().to_string_lossy(),
@@ -820,109 +896,8 @@ mod tests {
     use crate::testing;
 
     const SELF: &str = clap::crate_name!();
-    const NONE: &str = "azertyuiop-qsdfghjklm_wxcvbn";
     static LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
 
-    #[cargo_test]
-    fn test_singlethreaded_searchspawn_self_isok() -> Result<()> {
-        let _lk = LOCK.lock();
-        let _reg = testing::init_registry();
-        testing::fake_publish(SELF, "0.0.0");
-        testing::set_env();
-
-        let proc = spawn_search_exact(SELF)?;
-        assert_ne!(proc.id(), 0);
-        assert!(proc.stdin.is_none());
-        assert!(proc.stderr.is_none());
-        assert!(proc.stdout.is_some());
-
-        let res = proc.wait_with_output()?;
-        assert!(res.status.success());
-        assert!(String::from_utf8(res.stdout)?.lines().count() > 0);
-
-        Ok(())
-    }
-
-    #[cargo_test]
-    fn test_singlethreaded_searchfinish_self_isok() -> Result<()> {
-        let _lk = LOCK.lock();
-        let _reg = testing::init_registry();
-        testing::fake_publish(SELF, "0.0.0");
-        testing::set_env();
-
-        assert!(
-            finish_search_exact(SELF, spawn_search_exact(SELF)?)?
-                <= clap::crate_version!().parse()?
-        );
-        Ok(())
-    }
-
-    #[cargo_test]
-    fn test_singlethreaded_searchspawn_none_isok() -> Result<()> {
-        let _lk = LOCK.lock();
-        let _reg = testing::init_registry();
-        testing::set_env();
-
-        let proc = spawn_search_exact(NONE)?;
-        assert_ne!(proc.id(), 0);
-        assert!(proc.stdin.is_none());
-        assert!(proc.stderr.is_none());
-        assert!(proc.stdout.is_some());
-
-        let res = proc.wait_with_output()?;
-        assert!(res.status.success());
-        assert_eq!(String::from_utf8(res.stdout)?.lines().count(), 0);
-
-        Ok(())
-    }
-
-    #[cargo_test]
-    fn test_singlethreaded_searchfinish_none_iserr() -> Result<()> {
-        let _lk = LOCK.lock();
-        let _reg = testing::init_registry();
-        testing::set_env();
-
-        assert!(finish_search_exact(NONE, spawn_search_exact(NONE)?).is_err());
-        Ok(())
-    }
-
-    #[cargo_test]
-    fn test_singlethreaded_searchall_selfandothers_isok() -> Result<()> {
-        let _lk = LOCK.lock();
-        let _reg = testing::init_registry();
-        testing::fake_publish_all([
-            (SELF, clap::crate_version!()),
-            ("cargo-expand", "1.0.79"),
-            ("cargo-tarpaulin", "0.27.3"),
-            ("bat", "0.24.0"),
-        ]);
-        testing::set_env();
-
-        for (pkg, ver) in search_exact_all(&[SELF, "cargo-expand", "cargo-tarpaulin", "bat"])? {
-            assert_eq!(
-                ver,
-                match &*pkg {
-                    SELF => clap::crate_version!(),
-                    "cargo-expand" => "1.0.79",
-                    "cargo-tarpaulin" => "0.27.3",
-                    "bat" => "0.24.0",
-                    pkg => eyre::bail!("Unexpected package: {pkg:?}"),
-                }
-                .parse()?,
-            );
-        }
-        Ok(())
-    }
-
-    #[cargo_test]
-    fn test_singlethreaded_searchall_none_iserr() {
-        let _lk = LOCK.lock();
-        let _reg = testing::init_registry();
-        testing::set_env();
-
-        assert!(search_exact_all(&[NONE]).is_err());
-    }
-
     #[cargo_test(public_network_test)]
     fn test_singlethreaded_binstall() {
         let _lk = LOCK.lock();
@@ -943,7 +918,9 @@ mod tests {
             false,
             false,
             None,
+            None,
             0,
+            false,
         )
         .unwrap();
     }
@@ -955,7 +932,8 @@ mod tests {
         testing::set_env();
 
         assert!(binstall_is_available(
-            &["cargo-binstall".to_owned()].into_iter().collect()
+            &["cargo-binstall".to_owned()].into_iter().collect(),
+            None,
         ));
     }
 
@@ -965,7 +943,7 @@ mod tests {
         testing::fake_install("cargo-binstall", "0.0.0", false);
         testing::set_env();
 
-        assert!(binstall_is_available(&BTreeSet::new()));
+        assert!(binstall_is_available(&BTreeSet::new(), None));
     }
 
     #[cargo_test]
@@ -975,7 +953,8 @@ mod tests {
         testing::set_env();
 
         assert!(!binstall_is_available(
-            &["abc".to_owned()].into_iter().collect()
+            &["abc".to_owned()].into_iter().collect(),
+            None,
         ));
     }
 
@@ -990,7 +969,7 @@ mod tests {
         testing::fake_install("abc", "0.0.0", false);
         testing::set_env();
 
-        assert!(!binstall_is_available(&BTreeSet::new()));
+        assert!(!binstall_is_available(&BTreeSet::new(), None));
     }
 
     // To synchronize with the version installed in CI.
@@ -1013,4 +992,14 @@ mod tests {
         unsafe { env::remove_var("CARGO_INSTALL_ROOT") };
         Ok(())
     }
+
+    #[test]
+    fn test_rustcversion_isok() {
+        assert!(rustc_version().is_ok());
+    }
+
+    #[test]
+    fn test_rustchost_isok() {
+        assert!(!rustc_host().unwrap().is_empty());
+    }
 }
@@ -6,11 +6,15 @@
     clippy::struct_excessive_bools,
     reason = "This is the CLI module, so contains types handling all supported boolean flags."
 )]
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use clap::builder::ArgPredicate;
 use clap::{ArgAction, ColorChoice, Parser, ValueEnum};
 use clap_complete::Shell;
+use schemars::JsonSchema;
+use semver::VersionReq;
 use serde::{Deserialize, Serialize};
 
 /// Cargo entry point for `cargo-liner`.
@@ -39,6 +43,21 @@ pub struct LinerArgs {
     #[command(subcommand)]
     pub command: Option<LinerCommands>,
 
+    /// Use the configuration file at the given path instead of the default
+    /// one.
+    ///
+    /// By default, the project configuration found by walking up from the
+    /// current directory is used, if any, otherwise `$CARGO_HOME/liner.toml`.
+    /// When `-` is given, the configuration is read from the standard input
+    /// instead, in which case the commands writing to it, such as `import`,
+    /// fail. The `liner.d` directory next to the given file is merged after
+    /// it, as usual.
+    /// This takes precedence over the environment.
+    ///
+    /// [env: `CARGO_LINER_CONFIG`]
+    #[arg(long, global = true, value_name = "PATH", display_order = 988)]
+    pub config: Option<PathBuf>,
+
     /// Be more verbose. Use multiple times to be more and more so each time.
     ///
     /// When omitted, INFO and above messages of only this crate are logged.
@@ -100,6 +119,25 @@ pub struct LinerArgs {
         display_order = 991
     )]
     pub color: ColorChoice,
+
+    /// Control the format of the reports.
+    ///
+    /// When `json` is used, the results of the version checks, the
+    /// installation statuses, the uninstallation plan, the imported packages
+    /// and the error reports are additionally printed to the standard output
+    /// as JSON objects, one per line, each having a `reason` field indicating
+    /// its kind. The human-readable logs are left unchanged on the standard
+    /// error output.
+    #[arg(
+        long,
+        global = true,
+        required = false,
+        value_enum,
+        default_value = "human",
+        value_name = "FMT",
+        display_order = 992
+    )]
+    pub message_format: MessageFormat,
 }
 
 impl LinerArgs {
@@ -131,6 +169,16 @@ impl LinerArgs {
     }
 }
 
+/// Choices for [`LinerArgs::message_format`].
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// Only human-readable logs are displayed.
+    #[default]
+    Human,
+    /// JSON messages are printed in addition to the usual logs.
+    Json,
+}
+
 /// Subcommands for the main CLI.
 #[derive(clap::Subcommand, Debug, PartialEq, Eq)]
 pub enum LinerCommands {
@@ -145,13 +193,67 @@ pub enum LinerCommands {
     /// Cargo, but that are not part of the Liner configuration. Use with care!
     Jettison(JettisonArgsWithNegations),
 
+    /// Report what `ship` and `jettison` would do, without doing it.
+    ///
+    /// The same checks as `ship` are performed and not-configured installed
+    /// packages are listed. Exits with code 2 when at least one package needs
+    /// to be installed, updated or uninstalled, making it usable in scripts.
+    Status,
+
+    /// Validate the configuration file thoroughly.
+    ///
+    /// Unknown keys, conflicting options, options unsupported by
+    /// `cargo-binstall` when it is always used, and invalid requirements are
+    /// all reported, each with its location in the file. Setting `strict =
+    /// true` in the file makes every other command perform the same checks.
+    Check,
+
+    /// Print a JSON Schema of the configuration file.
+    ///
+    /// It is generated from the supported configuration items and can be
+    /// given to editors in order to validate and auto-complete the file, for
+    /// example using Taplo's `#:schema` directive. The schema is emitted to
+    /// standard output.
+    Schema,
+
+    /// Print the `PATH` to use in order to run the installed packages.
+    ///
+    /// It is the current `PATH` with the binaries directory of the
+    /// installation root prepended, which is the project's one when a project
+    /// configuration is in use. It can be applied by running for example
+    /// `export PATH="$(cargo liner path)"`. It is emitted to standard output.
+    Path,
+
+    /// Add a package to the configuration and install it.
+    ///
+    /// The configuration file is edited in place, keeping its comments and
+    /// formatting. An already-configured package has its requirement replaced.
+    Add(Box<AddArgs>),
+
+    /// Remove a package from the configuration.
+    ///
+    /// The configuration file is edited in place, keeping its comments and
+    /// formatting. The package is left installed unless asked otherwise.
+    Remove(RemoveArgs),
+
     /// Import the `$CARGO_HOME/.crates.toml` Cargo-edited save file as a new
     /// Liner configuration file.
     ///
     /// Star versions are used by default. The version transformation options
-    /// are mutually exclusive.
+    /// are mutually exclusive. The features, target and profile each package
+    /// was installed with are also imported from the `.crates2.json` file
+    /// next to it, the simple form being kept for the packages installed
+    /// without any particular option.
     Import(ImportArgs),
 
+    /// Migrate the configuration file to the current layout.
+    ///
+    /// A file written for an older version of the layout is rewritten in
+    /// place, keeping its comments and formatting, and the changes are
+    /// emitted to standard output as a diff. Only the main configuration file
+    /// is migrated: use `--config` in order to migrate the other layers.
+    Migrate(MigrateArgs),
+
     /// Generate an auto-completion script for the given shell.
     ///
     /// The script is generated for `cargo-liner`, but with arguments rooted on
@@ -161,9 +263,19 @@ pub enum LinerCommands {
 }
 
 /// Arguments for the `ship` subcommand.
-#[derive(clap::Args, Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[derive(clap::Args, Serialize, Deserialize, JsonSchema, Debug, Default, Clone, PartialEq, Eq)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct ShipArgs {
+    /// Names of the configured packages to ship, all of them by default.
+    ///
+    /// When given, only these packages are installed or updated, in addition
+    /// to the ones selected using `--group`, and they are so even when in a
+    /// group excluded using `--exclude-group`. Self is then only updated if
+    /// given as well. Cannot be used in conjunction with `--only-self`.
+    #[arg(value_name = "PACKAGE", conflicts_with("only_self"))]
+    #[serde(skip)]
+    pub packages: Vec<String>,
+
     /// Disable self-updating.
     ///
     /// Cannot be used in conjunction with `--only-self`.
@@ -186,7 +298,8 @@ pub struct ShipArgs {
 
     /// Only self-update and do not install or update any other package.
     ///
-    /// Cannot be used in conjunction with `--no-self`.
+    /// This is the same as selecting only self by name. Cannot be used in
+    /// conjunction with `--no-self`, `--group` or package names.
     ///
     /// [default: false]
     ///
@@ -199,7 +312,7 @@ pub struct ShipArgs {
         num_args = 0,
         default_missing_value = "true",
         default_value_if("_no_only_self", ArgPredicate::IsPresent, "false"),
-        conflicts_with("no_self"),
+        conflicts_with_all(["no_self", "group"]),
         display_order = 3
     )]
     pub only_self: Option<bool>,
@@ -332,6 +445,98 @@ pub struct ShipArgs {
     #[arg(short, long, required = false, display_order = 13)]
     pub target: Option<String>,
 
+    /// Only ship the packages belonging to one of the given groups.
+    ///
+    /// Packages belong to the groups listed by their `groups` key and to the
+    /// ones of the `groups` section listing them. Each group must be defined
+    /// by at least one of them. The option can be given multiple times or
+    /// with comma-separated values, and adds up with the package names.
+    ///
+    /// [default: not set, i.e. all the packages]
+    ///
+    /// [env: `CARGO_LINER_SHIP_GROUP`]
+    ///
+    /// [config: `defaults.ship.group`]
+    #[arg(
+        short,
+        long,
+        required = false,
+        value_name = "GROUP",
+        value_delimiter = ',',
+        display_order = 14
+    )]
+    pub group: Option<Vec<String>>,
+
+    /// Do not ship the packages belonging to one of the given groups.
+    ///
+    /// This takes precedence over `--group`, but not over the package names.
+    /// The option can be given multiple times or with comma-separated values.
+    ///
+    /// [default: not set, i.e. none excluded]
+    ///
+    /// [env: `CARGO_LINER_SHIP_EXCLUDE_GROUP`]
+    ///
+    /// [config: `defaults.ship.exclude-group`]
+    #[arg(
+        long,
+        required = false,
+        value_name = "GROUP",
+        value_delimiter = ',',
+        display_order = 15
+    )]
+    pub exclude_group: Option<Vec<String>>,
+
+    /// Install the exact versions recorded in the lock file.
+    ///
+    /// The lock file found next to the configuration file and named after it,
+    /// e.g. `liner.lock` for `liner.toml`, records the version each package
+    /// was installed at, along with its source, commit, features and target,
+    /// as written by the previous runs of `ship`. Each package is then
+    /// installed at its locked version, or from its locked commit when sourced
+    /// from a Git repository, thus reproducing the same set of tools on every
+    /// machine. It fails if one of the packages to ship is not locked, and the
+    /// lock file is left untouched. Cannot be used in conjunction with
+    /// `--update-lock`.
+    ///
+    /// [default: false]
+    ///
+    /// [env: `CARGO_LINER_SHIP_LOCKED_CONFIG`]
+    ///
+    /// [config: `defaults.ship.locked-config`]
+    #[arg(
+        long,
+        num_args = 0,
+        default_missing_value = "true",
+        default_value_if("_no_locked_config", ArgPredicate::IsPresent, "false"),
+        conflicts_with("update_lock"),
+        display_order = 16
+    )]
+    pub locked_config: Option<bool>,
+
+    /// Refresh the lock file with the versions installed by the run.
+    ///
+    /// By default, only the shipped packages missing from the lock file are
+    /// added to it, the other ones keeping their locked version even when
+    /// updated, and the packages that are not configured anymore are dropped.
+    /// This records the currently-installed version of each shipped package
+    /// instead. The lock file is never written during a dry run or when
+    /// `--skip-check` is in effect. Cannot be used in conjunction with
+    /// `--locked-config`.
+    ///
+    /// [default: false]
+    ///
+    /// [env: `CARGO_LINER_SHIP_UPDATE_LOCK`]
+    ///
+    /// [config: `defaults.ship.update-lock`]
+    #[arg(
+        long,
+        num_args = 0,
+        default_missing_value = "true",
+        default_value_if("_no_update_lock", ArgPredicate::IsPresent, "false"),
+        display_order = 18
+    )]
+    pub update_lock: Option<bool>,
+
     /// Control the usage of `cargo-binstall`.
     ///
     /// This third-party tool has dedicated support here. It is meant to be
@@ -353,10 +558,48 @@ pub struct ShipArgs {
         display_order = 50
     )]
     pub binstall: Option<BinstallChoice>,
+
+    /// Number of packages to install or update in parallel.
+    ///
+    /// By default, packages are installed one after the other. When more
+    /// than one job is requested, several calls to `cargo install` or `cargo
+    /// binstall` are run at once instead, each one with its output captured
+    /// and only displayed when it is done so that outputs do not interleave:
+    /// its colors are kept, but not its progress bars. Each call still uses
+    /// Cargo's own parallelism for its build as usual. The fail-fast behavior
+    /// stops starting new installations after the first error, but lets the
+    /// ones already running finish and reports them as well.
+    ///
+    /// [default: 1]
+    ///
+    /// [env: `CARGO_LINER_SHIP_JOBS`]
+    ///
+    /// [config: `defaults.ship.jobs`]
+    #[arg(short, long, required = false, value_name = "N", display_order = 51)]
+    pub jobs: Option<NonZeroUsize>,
+
+    /// Maximum number of version lookups to run in parallel.
+    ///
+    /// This caps the number of registry index files fetched and Git
+    /// repositories listed at once when checking for new versions, which may
+    /// be needed in order to avoid hitting rate or file descriptor limits with
+    /// many configured packages. When `--no-fail-fast` is in effect, the
+    /// packages whose lookup fails are warned about and considered unknown
+    /// instead of aborting the whole operation.
+    ///
+    /// [default: 16]
+    ///
+    /// [env: `CARGO_LINER_SHIP_LOOKUP_JOBS`]
+    ///
+    /// [config: `defaults.ship.lookup-jobs`]
+    #[arg(long, required = false, value_name = "N", display_order = 52)]
+    pub lookup_jobs: Option<NonZeroUsize>,
 }
 
 /// Choices for [`ShipArgs::binstall`].
-#[derive(Serialize, Deserialize, clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[derive(
+    Serialize, Deserialize, JsonSchema, clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq,
+)]
 #[serde(rename_all = "kebab-case")]
 pub enum BinstallChoice {
     /// The tool is heuristically detected and used if available.
@@ -467,6 +710,28 @@ pub struct ShipArgsWithNegations {
         display_order = 12
     )]
     _no_dry_run: (),
+
+    /// Negation of `--locked-config` that overrides it and restores the
+    /// default behavior as if absent, i.e. install the configured versions.
+    #[arg(
+        long,
+        required = false,
+        num_args = 0,
+        overrides_with = "locked_config",
+        display_order = 17
+    )]
+    _no_locked_config: (),
+
+    /// Negation of `--update-lock` that overrides it and restores the default
+    /// behavior as if absent, i.e. only lock the packages not locked yet.
+    #[arg(
+        long,
+        required = false,
+        num_args = 0,
+        overrides_with = "update_lock",
+        display_order = 19
+    )]
+    _no_update_lock: (),
 }
 
 impl AsRef<ShipArgs> for ShipArgsWithNegations {
@@ -477,7 +742,7 @@ impl AsRef<ShipArgs> for ShipArgsWithNegations {
 }
 
 /// Arguments for the `jettison` subcommand.
-#[derive(clap::Args, Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[derive(clap::Args, Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq, Eq)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct JettisonArgs {
     /// Disable the confirmation of removal.
@@ -603,6 +868,108 @@ impl AsRef<JettisonArgs> for JettisonArgsWithNegations {
     }
 }
 
+/// Package name with an optional version requirement, as `name[@req]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageSpec {
+    pub name: String,
+    /// Star requirement when omitted.
+    pub version: VersionReq,
+}
+
+impl FromStr for PackageSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, version) = s.split_once('@').unwrap_or((s, "*"));
+        if name.is_empty() {
+            return Err("the package name must not be empty".to_owned());
+        }
+        Ok(Self {
+            name: name.to_owned(),
+            version: VersionReq::parse(version)
+                .map_err(|err| format!("invalid version requirement {version:?}: {err}"))?,
+        })
+    }
+}
+
+/// Arguments for the `add` subcommand.
+#[derive(clap::Args, Debug, PartialEq, Eq)]
+pub struct AddArgs {
+    /// The package to add, optionally with a version requirement.
+    ///
+    /// Default requirement: `*`.
+    #[arg(value_name = "PKG[@REQ]")]
+    pub package: PackageSpec,
+
+    /// Comma-separated list of features to activate.
+    #[arg(short = 'F', long, value_delimiter = ',', value_name = "FEATURES")]
+    pub features: Vec<String>,
+
+    /// Activate all available features.
+    #[arg(long)]
+    pub all_features: bool,
+
+    /// Do not activate the `default` feature.
+    #[arg(long)]
+    pub no_default_features: bool,
+
+    /// Git repository URL to install the package from.
+    #[arg(long, value_name = "URL", conflicts_with_all(["path", "registry", "index"]))]
+    pub git: Option<String>,
+
+    /// Branch to use when installing from Git.
+    #[arg(long, requires = "git", conflicts_with_all(["tag", "rev"]))]
+    pub branch: Option<String>,
+
+    /// Tag to use when installing from Git.
+    #[arg(long, requires = "git", conflicts_with_all(["branch", "rev"]))]
+    pub tag: Option<String>,
+
+    /// Specific commit to use when installing from Git.
+    #[arg(long, requires = "git", conflicts_with_all(["branch", "tag"]))]
+    pub rev: Option<String>,
+
+    /// Filesystem path to a local package to install.
+    #[arg(long, conflicts_with_all(["registry", "index"]))]
+    pub path: Option<String>,
+
+    /// Registry to install the package from.
+    #[arg(long, conflicts_with = "index")]
+    pub registry: Option<String>,
+
+    /// Registry index to install the package from.
+    #[arg(long)]
+    pub index: Option<String>,
+
+    /// Only install the given binary. Can be used multiple times.
+    #[arg(long = "bin", value_name = "NAME")]
+    pub bins: Vec<String>,
+
+    /// Install with the package's `Cargo.lock`.
+    #[arg(long)]
+    pub locked: bool,
+
+    /// Only edit the configuration without installing the package.
+    ///
+    /// Default: `false`, i.e. ship the added package right away, but only it.
+    #[arg(short, long)]
+    pub no_install: bool,
+}
+
+/// Arguments for the `remove` subcommand.
+#[derive(clap::Args, Debug, PartialEq, Eq)]
+pub struct RemoveArgs {
+    /// The name of the package to remove.
+    #[arg(value_name = "PKG")]
+    pub package: String,
+
+    /// Also uninstall the package if it is installed.
+    ///
+    /// Default: `false`, i.e. only edit the configuration.
+    #[arg(short, long)]
+    pub uninstall: bool,
+}
+
 /// Arguments for the `import` subcommand.
 #[derive(clap::Args, Debug, PartialEq, Eq)]
 pub struct ImportArgs {
@@ -635,6 +1002,9 @@ pub struct ImportArgs {
 
     /// Overwrite the current configuration file if it already exists.
     ///
+    /// Comments and layout of the existing file are kept for the entries that
+    /// remain, unless the file is invalid, in which case it is replaced whole.
+    ///
     /// Default: `false`, i.e. return an error in case the file already exists.
     #[arg(short, long)]
     pub force: bool,
@@ -659,6 +1029,16 @@ pub struct ImportArgs {
     pub keep_local: bool,
 }
 
+/// Arguments for the `migrate` subcommand.
+#[derive(clap::Args, Debug, PartialEq, Eq)]
+pub struct MigrateArgs {
+    /// Only print the changes without writing them to the file.
+    ///
+    /// Default: `false`, i.e. overwrite the file with its migrated contents.
+    #[arg(short, long)]
+    pub dry_run: bool,
+}
+
 /// Arguments for the `completions` subcommand.
 #[derive(clap::Args, Debug, PartialEq, Eq)]
 pub struct CompletionsArgs {
@@ -687,9 +1067,11 @@ mod tests {
             args,
             CargoArgs::Liner(LinerArgs {
                 command: None,
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
         assert_eq!(args.liner().verbosity(), 0);
@@ -712,9 +1094,11 @@ mod tests {
                 args,
                 CargoArgs::Liner(LinerArgs {
                     command: None,
+                    config: None,
                     verbose: i as u8,
                     quiet: 0,
                     color: ColorChoice::Auto,
+                    message_format: MessageFormat::Human,
                 }),
             );
             assert_eq!(args.liner().verbosity(), i as i8);
@@ -738,9 +1122,11 @@ mod tests {
                 args,
                 CargoArgs::Liner(LinerArgs {
                     command: None,
+                    config: None,
                     verbose: 0,
                     quiet: i as u8,
                     color: ColorChoice::Auto,
+                    message_format: MessageFormat::Human,
                 }),
             );
             assert_eq!(args.liner().verbosity(), -(i as i8));
@@ -763,14 +1149,37 @@ mod tests {
                 CargoArgs::try_parse_from(["cargo", "liner", "--color", val_str]).unwrap(),
                 CargoArgs::Liner(LinerArgs {
                     command: None,
+                    config: None,
                     verbose: 0,
                     quiet: 0,
                     color: val,
+                    message_format: MessageFormat::Human,
                 }),
             );
         }
     }
 
+    #[test]
+    fn test_messageformat() {
+        for (val, val_str) in [
+            (MessageFormat::Human, "human"),
+            (MessageFormat::Json, "json"),
+        ] {
+            assert_eq!(
+                CargoArgs::try_parse_from(["cargo", "liner", "--message-format", val_str]).unwrap(),
+                CargoArgs::Liner(LinerArgs {
+                    command: None,
+                    config: None,
+                    verbose: 0,
+                    quiet: 0,
+                    color: ColorChoice::Auto,
+                    message_format: val,
+                }),
+            );
+        }
+        assert!(CargoArgs::try_parse_from(["cargo", "liner", "--message-format", "xml"]).is_err());
+    }
+
     #[test]
     fn test_ship() {
         assert_eq!(
@@ -778,6 +1187,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: None,
                         only_self: None,
                         skip_check: None,
@@ -785,7 +1195,13 @@ mod tests {
                         force: None,
                         dry_run: None,
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: None,
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -793,10 +1209,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -808,6 +1228,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: Some(true),
                         only_self: None,
                         skip_check: None,
@@ -815,7 +1236,13 @@ mod tests {
                         force: None,
                         dry_run: None,
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: None,
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -823,10 +1250,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -839,6 +1270,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: Some(false),
                         only_self: None,
                         skip_check: None,
@@ -846,7 +1278,13 @@ mod tests {
                         force: None,
                         dry_run: None,
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: None,
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -854,10 +1292,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -869,6 +1311,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: None,
                         only_self: Some(true),
                         skip_check: None,
@@ -876,7 +1319,13 @@ mod tests {
                         force: None,
                         dry_run: None,
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: None,
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -884,10 +1333,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -900,6 +1353,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: None,
                         only_self: Some(false),
                         skip_check: None,
@@ -907,7 +1361,13 @@ mod tests {
                         force: None,
                         dry_run: None,
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: None,
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -915,10 +1375,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -930,6 +1394,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: None,
                         only_self: None,
                         skip_check: Some(true),
@@ -937,7 +1402,13 @@ mod tests {
                         force: None,
                         dry_run: None,
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: None,
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -945,10 +1416,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -967,6 +1442,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: None,
                         only_self: None,
                         skip_check: Some(false),
@@ -974,7 +1450,13 @@ mod tests {
                         force: None,
                         dry_run: None,
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: None,
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -982,10 +1464,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -997,6 +1483,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: None,
                         only_self: None,
                         skip_check: None,
@@ -1004,7 +1491,13 @@ mod tests {
                         force: None,
                         dry_run: None,
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: None,
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -1012,10 +1505,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1028,6 +1525,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: None,
                         only_self: None,
                         skip_check: None,
@@ -1035,7 +1533,13 @@ mod tests {
                         force: None,
                         dry_run: None,
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: None,
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -1043,10 +1547,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1058,6 +1566,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: None,
                         only_self: None,
                         skip_check: None,
@@ -1065,7 +1574,13 @@ mod tests {
                         force: Some(true),
                         dry_run: None,
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: None,
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -1073,10 +1588,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1088,6 +1607,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: None,
                         only_self: None,
                         skip_check: None,
@@ -1095,7 +1615,13 @@ mod tests {
                         force: Some(false),
                         dry_run: None,
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: None,
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -1103,10 +1629,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1118,6 +1648,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: None,
                         only_self: None,
                         skip_check: None,
@@ -1125,7 +1656,13 @@ mod tests {
                         force: None,
                         dry_run: Some(true),
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: None,
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -1133,10 +1670,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1149,6 +1690,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: None,
                         only_self: None,
                         skip_check: None,
@@ -1156,7 +1698,13 @@ mod tests {
                         force: None,
                         dry_run: Some(false),
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: None,
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -1164,10 +1712,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1186,6 +1738,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: None,
                         only_self: None,
                         skip_check: None,
@@ -1193,7 +1746,13 @@ mod tests {
                         force: None,
                         dry_run: None,
                         target: Some("reallyloongarch256-unknown-linux-musl".to_owned()),
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: None,
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -1201,10 +1760,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1216,6 +1779,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: None,
                         only_self: None,
                         skip_check: None,
@@ -1223,7 +1787,13 @@ mod tests {
                         force: None,
                         dry_run: None,
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: Some(BinstallChoice::Auto),
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -1231,10 +1801,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1246,6 +1820,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: None,
                         only_self: None,
                         skip_check: None,
@@ -1253,7 +1828,13 @@ mod tests {
                         force: None,
                         dry_run: None,
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: Some(BinstallChoice::Always),
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -1261,10 +1842,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1276,6 +1861,7 @@ mod tests {
             CargoArgs::Liner(LinerArgs {
                 command: Some(LinerCommands::Ship(ShipArgsWithNegations {
                     inner: ShipArgs {
+                        packages: Vec::new(),
                         no_self: None,
                         only_self: None,
                         skip_check: None,
@@ -1283,7 +1869,71 @@ mod tests {
                         force: None,
                         dry_run: None,
                         target: None,
+                        group: None,
+                        exclude_group: None,
+                        locked_config: None,
+                        update_lock: None,
                         binstall: Some(BinstallChoice::Never),
+                        jobs: None,
+                        lookup_jobs: None,
+                    },
+                    _with_self: (),
+                    _no_only_self: (),
+                    _no_skip_check: (),
+                    _fail_fast: (),
+                    _no_force: (),
+                    _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
+                })),
+                config: None,
+                verbose: 0,
+                quiet: 0,
+                color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ship_packages_groups() {
+        assert_eq!(
+            CargoArgs::try_parse_from([
+                "cargo",
+                "liner",
+                "ship",
+                "abc",
+                "def",
+                "-g",
+                "release,dev",
+                "--group",
+                "ci",
+                "--exclude-group",
+                "heavy",
+            ])
+            .unwrap(),
+            CargoArgs::Liner(LinerArgs {
+                command: Some(LinerCommands::Ship(ShipArgsWithNegations {
+                    inner: ShipArgs {
+                        packages: vec!["abc".to_owned(), "def".to_owned()],
+                        no_self: None,
+                        only_self: None,
+                        skip_check: None,
+                        no_fail_fast: None,
+                        force: None,
+                        dry_run: None,
+                        target: None,
+                        group: Some(vec![
+                            "release".to_owned(),
+                            "dev".to_owned(),
+                            "ci".to_owned(),
+                        ]),
+                        exclude_group: Some(vec!["heavy".to_owned()]),
+                        locked_config: None,
+                        update_lock: None,
+                        binstall: None,
+                        jobs: None,
+                        lookup_jobs: None,
                     },
                     _with_self: (),
                     _no_only_self: (),
@@ -1291,10 +1941,14 @@ mod tests {
                     _fail_fast: (),
                     _no_force: (),
                     _no_dry_run: (),
+                    _no_locked_config: (),
+                    _no_update_lock: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1307,6 +1961,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ship_onlyselfandselection_iserr() {
+        assert!(
+            CargoArgs::try_parse_from(["cargo", "liner", "ship", "--only-self", "abc"]).is_err()
+        );
+        assert!(
+            CargoArgs::try_parse_from(["cargo", "liner", "ship", "--only-self", "-g", "abc"])
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_jettison_noconfirm() {
         assert_eq!(
@@ -1322,9 +1987,11 @@ mod tests {
                     _fail_fast: (),
                     _no_dry_run: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1345,9 +2012,11 @@ mod tests {
                     _fail_fast: (),
                     _no_dry_run: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1367,9 +2036,11 @@ mod tests {
                     _fail_fast: (),
                     _no_dry_run: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1396,9 +2067,11 @@ mod tests {
                     _fail_fast: (),
                     _no_dry_run: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1418,9 +2091,11 @@ mod tests {
                     _fail_fast: (),
                     _no_dry_run: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1441,9 +2116,11 @@ mod tests {
                     _fail_fast: (),
                     _no_dry_run: (),
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1461,9 +2138,11 @@ mod tests {
                     keep_self: false,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1481,9 +2160,11 @@ mod tests {
                     keep_self: false,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1501,9 +2182,11 @@ mod tests {
                     keep_self: false,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1521,9 +2204,11 @@ mod tests {
                     keep_self: false,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1541,9 +2226,11 @@ mod tests {
                     keep_self: false,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1562,9 +2249,11 @@ mod tests {
                     keep_self: false,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1582,9 +2271,11 @@ mod tests {
                     keep_self: false,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1602,9 +2293,11 @@ mod tests {
                     keep_self: false,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1622,9 +2315,11 @@ mod tests {
                     keep_self: true,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1642,9 +2337,11 @@ mod tests {
                     keep_self: false,
                     keep_local: true,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1663,9 +2360,11 @@ mod tests {
                     keep_self: true,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1684,9 +2383,11 @@ mod tests {
                     keep_self: true,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1712,9 +2413,11 @@ mod tests {
                     keep_self: true,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1733,9 +2436,11 @@ mod tests {
                     keep_self: true,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1761,9 +2466,11 @@ mod tests {
                     keep_self: true,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1782,9 +2489,11 @@ mod tests {
                     keep_self: true,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1810,9 +2519,11 @@ mod tests {
                     keep_self: true,
                     keep_local: false,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             }),
         );
     }
@@ -1840,6 +2551,134 @@ mod tests {
         );
     }
 
+    fn add_args(package: PackageSpec) -> AddArgs {
+        AddArgs {
+            package,
+            features: Vec::new(),
+            all_features: false,
+            no_default_features: false,
+            git: None,
+            branch: None,
+            tag: None,
+            rev: None,
+            path: None,
+            registry: None,
+            index: None,
+            bins: Vec::new(),
+            locked: false,
+            no_install: false,
+        }
+    }
+
+    #[test]
+    fn test_add() {
+        assert_eq!(
+            CargoArgs::try_parse_from(["cargo", "liner", "add", "abc"]).unwrap(),
+            CargoArgs::Liner(LinerArgs {
+                command: Some(LinerCommands::Add(Box::new(add_args(PackageSpec {
+                    name: "abc".to_owned(),
+                    version: VersionReq::STAR,
+                })))),
+                config: None,
+                verbose: 0,
+                quiet: 0,
+                color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
+            }),
+        );
+    }
+
+    #[test]
+    fn test_add_options() {
+        assert_eq!(
+            CargoArgs::try_parse_from([
+                "cargo",
+                "liner",
+                "add",
+                "abc@~1.2",
+                "-F",
+                "a,b",
+                "--features",
+                "c",
+                "--no-default-features",
+                "--git",
+                "https://example.com/abc.git",
+                "--branch",
+                "dev",
+                "--bin",
+                "d",
+                "--locked",
+                "--no-install",
+            ])
+            .unwrap(),
+            CargoArgs::Liner(LinerArgs {
+                command: Some(LinerCommands::Add(Box::new(AddArgs {
+                    features: vec!["a".to_owned(), "b".to_owned(), "c".to_owned()],
+                    no_default_features: true,
+                    git: Some("https://example.com/abc.git".to_owned()),
+                    branch: Some("dev".to_owned()),
+                    bins: vec!["d".to_owned()],
+                    locked: true,
+                    no_install: true,
+                    ..add_args(PackageSpec {
+                        name: "abc".to_owned(),
+                        version: VersionReq::parse("~1.2").unwrap(),
+                    })
+                }))),
+                config: None,
+                verbose: 0,
+                quiet: 0,
+                color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
+            }),
+        );
+    }
+
+    #[test]
+    fn test_add_badspec_iserr() {
+        for spec in ["", "@1", "abc@", "abc@x.y"] {
+            assert!(CargoArgs::try_parse_from(["cargo", "liner", "add", spec]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_add_conflicts_iserr() {
+        for args in [
+            &["--branch", "dev"][..],
+            &["--git", "url", "--path", "path"],
+            &["--git", "url", "--tag", "t", "--rev", "r"],
+            &["--registry", "reg", "--index", "idx"],
+        ] {
+            assert!(
+                CargoArgs::try_parse_from(["cargo", "liner", "add", "abc"].iter().chain(args))
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_remove() {
+        assert_eq!(
+            CargoArgs::try_parse_from(["cargo", "liner", "remove", "abc", "--uninstall"]).unwrap(),
+            CargoArgs::Liner(LinerArgs {
+                command: Some(LinerCommands::Remove(RemoveArgs {
+                    package: "abc".to_owned(),
+                    uninstall: true,
+                })),
+                config: None,
+                verbose: 0,
+                quiet: 0,
+                color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
+            }),
+        );
+    }
+
+    #[test]
+    fn test_remove_noarg_iserr() {
+        assert!(CargoArgs::try_parse_from(["cargo", "liner", "remove"]).is_err());
+    }
+
     #[test]
     fn test_completions() {
         assert_eq!(
@@ -1848,9 +2687,11 @@ mod tests {
                 command: Some(LinerCommands::Completions(CompletionsArgs {
                     shell: Shell::Bash,
                 })),
+                config: None,
                 verbose: 0,
                 quiet: 0,
                 color: ColorChoice::Auto,
+                message_format: MessageFormat::Human,
             })
         );
     }
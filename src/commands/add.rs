@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use color_eyre::eyre::{self, Context};
+use color_eyre::{Result, Section};
+use toml_edit::{Item, Table};
+
+use crate::cli::{AddArgs, MessageFormat, ShipArgs};
+use crate::coloring::Colorizer;
+use crate::commands::ship;
+use crate::config::{DetailedPackageReq, EffectiveShipConfig, PackageRequirement, UserConfig};
+
+pub fn run(
+    args: &AddArgs,
+    config_path: Option<&Path>,
+    env_args: ShipArgs,
+    colorizer: &Colorizer,
+    cargo_verbosity: i8,
+    message_format: MessageFormat,
+) -> Result<()> {
+    let pkg_name = &args.package.name;
+    let pkg_req = PackageRequirement::from(package_requirement(args));
+    let pkg_value = pkg_req.to_toml_value()?;
+    log::info!("Adding {pkg_name:?} to the configuration...");
+
+    let user_config = UserConfig::edit_file(config_path, |doc| {
+        let pkgs = doc
+            .entry("packages")
+            .or_insert_with(|| Item::Table(Table::new()))
+            .as_table_like_mut()
+            .ok_or_else(|| eyre::eyre!("The `packages` section is not a table."))
+            .suggestion("Check the file for any typos and syntax errors.")?;
+
+        if pkgs.contains_key(pkg_name) {
+            log::warn!("{pkg_name:?} is already configured: its requirement will be replaced.");
+        }
+        // Keep the packages sorted if they already are.
+        let was_sorted = pkgs.iter().map(|(name, _)| name).is_sorted();
+        pkgs.insert(pkg_name, Item::Value(pkg_value));
+        if was_sorted {
+            pkgs.sort_values();
+        }
+        Ok(())
+    })
+    .wrap_err("Failed to edit the configuration file.")?;
+
+    if args.no_install {
+        log::warn!("Skipping the installation, as requested with `--no-install`.");
+        return Ok(());
+    }
+
+    // Only ship the added package, with the configured defaults.
+    ship::run(
+        EffectiveShipConfig::new(
+            UserConfig {
+                version: user_config.version,
+                strict: user_config.strict,
+                include: user_config.include,
+                exclude: user_config.exclude,
+                packages: BTreeMap::from([(pkg_name.clone(), pkg_req)]),
+                templates: user_config.templates,
+                groups: user_config.groups,
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
+                defaults: user_config.defaults,
+                root: user_config.root,
+            },
+            env_args,
+            ShipArgs {
+                no_self: Some(true),
+                only_self: Some(false),
+                locked_config: Some(false),
+                ..Default::default()
+            },
+        )?,
+        config_path,
+        colorizer,
+        cargo_verbosity,
+        message_format,
+    )
+}
+
+/// Builds the requirement described by the given arguments.
+fn package_requirement(args: &AddArgs) -> DetailedPackageReq {
+    DetailedPackageReq {
+        version: args.package.version.clone(),
+        default_features: args.no_default_features.then_some(false),
+        all_features: args.all_features.then_some(true),
+        features: args.features.clone(),
+        index: args.index.clone(),
+        registry: args.registry.clone(),
+        git: args.git.clone(),
+        branch: args.branch.clone(),
+        tag: args.tag.clone(),
+        rev: args.rev.clone(),
+        path: args.path.clone(),
+        bins: args.bins.clone(),
+        locked: args.locked.then_some(true),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,13 @@
+use std::path::Path;
+
+use color_eyre::Result;
+
+use crate::config::UserConfig;
+
+/// Validates the configuration file, failing with every issue found.
+pub fn run(config_path: Option<&Path>) -> Result<()> {
+    log::info!("Checking the configuration file...");
+    UserConfig::check_file(config_path)?;
+    log::info!("No issue found.");
+    Ok(())
+}
@@ -1,12 +1,21 @@
+use std::path::Path;
+
 use color_eyre::eyre::Context;
 use color_eyre::{Result, Section, eyre};
 
-use crate::cli::ImportArgs;
-use crate::config::{CargoCratesToml, UserConfig};
+use crate::cargo;
+use crate::cli::{ImportArgs, MessageFormat};
+use crate::config::{CargoCrates2Json, CargoCratesToml, PackageRequirement, UserConfig};
+use crate::message::Message;
 
-pub fn run(args: &ImportArgs) -> Result<()> {
-    if UserConfig::file_path()
-        .wrap_err("Failed to build the configuration file path.")?
+pub fn run(
+    args: &ImportArgs,
+    config_path: Option<&Path>,
+    message_format: MessageFormat,
+) -> Result<()> {
+    let (path, root) = UserConfig::location(config_path)
+        .wrap_err("Failed to build the configuration file path.")?;
+    if path
         .try_exists()
         .wrap_err("Failed to check if the configuration file exists.")
         .suggestion("Check the permissions of the Cargo home directory.")?
@@ -14,18 +23,16 @@ pub fn run(args: &ImportArgs) -> Result<()> {
         if args.force {
             log::warn!("Configuration file will be overwritten.");
         } else {
-            eyre::bail!("Configuration file already exists, use -f/--force to overwrite.");
+            eyre::bail!(
+                "Configuration file already exists at {path:?}, use -f/--force to overwrite."
+            );
         }
     }
 
     log::info!("Importing Cargo installed crates as a new configuration file...");
 
     // Clap conflict settings ensure the options are mutually exclusive.
-    (if args.force {
-        UserConfig::overwrite_file
-    } else {
-        UserConfig::save_file
-    })(&(if args.exact {
+    let mut config = (if args.exact {
         CargoCratesToml::into_exact_version_config
     } else if args.compatible {
         CargoCratesToml::into_comp_version_config
@@ -34,9 +41,49 @@ pub fn run(args: &ImportArgs) -> Result<()> {
     } else {
         CargoCratesToml::into_star_version_config
     })(
-        CargoCratesToml::parse_file().wrap_err("Failed to parse Cargo's .crates.toml file.")?,
+        CargoCratesToml::parse_file(root.as_deref())
+            .wrap_err("Failed to parse Cargo's .crates.toml file.")?,
         args.keep_self,
         args.keep_local,
-    ))
-    .wrap_err("Failed to save the configuration file.")
+    );
+
+    log::debug!("Importing the options the packages were installed with...");
+    let mut infos = CargoCrates2Json::parse_file(root.as_deref())
+        .wrap_err("Failed to parse Cargo's .crates2.json file.")?
+        .into_name_infos();
+    // Only needed in order to compare the recorded targets to it.
+    let host = infos
+        .values()
+        .any(|info| info.target.is_some())
+        .then(|| {
+            cargo::rustc_host()
+                .inspect_err(|err| log::debug!("Could not get the host target: {err}"))
+                .ok()
+        })
+        .flatten();
+    for (pkg_name, pkg_req) in &mut config.packages {
+        if let PackageRequirement::Simple(ver_req) = pkg_req
+            && let Some(info) = infos.remove(pkg_name)
+        {
+            *pkg_req = info.into_requirement(ver_req.clone(), host.as_deref());
+        }
+    }
+
+    (if args.force {
+        UserConfig::overwrite_file
+    } else {
+        UserConfig::save_file
+    })(&config, Some(&path))
+    .wrap_err("Failed to save the configuration file.")?;
+
+    if message_format == MessageFormat::Json {
+        for (pkg_name, pkg_req) in &config.packages {
+            Message::Import {
+                package: pkg_name,
+                requirement: pkg_req,
+            }
+            .emit()?;
+        }
+    }
+    Ok(())
 }
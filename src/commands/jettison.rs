@@ -1,28 +1,36 @@
 use std::collections::BTreeMap;
 use std::io::{self, BufRead};
+use std::path::Path;
 
 use clap::ColorChoice;
-use color_eyre::eyre::Context;
+use color_eyre::eyre::{Context, eyre};
 use color_eyre::{Result, Section};
 use semver::Version;
 use tabled::Tabled;
 
 use crate::cargo;
+use crate::cli::MessageFormat;
 use crate::commands::styled_table;
-use crate::config::{CargoCratesToml, DetailedPackageReq, EffectiveJettisonConfig};
+use crate::config::{CargoCratesToml, DetailedPackageReq, EffectiveJettisonConfig, UserConfig};
+use crate::message::Message;
 
 pub fn run(
     config: &EffectiveJettisonConfig,
+    config_path: Option<&Path>,
     cargo_color: ColorChoice,
     cargo_verbosity: i8,
+    message_format: MessageFormat,
 ) -> Result<()> {
     let to_uninstall = needing_uninstall(
-        CargoCratesToml::parse_file()
+        CargoCratesToml::parse_file(config.root.as_deref())
             .wrap_err("Failed to parse Cargo's .crates.toml file.")?
             .into_name_versions(),
         &config.packages,
     );
     log_uninstallation_plan(&to_uninstall);
+    if message_format == MessageFormat::Json {
+        emit_uninstallation_plan(&to_uninstall)?;
+    }
 
     // Nothing to do in this case anyway, so cut short.
     if to_uninstall.is_empty() {
@@ -38,6 +46,12 @@ pub fn run(
                 "--dry-run"
             },
         );
+    } else if config_path.is_some_and(UserConfig::is_stdin) {
+        // The confirmation would otherwise only read the end of the input.
+        return Err(eyre!(
+            "Cannot ask for confirmation as the configuration is read from the standard input."
+        ))
+        .suggestion("Use `--no-confirm` or `--dry-run` in order to skip the confirmation.");
     } else if ask_confirmation().wrap_err("Failed to ask for interactive confirmation.")? {
         log::info!("Aborting.");
         return Ok(());
@@ -45,6 +59,7 @@ pub fn run(
 
     cargo::uninstall_all(
         to_uninstall.into_keys(),
+        config.root.as_deref(),
         config.args.no_fail_fast,
         config.args.dry_run,
         cargo_color,
@@ -65,7 +80,7 @@ pub fn run(
 
 /// Computes the package names that need uninstallation: all those installed
 /// but not part of the user-configured ones.
-fn needing_uninstall(
+pub fn needing_uninstall(
     mut installed: BTreeMap<String, Version>,
     configured: &BTreeMap<String, DetailedPackageReq>,
 ) -> BTreeMap<String, Version> {
@@ -93,13 +108,25 @@ fn log_uninstallation_plan(to_uninstall: &BTreeMap<String, Version>) {
     }
 }
 
+/// Prints the packages that will be removed as machine-readable messages.
+pub fn emit_uninstallation_plan(to_uninstall: &BTreeMap<String, Version>) -> Result<()> {
+    for (pkg, ver) in to_uninstall {
+        Message::UninstallPlan {
+            package: pkg,
+            version: ver,
+        }
+        .emit()?;
+    }
+    Ok(())
+}
+
 /// [`Tabled`] for logging packages.
 #[derive(Tabled)]
-struct PackageEntry {
+pub struct PackageEntry {
     #[tabled(rename = "Name")]
-    name: String,
+    pub name: String,
     #[tabled(rename = "Version")]
-    version: String,
+    pub version: String,
 }
 
 /// Interactively asks for user confirmation of this operation.
@@ -0,0 +1,213 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::Path;
+
+use color_eyre::eyre::WrapErr;
+use color_eyre::{Result, Section};
+
+use crate::cli::MigrateArgs;
+use crate::config::UserConfig;
+
+/// Number of unchanged lines shown around the changed ones in the diff.
+const DIFF_CONTEXT: usize = 3;
+
+/// Migrates the configuration file to the current layout and prints the
+/// changes as a unified diff to the standard output.
+pub fn run(args: &MigrateArgs, config_path: Option<&Path>) -> Result<()> {
+    log::info!("Migrating the configuration file...");
+    let path = UserConfig::file_path(config_path)
+        .wrap_err("Failed to build the configuration file path.")?;
+    let (old_str, new_str, summaries) = UserConfig::migrate_file(config_path, args.dry_run)
+        .wrap_err("Failed to migrate the configuration file.")?;
+
+    if summaries.is_empty() {
+        log::info!("The configuration file is already up to date.");
+        return Ok(());
+    }
+    for summary in summaries {
+        log::info!("Applied migration: {summary}.");
+    }
+    if args.dry_run {
+        log::warn!("Dry run: the configuration file is left untouched.");
+    }
+
+    io::stdout()
+        .lock()
+        .write_all(unified_diff(&path, &old_str, &new_str).as_bytes())
+        .wrap_err("Failed to write the diff to the standard output.")
+        .suggestion("Check that the standard output is still open.")
+}
+
+/// Operation of an edit script turning some lines into others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Keep,
+    Delete,
+    Insert,
+}
+
+impl Edit {
+    /// Returns the prefix of the lines of the operation in a unified diff.
+    fn prefix(self) -> char {
+        match self {
+            Self::Keep => ' ',
+            Self::Delete => '-',
+            Self::Insert => '+',
+        }
+    }
+}
+
+/// Returns the shortest edit script turning the old lines into the new ones,
+/// based on their longest common subsequence.
+fn edit_script<'line>(old: &[&'line str], new: &[&'line str]) -> Vec<(Edit, &'line str)> {
+    // Lengths of the longest common subsequences of all the suffixes.
+    let mut lcs = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut edits = Vec::with_capacity(old.len().max(new.len()));
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            edits.push((Edit::Keep, old[i]));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            edits.push((Edit::Delete, old[i]));
+            i += 1;
+        } else {
+            edits.push((Edit::Insert, new[j]));
+            j += 1;
+        }
+    }
+    edits
+}
+
+/// Returns the differences between the given old and new contents of the
+/// file at the given path in the unified format, or nothing if there is none.
+fn unified_diff(path: &Path, old_str: &str, new_str: &str) -> String {
+    let old = old_str.lines().collect::<Vec<_>>();
+    let new = new_str.lines().collect::<Vec<_>>();
+    let edits = edit_script(&old, &new);
+    let changes = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, (edit, _))| *edit != Edit::Keep)
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut diff = format!("--- {}\n+++ {}\n", path.display(), path.display());
+    let count = |edits: &[(Edit, &str)], skipped: Edit| {
+        edits.iter().filter(|(edit, _)| *edit != skipped).count()
+    };
+    let mut idx = 0;
+    while idx < changes.len() {
+        // Hunks whose contexts would overlap are merged.
+        let start = changes[idx].saturating_sub(DIFF_CONTEXT);
+        while idx + 1 < changes.len() && changes[idx + 1] - changes[idx] <= 2 * DIFF_CONTEXT + 1 {
+            idx += 1;
+        }
+        let end = (changes[idx] + 1 + DIFF_CONTEXT).min(edits.len());
+        idx += 1;
+
+        let hunk = &edits[start..end];
+        let range = |line_start: usize, len: usize| {
+            format!(
+                "{},{len}",
+                if len == 0 { line_start } else { line_start + 1 }
+            )
+        };
+        let _ = writeln!(
+            diff,
+            "@@ -{} +{} @@",
+            range(
+                count(&edits[..start], Edit::Insert),
+                count(hunk, Edit::Insert)
+            ),
+            range(
+                count(&edits[..start], Edit::Delete),
+                count(hunk, Edit::Delete)
+            ),
+        );
+        for (edit, line) in hunk {
+            diff.push(edit.prefix());
+            diff.push_str(line);
+            diff.push('\n');
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_unifieddiff_same_isempty() {
+        assert_eq!(unified_diff(Path::new("liner.toml"), "", ""), "");
+        assert_eq!(
+            unified_diff(Path::new("liner.toml"), "a\nb\n", "a\nb\n"),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_unifieddiff_hunks() {
+        let old = (1..=20).map(|n| n.to_string() + "\n").collect::<String>();
+        let new = old
+            .lines()
+            .map(|line| match line {
+                "2" => "two\n".to_owned(),
+                "5" => String::new(),
+                "18" => "18\neighteen\n".to_owned(),
+                _ => line.to_owned() + "\n",
+            })
+            .collect::<String>();
+        assert_eq!(
+            unified_diff(Path::new("liner.toml"), &old, &new),
+            indoc!(
+                "
+                    --- liner.toml
+                    +++ liner.toml
+                    @@ -1,8 +1,7 @@
+                     1
+                    -2
+                    +two
+                     3
+                     4
+                    -5
+                     6
+                     7
+                     8
+                    @@ -16,5 +15,6 @@
+                     16
+                     17
+                     18
+                    +eighteen
+                     19
+                     20
+                "
+            ),
+        );
+    }
+
+    #[test]
+    fn test_unifieddiff_fromempty() {
+        assert_eq!(
+            unified_diff(Path::new("liner.toml"), "", "a\n"),
+            "--- liner.toml\n+++ liner.toml\n@@ -0,0 +1,1 @@\n+a\n",
+        );
+    }
+}
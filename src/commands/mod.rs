@@ -3,10 +3,17 @@
 use tabled::settings::Style;
 use tabled::{Table, Tabled};
 
+pub mod add;
+pub mod check;
 pub mod completions;
 pub mod import;
 pub mod jettison;
+pub mod migrate;
+pub mod path;
+pub mod remove;
+pub mod schema;
 pub mod ship;
+pub mod status;
 
 /// Builds a [`Table`] from the given iterator with a default style.
 fn styled_table(iter: impl IntoIterator<Item = impl Tabled>) -> Table {
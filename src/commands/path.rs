@@ -0,0 +1,37 @@
+use std::io::{self, Write};
+use std::path::Path;
+use std::{env, iter};
+
+use color_eyre::eyre::WrapErr;
+use color_eyre::{Result, Section};
+
+use crate::config::{CargoCratesToml, UserConfig};
+
+/// Name of the directory of the installation root holding the binaries.
+const BIN_DIR_NAME: &str = "bin";
+
+/// Prints the `PATH` to use in order to run the installed packages to the
+/// standard output.
+pub fn run(config_path: Option<&Path>) -> Result<()> {
+    let (_, root) = UserConfig::location(config_path)
+        .wrap_err("Failed to build the configuration file path.")?;
+    let bin_dir = CargoCratesToml::file_path(root.as_deref())
+        .wrap_err("Failed to build the installation root path.")?
+        .with_file_name(BIN_DIR_NAME);
+    log::debug!("Binaries directory: {bin_dir:#?}.");
+
+    let cur_paths = env::var_os("PATH").unwrap_or_default();
+    let path = env::join_paths(
+        iter::once(bin_dir.clone())
+            .chain(env::split_paths(&cur_paths).filter(|path| path != &bin_dir)),
+    )
+    .wrap_err("Failed to build the new `PATH`.")
+    .suggestion("Check that the `PATH` environment variable is well-formed.")?;
+
+    let mut stdout = io::stdout().lock();
+    stdout
+        .write_all(path.as_encoded_bytes())
+        .and_then(|()| writeln!(stdout))
+        .wrap_err("Failed to write the `PATH` to the standard output.")
+        .suggestion("Check that the standard output is still open.")
+}
@@ -0,0 +1,54 @@
+use std::path::Path;
+
+use clap::ColorChoice;
+use color_eyre::eyre::{self, Context};
+use color_eyre::{Result, Section};
+use toml_edit::Item;
+
+use crate::cargo;
+use crate::cli::RemoveArgs;
+use crate::config::{CargoCratesToml, UserConfig};
+
+pub fn run(
+    args: &RemoveArgs,
+    config_path: Option<&Path>,
+    cargo_color: ColorChoice,
+    cargo_verbosity: i8,
+) -> Result<()> {
+    let pkg_name = &args.package;
+    log::info!("Removing {pkg_name:?} from the configuration...");
+
+    let user_config = UserConfig::edit_file(config_path, |doc| {
+        doc.get_mut("packages")
+            .and_then(Item::as_table_like_mut)
+            .and_then(|pkgs| pkgs.remove(pkg_name))
+            .ok_or_else(|| eyre::eyre!("Package {pkg_name:?} is not configured."))
+            .suggestion("Check the spelling of the package name.")
+            .suggestion(
+                "If it is configured by another configuration file, list it under `exclude` instead.",
+            )
+            .map(|_| ())
+    })
+    .wrap_err("Failed to edit the configuration file.")?;
+
+    if args.uninstall {
+        if CargoCratesToml::parse_file(user_config.root.as_deref())
+            .wrap_err("Failed to parse Cargo's .crates.toml file.")?
+            .into_name_versions()
+            .contains_key(pkg_name)
+        {
+            cargo::uninstall_all(
+                [pkg_name],
+                user_config.root.as_deref(),
+                false,
+                false,
+                cargo_color,
+                cargo_verbosity,
+            )?;
+        } else {
+            log::warn!("{pkg_name:?} is not installed: nothing to uninstall.");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use std::io::{self, Write};
+
+use color_eyre::eyre::WrapErr;
+use color_eyre::{Result, Section};
+use schemars::Schema;
+use schemars::generate::SchemaSettings;
+use schemars::transform::RecursiveTransform;
+use serde_json::Value;
+
+use crate::config::UserConfig;
+
+/// Prints the JSON Schema of the configuration file to the standard output.
+pub fn run() -> Result<()> {
+    log::info!("Generating the JSON Schema of the configuration...");
+    let schema = SchemaSettings::draft2020_12()
+        .with_transform(RecursiveTransform(remove_nulls))
+        .into_generator()
+        .into_root_schema_for::<UserConfig>();
+    let schema_str = serde_json::to_string_pretty(&schema)
+        .wrap_err("Failed to serialize the configuration schema.")
+        .note("This really should not happen.")
+        .suggestion(crate::OPEN_ISSUE_MSG)?;
+    writeln!(io::stdout().lock(), "{schema_str}")
+        .wrap_err("Failed to write the schema to the standard output.")
+        .suggestion("Check that the standard output is still open.")
+}
+
+/// Removes the `null` types and defaults from the given schema, as TOML has no
+/// such value: optional items are simply omitted instead.
+fn remove_nulls(schema: &mut Schema) {
+    /// Returns whether the value is or contains `null`.
+    fn has_null(value: &Value) -> bool {
+        match value {
+            Value::Null => true,
+            Value::Array(values) => values.iter().any(has_null),
+            Value::Object(map) => map.values().any(has_null),
+            _ => false,
+        }
+    }
+
+    let Some(map) = schema.as_object_mut() else {
+        return;
+    };
+    if map.get("default").is_some_and(has_null) {
+        map.remove("default");
+    }
+
+    if let Some(Value::Array(types)) = map.get_mut("type") {
+        types.retain(|ty| ty != "null");
+        if let [ty] = types.as_slice() {
+            let ty = ty.clone();
+            map.insert("type".to_owned(), ty);
+        }
+    }
+
+    if let Some(Value::Array(subs)) = map.get_mut("anyOf") {
+        subs.retain(|sub| sub.get("type").is_none_or(|ty| ty != "null"));
+        if let [Value::Object(sub)] = subs.as_slice() {
+            let sub = sub.clone();
+            map.remove("anyOf");
+            for (key, val) in sub {
+                map.entry(key).or_insert(val);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use schemars::json_schema;
+
+    use super::*;
+
+    #[test]
+    fn test_removenulls() {
+        let mut schema = json_schema!({
+            "type": ["boolean", "null"],
+            "default": null,
+        });
+        remove_nulls(&mut schema);
+        assert_eq!(schema, json_schema!({ "type": "boolean" }));
+
+        let mut schema = json_schema!({
+            "description": "Some choice.",
+            "anyOf": [{ "$ref": "#/$defs/Choice" }, { "type": "null" }],
+            "default": { "a": null, "b": true },
+        });
+        remove_nulls(&mut schema);
+        assert_eq!(
+            schema,
+            json_schema!({
+                "description": "Some choice.",
+                "$ref": "#/$defs/Choice",
+            }),
+        );
+
+        let mut schema = json_schema!({
+            "type": ["string", "integer"],
+            "default": 1,
+        });
+        let expected = schema.clone();
+        remove_nulls(&mut schema);
+        assert_eq!(schema, expected);
+    }
+}
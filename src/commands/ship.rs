@@ -1,17 +1,44 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::mem;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
 
-use color_eyre::Result;
-use color_eyre::eyre::Context;
-use semver::Version;
+use color_eyre::eyre::{Context, eyre};
+use color_eyre::{Result, Section};
+use semver::{Prerelease, Version, VersionReq};
 use tabled::Tabled;
 
 use crate::cargo::{self, InstallStatus};
+use crate::cli::MessageFormat;
 use crate::coloring::Colorizer;
 use crate::commands::styled_table;
-use crate::config::{CargoCratesToml, DetailedPackageReq, EffectiveShipConfig};
+use crate::config::{
+    CargoCrates2Json, CargoCratesToml, DetailedPackageReq, EffectiveShipConfig, InstallInfo,
+    LinerLock,
+};
+use crate::message::Message;
+use crate::registry::{self, IndexEntry};
+use crate::{git, local};
 
-pub fn run(config: &EffectiveShipConfig, colorizer: &Colorizer, cargo_verbosity: i8) -> Result<()> {
-    let (inst_res, old_vers, new_vers) = if config.args.skip_check {
+/// Number of version lookups run in parallel when not configured otherwise.
+const DEFAULT_LOOKUP_JOBS: usize = 16;
+
+pub fn run(
+    mut config: EffectiveShipConfig,
+    config_path: Option<&Path>,
+    colorizer: &Colorizer,
+    cargo_verbosity: i8,
+    message_format: MessageFormat,
+) -> Result<()> {
+    let lock_path =
+        LinerLock::file_path(config_path).wrap_err("Failed to build the lock file path.")?;
+    if config.args.locked_config {
+        config.packages = pin_packages(mem::take(&mut config.packages), lock_path.as_deref())
+            .wrap_err("Failed to apply the lock file.")?;
+    }
+    let config = &config;
+
+    let (inst_rep, check) = if config.args.skip_check {
         // Don't parse `.crates.toml` here: can be used as a workaround.
         (
             cargo::install_all(
@@ -21,58 +48,52 @@ pub fn run(config: &EffectiveShipConfig, colorizer: &Colorizer, cargo_verbosity:
                 config.args.force,
                 config.args.dry_run,
                 config.args.target.as_deref(),
+                config.root.as_deref(),
                 config.args.binstall,
+                config.args.jobs,
                 *colorizer.color(),
                 cargo_verbosity,
             ),
-            BTreeMap::new(),
-            BTreeMap::new(),
+            VersionCheck::default(),
         )
     } else {
-        let cct =
-            CargoCratesToml::parse_file().wrap_err("Failed to parse Cargo's .crates.toml file.")?;
-        let old_vers = cct.clone().into_name_versions();
-        let new_vers = cargo::search_exact_all(
-            &config
-                .packages
-                .iter()
-                .filter(|(_, pkg)| !pkg.effective_skip_check())
-                .map(|(name, _)| name.clone())
-                .collect::<Vec<_>>(),
-        )
-        .wrap_err("Failed to fetch the latest versions of the configured packages.")?;
-        log_version_check_summary(colorizer, &config.packages, &new_vers, &old_vers);
+        let (cct, check, to_install) = check(config, colorizer, message_format)?;
+        warn_about_selected_entries(&to_install, &check.new_entries);
 
         (
             cargo::install_all(
-                &needing_install(&config.packages, &new_vers, &old_vers),
+                &to_install,
                 &cct.into_names(),
                 config.args.no_fail_fast,
                 config.args.force,
                 config.args.dry_run,
                 config.args.target.as_deref(),
+                config.root.as_deref(),
                 config.args.binstall,
+                config.args.jobs,
                 *colorizer.color(),
                 cargo_verbosity,
             ),
-            old_vers,
-            new_vers,
+            check,
         )
     };
 
-    if let Some(err) = match inst_res {
-        Ok(rep) => {
-            log_install_report(
-                colorizer,
-                &rep.package_statuses,
-                &new_vers,
-                &old_vers,
-                config.args.dry_run,
-            );
-            rep.error_report
-        }
-        Err(err) => Some(err),
-    } {
+    log_install_report(
+        colorizer,
+        &inst_rep.package_statuses,
+        &check,
+        config.args.dry_run,
+    );
+    if message_format == MessageFormat::Json {
+        emit_install_messages(&inst_rep.package_statuses, &check, config.args.dry_run)?;
+    }
+    if let Some(lock_path) = &lock_path
+        && !(config.args.locked_config || config.args.dry_run || config.args.skip_check)
+    {
+        record_lock(config, lock_path).wrap_err("Failed to update the lock file.")?;
+    }
+
+    if let Some(err) = inst_rep.error_report {
         Err(err).wrap_err_with(|| {
             format!(
                 "Failed to install or update {} of the configured packages.",
@@ -87,21 +108,459 @@ pub fn run(config: &EffectiveShipConfig, colorizer: &Colorizer, cargo_verbosity:
 
     Ok(())
 }
+
+/// Pins each of the given packages to its version recorded in the lock file at
+/// the given path, failing if there is none or if one of them is not locked.
+fn pin_packages(
+    pkgs: BTreeMap<String, DetailedPackageReq>,
+    lock_path: Option<&Path>,
+) -> Result<BTreeMap<String, DetailedPackageReq>> {
+    let lock_path = lock_path
+        .ok_or_else(|| {
+            eyre!("There is no lock file as the configuration is read from the standard input.")
+        })
+        .suggestion("Use `--config` with the path to an actual file instead.")?;
+    let lock = LinerLock::parse_file(lock_path)?
+        .ok_or_else(|| eyre!("The lock file does not exist at {lock_path:?}."))
+        .suggestion("Run `cargo liner ship` without `--locked-config` in order to create it.")?;
+
+    pkgs.into_iter()
+        .map(|(pkg_name, pkg)| {
+            let pkg = lock.pin(&pkg_name, pkg).suggestion(
+                "Run `cargo liner ship` without `--locked-config` in order to lock it.",
+            )?;
+            Ok((pkg_name, pkg))
+        })
+        .collect()
+}
+
+/// Records the installed versions of the shipped packages in the lock file at
+/// the given path, creating it if needed.
+///
+/// Only the packages that are not locked yet are recorded, unless the lock is
+/// to be updated, in which case all of them are, and the ones that are not
+/// configured anymore are dropped. The file is left untouched if nothing
+/// changed.
+fn record_lock(config: &EffectiveShipConfig, lock_path: &Path) -> Result<()> {
+    let mut lock = LinerLock::parse_file(lock_path)?.unwrap_or_default();
+    let cct = CargoCratesToml::parse_file(config.root.as_deref())
+        .wrap_err("Failed to parse Cargo's .crates.toml file.")?;
+    let infos = CargoCrates2Json::parse_file(config.root.as_deref())
+        .wrap_err("Failed to parse Cargo's .crates2.json file.")?
+        .into_name_infos();
+
+    if lock.record(
+        &config.packages,
+        &config.configured,
+        cct,
+        infos,
+        config.args.update_lock,
+    ) {
+        lock.save_file(lock_path)
+    } else {
+        log::debug!("The lock file is already up to date.");
+        Ok(())
+    }
+}
+
+/// Checks the versions of the configured packages against the installed ones
+/// and displays the results.
+///
+/// Returns Cargo's document of installed packages, the gathered versions and
+/// the packages that need an install or update.
+pub fn check(
+    config: &EffectiveShipConfig,
+    colorizer: &Colorizer,
+    message_format: MessageFormat,
+) -> Result<(
+    CargoCratesToml,
+    VersionCheck,
+    BTreeMap<String, DetailedPackageReq>,
+)> {
+    let cct = CargoCratesToml::parse_file(config.root.as_deref())
+        .wrap_err("Failed to parse Cargo's .crates.toml file.")?;
+    let check = VersionCheck::gather(&cct, config)?;
+    log_version_check_summary(colorizer, &config.packages, &check);
+    if message_format == MessageFormat::Json {
+        emit_version_check_messages(&config.packages, &check)?;
+    }
+    let to_install = needing_install(&config.packages, &check);
+    Ok((cct, check, to_install))
+}
+
+/// Installed and available versions of the configured packages, as gathered
+/// in order to determine which ones need to be installed or updated.
+#[derive(Debug, Default)]
+pub struct VersionCheck {
+    /// Installed versions.
+    old_vers: BTreeMap<String, Version>,
+    /// Newest versions satisfying the configured requirements.
+    new_vers: BTreeMap<String, Version>,
+    /// Latest versions, regardless of the configured requirements.
+    latest_vers: BTreeMap<String, Version>,
+    /// Installed commits of packages installed from Git repositories.
+    old_commits: BTreeMap<String, String>,
+    /// Commits currently designated by the references of Git-sourced packages.
+    new_commits: BTreeMap<String, String>,
+    /// Canonical paths of the packages installed from local ones, for those
+    /// still existing.
+    old_paths: BTreeMap<String, PathBuf>,
+    /// Canonical paths of the locally-sourced packages.
+    new_paths: BTreeMap<String, PathBuf>,
+    /// Index entries of the newest versions satisfying the requirements.
+    new_entries: BTreeMap<String, IndexEntry>,
+    /// Names of the options that changed since the installation, for the
+    /// packages where at least one did.
+    changed_opts: BTreeMap<String, Vec<&'static str>>,
+    /// Packages whose lookup failed and was ignored due to `--no-fail-fast`.
+    failed_lookups: BTreeSet<String>,
+    /// Packages whose source does not support looking their versions up, thus
+    /// always installed as if their check was skipped.
+    unchecked: BTreeSet<String>,
+}
+
+impl VersionCheck {
+    /// Gathers the versions of the configured packages from their respective
+    /// sources, as well as the ones installed according to the given document.
+    fn gather(cct: &CargoCratesToml, config: &EffectiveShipConfig) -> Result<Self> {
+        let (pkgs, args) = (&config.packages, &config.args);
+        let jobs = args
+            .lookup_jobs
+            .map_or(DEFAULT_LOOKUP_JOBS, NonZeroUsize::get);
+        let (path_pkgs, remote_pkgs) = pkgs
+            .iter()
+            .filter(|(_, pkg)| !pkg.effective_skip_check())
+            .map(|(name, pkg)| (name.clone(), pkg.clone()))
+            .partition::<BTreeMap<_, _>, _>(|(_, pkg)| pkg.path.is_some());
+        let (git_pkgs, reg_pkgs) = remote_pkgs
+            .into_iter()
+            .partition::<BTreeMap<_, _>, _>(|(_, pkg)| pkg.git.is_some());
+        let fetched = registry::fetch_all(&reg_pkgs, jobs, args.no_fail_fast)
+            .wrap_err("Failed to fetch the latest versions of the configured packages.")?;
+        let index_entries = fetched.entries;
+        let new_commits = git::resolve_all(&git_pkgs, jobs, args.no_fail_fast)
+            .wrap_err("Failed to fetch the latest commits of the Git-sourced packages.")?;
+        let local_pkgs = local::read_all(&path_pkgs, args.no_fail_fast)
+            .wrap_err("Failed to read the versions of the locally-sourced packages.")?;
+        let failed_lookups = reg_pkgs
+            .into_keys()
+            .filter(|pkg_name| {
+                !index_entries.contains_key(pkg_name) && !fetched.unsupported.contains(pkg_name)
+            })
+            .chain(
+                git_pkgs
+                    .into_keys()
+                    .filter(|pkg_name| !new_commits.contains_key(pkg_name)),
+            )
+            .chain(
+                path_pkgs
+                    .into_keys()
+                    .filter(|pkg_name| !local_pkgs.contains_key(pkg_name)),
+            )
+            .collect();
+        let new_entries = matching_entries(pkgs, &index_entries);
+        Ok(Self {
+            old_vers: cct.clone().into_name_versions(),
+            new_vers: new_entries
+                .iter()
+                .map(|(pkg_name, entry)| (pkg_name.clone(), entry.version.clone()))
+                .chain(
+                    local_pkgs
+                        .iter()
+                        .map(|(pkg_name, local_pkg)| (pkg_name.clone(), local_pkg.version.clone())),
+                )
+                .collect(),
+            latest_vers: latest_versions(&index_entries),
+            old_commits: cct.clone().into_name_git_commits(),
+            new_commits,
+            // Canonicalize in order to compare with the configured paths.
+            old_paths: cct
+                .clone()
+                .into_name_paths()
+                .into_iter()
+                .filter_map(|(pkg_name, path)| {
+                    path.canonicalize().ok().map(|path| (pkg_name, path))
+                })
+                .collect(),
+            new_paths: local_pkgs
+                .into_iter()
+                .map(|(pkg_name, local_pkg)| (pkg_name, local_pkg.path))
+                .collect(),
+            new_entries: new_entries
+                .into_iter()
+                .map(|(pkg_name, entry)| (pkg_name, entry.clone()))
+                .collect(),
+            changed_opts: changed_options(
+                pkgs,
+                CargoCrates2Json::parse_file(config.root.as_deref())
+                    .wrap_err("Failed to parse Cargo's .crates2.json file.")?
+                    .into_name_infos(),
+                args.target.as_deref(),
+            ),
+            failed_lookups,
+            unchecked: fetched.unsupported,
+        })
+    }
+
+    /// Returns whether the given package is always installed: either its
+    /// check is skipped or its source does not support it.
+    pub fn is_unchecked(&self, pkg_name: &str, pkg_req: &DetailedPackageReq) -> bool {
+        pkg_req.effective_skip_check() || self.unchecked.contains(pkg_name)
+    }
+
+    /// Returns whether the given Git-sourced package is installed from the
+    /// commit its configured reference currently resolves to.
+    fn is_commit_up_to_date(&self, pkg_name: &str) -> bool {
+        self.old_commits
+            .get(pkg_name)
+            .zip(self.new_commits.get(pkg_name))
+            .is_some_and(|(old_commit, new_commit)| git::commit_matches(old_commit, new_commit))
+    }
+
+    /// Returns whether the given locally-sourced package is installed from
+    /// the same path and with the same version as the one found there.
+    fn is_local_up_to_date(&self, pkg_name: &str) -> bool {
+        self.old_paths
+            .get(pkg_name)
+            .is_some_and(|old_path| self.new_paths.get(pkg_name) == Some(old_path))
+            && self
+                .old_vers
+                .get(pkg_name)
+                .is_some_and(|old_ver| self.new_vers.get(pkg_name) == Some(old_ver))
+    }
+
+    /// Returns whether the given package is displayed as being up-to-date.
+    fn is_up_to_date(&self, pkg_name: &str, pkg_req: &DetailedPackageReq) -> bool {
+        let is_up_to_date = if self.is_unchecked(pkg_name, pkg_req) {
+            false
+        } else if pkg_req.path.is_some() {
+            self.is_local_up_to_date(pkg_name)
+        } else if pkg_req.git.is_some() {
+            self.is_commit_up_to_date(pkg_name)
+        } else {
+            // Up-to-date when no newer version satisfying the requirement is
+            // available and the installed one satisfies it as well.
+            self.latest_vers.contains_key(pkg_name)
+                && self.old_vers.get(pkg_name).is_some_and(|old_ver| {
+                    req_matches(&pkg_req.version, old_ver)
+                        && self
+                            .new_vers
+                            .get(pkg_name)
+                            .is_none_or(|new_ver| old_ver >= new_ver)
+                })
+        };
+        is_up_to_date && !self.changed_opts.contains_key(pkg_name)
+    }
+
+    /// Formats the installed version of the given package for display, using
+    /// its abbreviated commit instead when installed from a Git repository.
+    fn old_ver_str(&self, colorizer: &Colorizer, pkg_name: &str) -> String {
+        self.old_commits.get(pkg_name).map_or_else(
+            || {
+                self.old_vers
+                    .get(pkg_name)
+                    .map_or_else(|| colorizer.none_icon().to_string(), ToString::to_string)
+            },
+            |old_commit| git::short_commit(old_commit).to_owned(),
+        )
+    }
+
+    /// Formats the version to install of the given package for display, using
+    /// its abbreviated commit instead when sourced from a Git repository.
+    fn new_ver_str(&self, colorizer: &Colorizer, pkg_name: &str) -> String {
+        self.new_commits.get(pkg_name).map_or_else(
+            || {
+                self.new_vers
+                    .get(pkg_name)
+                    .map_or_else(|| colorizer.unknown_icon().to_string(), ToString::to_string)
+            },
+            |new_commit| git::short_commit(new_commit).to_owned(),
+        )
+    }
+}
+
+/// Returns the latest version of each package as found in the given index
+/// entries, ignoring yanked ones and preferring non-pre-release ones.
+fn latest_versions(index_entries: &BTreeMap<String, Vec<IndexEntry>>) -> BTreeMap<String, Version> {
+    index_entries
+        .iter()
+        .filter_map(|(pkg_name, entries)| {
+            let unyanked = entries.iter().filter(|entry| !entry.yanked);
+            unyanked
+                .clone()
+                .filter(|entry| entry.version.pre.is_empty())
+                .map(|entry| &entry.version)
+                .max()
+                .or_else(|| unyanked.map(|entry| &entry.version).max())
+                .map(|ver| (pkg_name.clone(), ver.clone()))
+        })
+        .collect()
+}
+
+/// Returns the newest index entry that satisfies the requirement of its
+/// package, for each package that has one, ignoring yanked ones.
+///
+/// Packages whose published versions all lie outside of their configured
+/// requirement are left out: `cargo install` would not be able to reach them.
+fn matching_entries<'e>(
+    pkgs: &BTreeMap<String, DetailedPackageReq>,
+    index_entries: &'e BTreeMap<String, Vec<IndexEntry>>,
+) -> BTreeMap<String, &'e IndexEntry> {
+    index_entries
+        .iter()
+        .filter_map(|(pkg_name, entries)| {
+            entries
+                .iter()
+                .filter(|entry| {
+                    !entry.yanked
+                        && pkgs
+                            .get(pkg_name)
+                            .is_none_or(|pkg| pkg.version.matches(&entry.version))
+                })
+                .max_by(|entry1, entry2| entry1.version.cmp(&entry2.version))
+                .map(|entry| (pkg_name.clone(), entry))
+        })
+        .collect()
+}
+
+/// Warns about the selected versions of the packages to install when they
+/// are known in advance to be probably failing.
+fn warn_about_selected_entries(
+    pkgs: &BTreeMap<String, DetailedPackageReq>,
+    new_entries: &BTreeMap<String, IndexEntry>,
+) {
+    let mut rustc_ver = None;
+
+    for (pkg_name, pkg) in pkgs {
+        let Some(entry) = new_entries.get(pkg_name) else {
+            continue;
+        };
+
+        for feat in pkg
+            .features
+            .iter()
+            .filter(|feat| !entry.features.contains_key(*feat))
+        {
+            log::warn!(
+                "Feature `{feat}` is not defined by `{pkg_name}` v{}: its installation will \
+                 probably fail.",
+                entry.version,
+            );
+        }
+
+        if let Some(rust_ver) = &entry.rust_version
+            && let Some(rustc_ver) = rustc_ver
+                .get_or_insert_with(|| {
+                    cargo::rustc_version()
+                        .inspect_err(|err| log::debug!("Could not get the version of Rust: {err}"))
+                        .ok()
+                })
+                .as_ref()
+            // Consider nightly toolchains as their release counterpart.
+            && without_pre(rustc_ver) < *rust_ver
+        {
+            log::warn!(
+                "`{pkg_name}` v{} requires Rust {rust_ver} while {rustc_ver} is active: its \
+                 installation will probably fail.",
+                entry.version,
+            );
+        }
+    }
+}
+
+/// Returns the names of the options that changed since the installation of
+/// each package, when any did, according to the given installation infos.
+///
+/// `target` is the global one, used for packages without their own.
+fn changed_options(
+    pkgs: &BTreeMap<String, DetailedPackageReq>,
+    old_infos: BTreeMap<String, InstallInfo>,
+    target: Option<&str>,
+) -> BTreeMap<String, Vec<&'static str>> {
+    let mut host = None;
+    let mut pkg_changes = BTreeMap::new();
+
+    for (pkg_name, info) in old_infos {
+        let Some(pkg) = pkgs
+            .get(&pkg_name)
+            .filter(|pkg| !pkg.effective_skip_check())
+        else {
+            continue;
+        };
+        let target = pkg.target.as_deref().or(target).or_else(|| {
+            // Only get the default target when it is needed.
+            info.target.as_ref()?;
+            host.get_or_insert_with(|| {
+                cargo::rustc_host()
+                    .inspect_err(|err| log::debug!("Could not get the host target: {err}"))
+                    .ok()
+            })
+            .as_deref()
+        });
+        let changes = info.changed_options(pkg, target);
+
+        if !changes.is_empty() {
+            log::debug!("Options of {pkg_name:?} changed: {changes:?}.");
+            pkg_changes.insert(pkg_name, changes);
+        }
+    }
+
+    pkg_changes
+}
+
+/// Returns whether the given version satisfies the given requirement.
+///
+/// Contrary to [`VersionReq::matches`], pre-release versions are considered
+/// as their release counterpart in order for them to match usual requirements
+/// such as `*`, thus avoiding to re-install them endlessly.
+fn req_matches(req: &VersionReq, ver: &Version) -> bool {
+    req.matches(ver) || (!ver.pre.is_empty() && req.matches(&without_pre(ver)))
+}
+
+/// Returns a copy of the given version with its pre-release part removed.
+fn without_pre(ver: &Version) -> Version {
+    Version {
+        pre: Prerelease::EMPTY,
+        ..ver.clone()
+    }
+}
+
 /// Returns the packages that do indeed need an install or update.
 fn needing_install(
     pkgs: &BTreeMap<String, DetailedPackageReq>,
-    new_vers: &BTreeMap<String, Version>,
-    old_vers: &BTreeMap<String, Version>,
+    check: &VersionCheck,
 ) -> BTreeMap<String, DetailedPackageReq> {
     let mut to_install = BTreeMap::new();
     log::debug!("Filtering packages by versions...");
 
     for (pkg_name, pkg) in pkgs {
-        if pkg.effective_skip_check()
-            || old_vers
-                .get(pkg_name)
-                // UNWRAP: `pkg.effective_skip_check()` was just checked for.
-                .is_none_or(|ver| ver < new_vers.get(pkg_name).unwrap())
+        // Nothing is known about it: only install it when missing.
+        if check.failed_lookups.contains(pkg_name) && check.old_vers.contains_key(pkg_name) {
+            log::trace!("{pkg_name:?} is not selected: its lookup failed.");
+            continue;
+        }
+
+        if check.is_unchecked(pkg_name, pkg)
+            || check.changed_opts.contains_key(pkg_name)
+            || if pkg.path.is_some() {
+                // Installed from elsewhere or with another version.
+                !check.is_local_up_to_date(pkg_name)
+            } else if pkg.git.is_some() {
+                // Installed from elsewhere or from another commit.
+                !check.is_commit_up_to_date(pkg_name)
+            } else {
+                check.old_vers.get(pkg_name).is_none_or(|old_ver| {
+                    // The installed version may not satisfy the requirement
+                    // anymore, for example if it was modified to pin an older
+                    // one.
+                    !req_matches(&pkg.version, old_ver)
+                        // No new version means the latest one is out of reach.
+                        || check
+                            .new_vers
+                            .get(pkg_name)
+                            .is_some_and(|new_ver| old_ver < new_ver)
+                })
+            }
         {
             to_install.insert(pkg_name.clone(), pkg.clone());
             log::trace!("{pkg_name:?} is selected to be installed or updated.");
@@ -118,8 +577,7 @@ fn needing_install(
 fn log_version_check_summary(
     colorizer: &Colorizer,
     pkg_reqs: &BTreeMap<String, DetailedPackageReq>,
-    new_vers: &BTreeMap<String, Version>,
-    old_vers: &BTreeMap<String, Version>,
+    check: &VersionCheck,
 ) {
     if pkg_reqs.is_empty() {
         log::info!("No package to install: none was configured and self was skipped.");
@@ -129,32 +587,45 @@ fn log_version_check_summary(
             // Iterate on `pkg_reqs` in order to get the package names: due to
             // the possibility of a partial `skip-check`, `old_vers U new_vers`
             // may not contain every one of them.
-            styled_table(pkg_reqs.keys().map(|pkg_name| {
-                let old_ver = old_vers.get(pkg_name);
-                let new_ver = new_vers.get(pkg_name);
+            styled_table(pkg_reqs.iter().map(|(pkg_name, pkg_req)| {
+                let latest_ver = check.latest_vers.get(pkg_name);
+                let changed_opts = check.changed_opts.get(pkg_name);
+                let is_up_to_date = check.is_up_to_date(pkg_name, pkg_req);
+
                 PackageStatus {
                     name: pkg_name.clone(),
-                    old_ver: old_ver
-                        .map_or_else(|| colorizer.none_icon().to_string(), ToString::to_string),
-                    // This should be equivalent to checking `effective_skip_check`:
-                    // a new version is unavailable only if not fetched.
-                    new_ver: new_ver.map_or_else(
-                        || colorizer.unknown_icon().to_string(),
-                        |new_ver| {
-                            old_ver
-                                .and_then(|old_ver| {
-                                    (old_ver >= new_ver).then(|| colorizer.none_icon().to_string())
-                                })
-                                .unwrap_or_else(|| new_ver.to_string())
-                        },
-                    ),
-                    status: new_ver
-                        .and_then(|new_ver| {
-                            old_ver.and_then(|old_ver| {
-                                (old_ver >= new_ver).then(|| colorizer.ok_icon().to_string())
-                            })
-                        })
-                        .unwrap_or_else(|| colorizer.todo_icon().to_string()),
+                    old_ver: check.old_ver_str(colorizer, pkg_name),
+                    new_ver: {
+                        // A latest version is unavailable only if not fetched:
+                        // this should be equivalent to `effective_skip_check`.
+                        let mut new_ver_str = if is_up_to_date {
+                            colorizer.none_icon().to_string()
+                        } else {
+                            check.new_ver_str(colorizer, pkg_name)
+                        };
+
+                        // Mention the latest version when out of reach.
+                        if let Some(latest_ver) = latest_ver
+                            && check.new_vers.get(pkg_name) != Some(latest_ver)
+                        {
+                            new_ver_str = format!("{new_ver_str} (latest: {latest_ver})");
+                        }
+
+                        // Mention why a reinstallation is needed.
+                        if let Some(changed_opts) = changed_opts {
+                            new_ver_str =
+                                format!("{new_ver_str} (changed: {})", changed_opts.join(", "));
+                        }
+
+                        new_ver_str
+                    },
+                    status: if is_up_to_date {
+                        colorizer.ok_icon().to_string()
+                    } else if check.failed_lookups.contains(pkg_name) {
+                        colorizer.unknown_icon().to_string()
+                    } else {
+                        colorizer.todo_icon().to_string()
+                    },
                 }
             })),
         );
@@ -165,8 +636,7 @@ fn log_version_check_summary(
 fn log_install_report(
     colorizer: &Colorizer,
     install_report: &BTreeMap<String, InstallStatus>,
-    new_vers: &BTreeMap<String, Version>,
-    old_vers: &BTreeMap<String, Version>,
+    check: &VersionCheck,
     dry_run: bool,
 ) {
     if !install_report.is_empty() {
@@ -175,12 +645,8 @@ fn log_install_report(
             styled_table(install_report.iter().map(|(pkg_name, status)| {
                 PackageStatus {
                     name: pkg_name.clone(),
-                    old_ver: old_vers
-                        .get(pkg_name)
-                        .map_or_else(|| colorizer.none_icon().to_string(), ToString::to_string),
-                    new_ver: new_vers
-                        .get(pkg_name)
-                        .map_or_else(|| colorizer.unknown_icon().to_string(), ToString::to_string),
+                    old_ver: check.old_ver_str(colorizer, pkg_name),
+                    new_ver: check.new_ver_str(colorizer, pkg_name),
                     status: match status {
                         InstallStatus::Installed => colorizer.new_icon().to_string(),
                         InstallStatus::Updated => colorizer.ok_icon().to_string(),
@@ -196,6 +662,48 @@ fn log_install_report(
     }
 }
 
+/// Prints the results of the version check as machine-readable messages.
+fn emit_version_check_messages(
+    pkg_reqs: &BTreeMap<String, DetailedPackageReq>,
+    check: &VersionCheck,
+) -> Result<()> {
+    for (pkg_name, pkg_req) in pkg_reqs {
+        Message::VersionCheck {
+            package: pkg_name,
+            old_version: check.old_vers.get(pkg_name),
+            new_version: check.new_vers.get(pkg_name),
+            latest_version: check.latest_vers.get(pkg_name),
+            old_commit: check.old_commits.get(pkg_name).map(String::as_str),
+            new_commit: check.new_commits.get(pkg_name).map(String::as_str),
+            changed_options: check.changed_opts.get(pkg_name).map_or(&[], Vec::as_slice),
+            up_to_date: check.is_up_to_date(pkg_name, pkg_req),
+        }
+        .emit()?;
+    }
+    Ok(())
+}
+
+/// Prints each package's installation status as machine-readable messages.
+fn emit_install_messages(
+    install_report: &BTreeMap<String, InstallStatus>,
+    check: &VersionCheck,
+    dry_run: bool,
+) -> Result<()> {
+    for (pkg_name, status) in install_report {
+        Message::InstallStatus {
+            package: pkg_name,
+            old_version: check.old_vers.get(pkg_name),
+            new_version: check.new_vers.get(pkg_name),
+            old_commit: check.old_commits.get(pkg_name).map(String::as_str),
+            new_commit: check.new_commits.get(pkg_name).map(String::as_str),
+            status,
+            dry_run,
+        }
+        .emit()?;
+    }
+    Ok(())
+}
+
 /// [`Tabled`] for logging package statuses.
 #[derive(Tabled)]
 struct PackageStatus {
@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+
+use color_eyre::Result;
+use semver::Version;
+
+use crate::cli::MessageFormat;
+use crate::coloring::Colorizer;
+use crate::commands::jettison::{self, PackageEntry};
+use crate::commands::{ship, styled_table};
+use crate::config::{EffectiveJettisonConfig, EffectiveShipConfig};
+
+/// Exit code used when at least one package needs an install, update or
+/// uninstallation.
+pub const OUTDATED_EXIT_CODE: u8 = 2;
+
+/// Reports what `ship` and `jettison` would do, without doing any of it.
+///
+/// Returns whether everything is already up-to-date.
+pub fn run(
+    ship_config: &EffectiveShipConfig,
+    jettison_config: &EffectiveJettisonConfig,
+    colorizer: &Colorizer,
+    message_format: MessageFormat,
+) -> Result<bool> {
+    // Always check, even when configured to skip: only checking is wanted.
+    let (cct, check, mut to_install) = ship::check(ship_config, colorizer, message_format)?;
+    let installed = cct.into_name_versions();
+    // Packages whose version cannot be checked only need to be installed.
+    to_install.retain(|pkg_name, pkg| {
+        !check.is_unchecked(pkg_name, pkg) || !installed.contains_key(pkg_name)
+    });
+
+    let to_uninstall = jettison::needing_uninstall(installed, &jettison_config.packages);
+    log_not_configured(&to_uninstall);
+    if message_format == MessageFormat::Json {
+        jettison::emit_uninstallation_plan(&to_uninstall)?;
+    }
+
+    if !to_install.is_empty() {
+        log::warn!(
+            "{} package(s) need to be installed or updated: use `cargo liner ship` to do so.",
+            to_install.len(),
+        );
+    }
+    if !to_uninstall.is_empty() {
+        log::warn!(
+            "{} package(s) are not configured: use `cargo liner jettison` to uninstall them.",
+            to_uninstall.len(),
+        );
+    }
+
+    Ok(to_install.is_empty() && to_uninstall.is_empty())
+}
+
+/// Displays the installed packages that are not configured.
+fn log_not_configured(not_configured: &BTreeMap<String, Version>) {
+    if not_configured.is_empty() {
+        log::info!("No package to uninstall: all installed are configured as well.");
+    } else {
+        log::info!(
+            "Not configured:\n{}",
+            styled_table(not_configured.iter().map(|(pkg, ver)| PackageEntry {
+                name: pkg.clone(),
+                version: ver.to_string(),
+            })),
+        );
+    }
+}
@@ -0,0 +1,494 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::env::consts::EXE_SUFFIX;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use color_eyre::Section;
+use color_eyre::eyre::{Result, WrapErr};
+use semver::VersionReq;
+use serde::Deserialize;
+
+use super::cargo_crates_toml::CargoCratesPackage;
+use super::{CargoCratesToml, DetailedPackageReq, PackageRequirement};
+
+/// Representation of the `$CARGO_HOME/.crates2.json` Cargo-managed save file.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+pub struct CargoCrates2Json {
+    pub installs: BTreeMap<CargoCratesPackage, InstallInfo>,
+}
+
+impl CargoCrates2Json {
+    /// The default name for the save file in Cargo's home.
+    pub const FILE_NAME: &'static str = ".crates2.json";
+
+    /// Returns the [`PathBuf`] pointing to the associated save file.
+    ///
+    /// It is searched next to [`CargoCratesToml::file_path`], the given
+    /// installation root included.
+    pub fn file_path(root: Option<&Path>) -> Result<PathBuf> {
+        Ok(CargoCratesToml::file_path(root)?.with_file_name(Self::FILE_NAME))
+    }
+
+    /// Parse and return a representation of the `$CARGO_HOME/.crates2.json`
+    /// Cargo-managed save file, or the one of the given installation root.
+    ///
+    /// A missing file is considered empty, as it is only an addition to the
+    /// `.crates.toml` one that older versions of Cargo do not write. So is an
+    /// empty file, as Cargo may leave one behind after a failed installation.
+    pub fn parse_file(root: Option<&Path>) -> Result<Self> {
+        let path =
+            Self::file_path(root).wrap_err("Failed to build Cargo's .crates2.json file path.")?;
+        log::debug!("Reading Cargo-installed packages from {path:#?}...");
+        let info_str = match fs::read_to_string(&path) {
+            Ok(info_str) => info_str,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                log::debug!("File not found: considering it empty.");
+                return Ok(Self::default());
+            }
+            Err(err) => {
+                return Err(err)
+                    .wrap_err("Failed to read Cargo's .crates2.json file.")
+                    .note("This can happen for many reasons.")
+                    .suggestion("Check if the file has the correct permissions.");
+            }
+        };
+        log::trace!("Read {} bytes.", info_str.len());
+        log::trace!("Got: {info_str:#?}.");
+        if info_str.trim().is_empty() {
+            log::debug!("Empty file: considering it as such.");
+            return Ok(Self::default());
+        }
+        log::debug!("Deserializing packages...");
+        let info = serde_json::from_str(&info_str)
+            .wrap_err("Failed to deserialize Cargo's .crates2.json file contents.")
+            .note("This should not easily happen as the file is automatically maintained by Cargo.")
+            .suggestion("Check if it is corrupted in some way.")?;
+        log::trace!("Got: {info:#?}.");
+        Ok(info)
+    }
+
+    /// Consumes the document and returns the map of installed package names
+    /// to their respective installation information.
+    pub fn into_name_infos(self) -> BTreeMap<String, InstallInfo> {
+        self.installs
+            .into_iter()
+            .map(|(pkg, info)| (pkg.name, info))
+            .collect()
+    }
+}
+
+/// Options a package was installed with, as recorded by Cargo.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+pub struct InstallInfo {
+    /// Names of the installed executables, EXE suffix included.
+    #[serde(default)]
+    pub bins: BTreeSet<String>,
+    #[serde(default)]
+    pub features: BTreeSet<String>,
+    #[serde(default)]
+    pub all_features: bool,
+    #[serde(default)]
+    pub no_default_features: bool,
+    pub profile: Option<String>,
+    pub target: Option<String>,
+}
+
+impl InstallInfo {
+    /// Returns the names of the options of the given package requirement that
+    /// differ from the ones recorded at install time.
+    ///
+    /// `target` is the one the package would be installed for, either
+    /// configured or the host's: the target is not compared when unknown. The
+    /// options that the package's extra arguments set as well are not compared
+    /// either, as their effective values cannot be known.
+    pub fn changed_options(
+        &self,
+        pkg: &DetailedPackageReq,
+        target: Option<&str>,
+    ) -> Vec<&'static str> {
+        let mut changes = Vec::new();
+
+        if pkg.features.iter().cloned().collect::<BTreeSet<_>>() != self.features {
+            changes.push("features");
+        }
+        if pkg.all_features.unwrap_or_default() != self.all_features {
+            changes.push("all-features");
+        }
+        if pkg.default_features.unwrap_or(true) == self.no_default_features {
+            changes.push("default-features");
+        }
+        // Only explicit selections of executables can be compared.
+        if !pkg.all_bins.unwrap_or_default()
+            && !pkg.all_examples.unwrap_or_default()
+            && (!pkg.bins.is_empty() || !pkg.examples.is_empty())
+            && pkg
+                .bins
+                .iter()
+                .chain(&pkg.examples)
+                .map(|bin| format!("{bin}{EXE_SUFFIX}"))
+                .collect::<BTreeSet<_>>()
+                != self.bins
+        {
+            changes.push("bins");
+        }
+        if let Some(target) = target
+            && self.target.as_deref().is_some_and(|old| old != target)
+        {
+            changes.push("target");
+        }
+        if self
+            .profile
+            .as_deref()
+            .is_some_and(|old| old != pkg.effective_profile())
+        {
+            changes.push("profile");
+        }
+
+        let extra_opts = extra_options(&pkg.extra_arguments);
+        changes.retain(|opt| !extra_opts.contains(opt));
+        changes
+    }
+
+    /// Converts the recorded options into a requirement of the given version,
+    /// the simple form being used when they are all the default ones.
+    ///
+    /// The target is only kept when it differs from the given host one, or
+    /// when the host is unknown. The executables are not kept: Cargo records
+    /// the ones installed, which cannot be told apart from a selection.
+    pub fn into_requirement(self, version: VersionReq, host: Option<&str>) -> PackageRequirement {
+        let mut pkg = DetailedPackageReq {
+            version,
+            default_features: self.no_default_features.then_some(false),
+            all_features: self.all_features.then_some(true),
+            features: self.features.into_iter().collect(),
+            ..Default::default()
+        };
+        if let Some(target) = self.target
+            && host.is_none_or(|host| host != target)
+        {
+            pkg.target = Some(target);
+        }
+        if let Some(profile) = self.profile
+            && profile != pkg.effective_profile()
+        {
+            pkg.extra_arguments = vec!["--profile".to_owned(), profile];
+        }
+        pkg.into()
+    }
+}
+
+/// Returns the names of the options that the given extra arguments of a
+/// package set as well, the profile excepted.
+fn extra_options(extra_args: &[String]) -> BTreeSet<&'static str> {
+    extra_args
+        .iter()
+        .filter_map(|arg| {
+            Some(
+                match arg.split_once('=').map_or(arg.as_str(), |(flag, _)| flag) {
+                    "--features" => "features",
+                    flag if flag.starts_with("-F") => "features",
+                    "--all-features" => "all-features",
+                    "--no-default-features" => "default-features",
+                    "--bin" | "--bins" | "--example" | "--examples" => "bins",
+                    "--target" => "target",
+                    _ => return None,
+                },
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use super::*;
+    use crate::config::cargo_crates_toml::{PackageSource, SourceKind};
+
+    #[test]
+    fn test_deser_cargocrates2_full() {
+        assert_eq!(
+            serde_json::from_str::<CargoCrates2Json>(
+                r#"{
+                    "installs": {
+                        "abc 1.2.3 (registry+https://github.com/rust-lang/crates.io-index)": {
+                            "version_req": "^1",
+                            "bins": ["abc"],
+                            "features": ["a", "b"],
+                            "all_features": false,
+                            "no_default_features": true,
+                            "profile": "release",
+                            "target": "x86_64-unknown-linux-gnu",
+                            "rustc": "rustc 1.95.0\nhost: x86_64-unknown-linux-gnu\n"
+                        }
+                    }
+                }"#,
+            )
+            .unwrap(),
+            CargoCrates2Json {
+                installs: BTreeMap::from([(
+                    CargoCratesPackage {
+                        name: "abc".to_owned(),
+                        version: Version::new(1, 2, 3),
+                        source: PackageSource {
+                            kind: SourceKind::Registry,
+                            url: "https://github.com/rust-lang/crates.io-index"
+                                .parse()
+                                .unwrap(),
+                        },
+                    },
+                    InstallInfo {
+                        bins: BTreeSet::from(["abc".to_owned()]),
+                        features: BTreeSet::from(["a".to_owned(), "b".to_owned()]),
+                        all_features: false,
+                        no_default_features: true,
+                        profile: Some("release".to_owned()),
+                        target: Some("x86_64-unknown-linux-gnu".to_owned()),
+                    },
+                )]),
+            },
+        );
+    }
+
+    #[test]
+    fn test_deser_cargocrates2_minimal() {
+        assert_eq!(
+            serde_json::from_str::<CargoCrates2Json>(
+                r#"{"installs":{"abc 1.2.3 (path+file:///a/b/c)":{}}}"#,
+            )
+            .unwrap()
+            .into_name_infos(),
+            BTreeMap::from([("abc".to_owned(), InstallInfo::default())]),
+        );
+    }
+
+    fn release_info() -> InstallInfo {
+        InstallInfo {
+            profile: Some("release".to_owned()),
+            target: Some("x86_64-unknown-linux-gnu".to_owned()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parsefile_emptyormissing_isdefault() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            CargoCrates2Json::parse_file(Some(tmp_dir.path())).unwrap(),
+            CargoCrates2Json::default(),
+        );
+        fs::write(tmp_dir.path().join(CargoCrates2Json::FILE_NAME), "").unwrap();
+        assert_eq!(
+            CargoCrates2Json::parse_file(Some(tmp_dir.path())).unwrap(),
+            CargoCrates2Json::default(),
+        );
+    }
+
+    #[test]
+    fn test_installinfo_changedoptions_none() {
+        assert!(
+            release_info()
+                .changed_options(
+                    &DetailedPackageReq::default(),
+                    Some("x86_64-unknown-linux-gnu"),
+                )
+                .is_empty()
+        );
+        assert!(
+            release_info()
+                .changed_options(&DetailedPackageReq::default(), None)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_installinfo_changedoptions_features() {
+        assert_eq!(
+            release_info().changed_options(
+                &DetailedPackageReq {
+                    features: vec!["a".to_owned()],
+                    all_features: Some(true),
+                    default_features: Some(false),
+                    ..Default::default()
+                },
+                None,
+            ),
+            ["features", "all-features", "default-features"],
+        );
+        assert!(
+            InstallInfo {
+                features: BTreeSet::from(["a".to_owned(), "b".to_owned()]),
+                ..release_info()
+            }
+            .changed_options(
+                &DetailedPackageReq {
+                    features: vec!["b".to_owned(), "a".to_owned()],
+                    ..Default::default()
+                },
+                None,
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_installinfo_changedoptions_bins() {
+        let info = InstallInfo {
+            bins: BTreeSet::from([format!("b1{EXE_SUFFIX}"), format!("e1{EXE_SUFFIX}")]),
+            ..release_info()
+        };
+        assert!(
+            info.changed_options(
+                &DetailedPackageReq {
+                    bins: vec!["b1".to_owned()],
+                    examples: vec!["e1".to_owned()],
+                    ..Default::default()
+                },
+                None,
+            )
+            .is_empty()
+        );
+        assert!(
+            info.changed_options(
+                &DetailedPackageReq {
+                    bins: vec!["b2".to_owned()],
+                    all_examples: Some(true),
+                    ..Default::default()
+                },
+                None,
+            )
+            .is_empty()
+        );
+        assert_eq!(
+            info.changed_options(
+                &DetailedPackageReq {
+                    bins: vec!["b1".to_owned()],
+                    ..Default::default()
+                },
+                None,
+            ),
+            ["bins"],
+        );
+    }
+
+    #[test]
+    fn test_installinfo_changedoptions_extraarguments_ignored() {
+        let info = InstallInfo {
+            bins: BTreeSet::from([format!("b1{EXE_SUFFIX}")]),
+            features: BTreeSet::from(["f1".to_owned(), "f2".to_owned()]),
+            all_features: true,
+            no_default_features: true,
+            ..release_info()
+        };
+        assert!(
+            info.changed_options(
+                &DetailedPackageReq {
+                    extra_arguments: [
+                        "--features",
+                        "f1",
+                        "-Ff2",
+                        "--all-features",
+                        "--no-default-features",
+                        "--bin=b1",
+                        "--target",
+                        "aarch64-unknown-linux-gnu",
+                    ]
+                    .map(str::to_owned)
+                    .to_vec(),
+                    bins: vec!["b2".to_owned()],
+                    ..Default::default()
+                },
+                Some("aarch64-unknown-linux-gnu"),
+            )
+            .is_empty(),
+        );
+        assert_eq!(
+            info.changed_options(
+                &DetailedPackageReq {
+                    extra_arguments: vec!["--locked".to_owned()],
+                    default_features: Some(false),
+                    all_features: Some(true),
+                    ..Default::default()
+                },
+                None,
+            ),
+            ["features"],
+        );
+    }
+
+    #[test]
+    fn test_installinfo_changedoptions_targetprofile() {
+        assert_eq!(
+            release_info().changed_options(
+                &DetailedPackageReq {
+                    extra_arguments: vec!["--debug".to_owned()],
+                    ..Default::default()
+                },
+                Some("aarch64-unknown-linux-gnu"),
+            ),
+            ["target", "profile"],
+        );
+    }
+
+    #[test]
+    fn test_installinfo_intorequirement_defaults_issimple() {
+        let ver_req = VersionReq::parse("=1.2.3").unwrap();
+        for (info, host) in [
+            (InstallInfo::default(), None),
+            (release_info(), Some("x86_64-unknown-linux-gnu")),
+            (
+                InstallInfo {
+                    bins: BTreeSet::from(["abc".to_owned()]),
+                    ..release_info()
+                },
+                Some("x86_64-unknown-linux-gnu"),
+            ),
+            (
+                InstallInfo {
+                    bins: BTreeSet::from(["abc-cli".to_owned(), "abc-gui".to_owned()]),
+                    ..release_info()
+                },
+                Some("x86_64-unknown-linux-gnu"),
+            ),
+        ] {
+            assert_eq!(
+                info.into_requirement(ver_req.clone(), host),
+                PackageRequirement::Simple(ver_req.clone()),
+            );
+        }
+    }
+
+    #[test]
+    fn test_installinfo_intorequirement_full() {
+        assert_eq!(
+            InstallInfo {
+                bins: BTreeSet::from(["abc".to_owned()]),
+                features: BTreeSet::from(["b".to_owned(), "a".to_owned()]),
+                all_features: true,
+                no_default_features: true,
+                profile: Some("dev".to_owned()),
+                target: Some("x86_64-unknown-linux-gnu".to_owned()),
+            }
+            .into_requirement(VersionReq::STAR, Some("aarch64-unknown-linux-gnu")),
+            PackageRequirement::Detailed(Box::new(DetailedPackageReq {
+                default_features: Some(false),
+                all_features: Some(true),
+                features: vec!["a".to_owned(), "b".to_owned()],
+                target: Some("x86_64-unknown-linux-gnu".to_owned()),
+                extra_arguments: vec!["--profile".to_owned(), "dev".to_owned()],
+                ..Default::default()
+            })),
+        );
+    }
+
+    #[test]
+    fn test_installinfo_intorequirement_unknownhost_keepstarget() {
+        assert_eq!(
+            release_info().into_requirement(VersionReq::STAR, None),
+            PackageRequirement::Detailed(Box::new(DetailedPackageReq {
+                target: Some("x86_64-unknown-linux-gnu".to_owned()),
+                ..Default::default()
+            })),
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::{BTreeMap, BTreeSet};
-use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::{fmt, fs, io};
 
 use color_eyre::Section;
 use color_eyre::eyre::{self, Result, WrapErr, eyre};
@@ -26,13 +26,17 @@ impl CargoCratesToml {
 
     /// Returns the [`PathBuf`] pointing to the associated save file.
     ///
-    /// In order to determine which exact file to get, it will first try to use
-    /// `$CARGO_INSTALL_ROOT` if available, or then fall back to `$CARGO_HOME`
-    /// otherwise.
-    pub fn file_path() -> Result<PathBuf> {
+    /// In order to determine which exact file to get, it will first use the
+    /// given installation root if any, then try to use `$CARGO_INSTALL_ROOT`
+    /// if available, or then fall back to `$CARGO_HOME` otherwise.
+    pub fn file_path(root: Option<&Path>) -> Result<PathBuf> {
         const INSTALL_ROOT_CONFIG_KEY: &str = "install.root";
         log::debug!("Building file path...");
 
+        if let Some(root) = root {
+            return Ok(root.join(Self::FILE_NAME));
+        }
+
         // Don't particularly filter: default to `$CARGO_HOME` on any error.
         match cargo::config_get(INSTALL_ROOT_CONFIG_KEY) {
             Ok(install_root_path) => Ok(install_root_path
@@ -51,14 +55,27 @@ impl CargoCratesToml {
     }
 
     /// Parse and return a representation of the `$CARGO_HOME/.crates.toml`
-    /// Cargo-managed save file.
-    pub fn parse_file() -> Result<Self> {
-        let path = Self::file_path().wrap_err("Failed to build Cargo's .crates.toml file path.")?;
+    /// Cargo-managed save file, or the one of the given installation root.
+    ///
+    /// The file of a given root is considered empty when missing, as it only
+    /// gets created by Cargo when installing a first package there.
+    pub fn parse_file(root: Option<&Path>) -> Result<Self> {
+        let path =
+            Self::file_path(root).wrap_err("Failed to build Cargo's .crates.toml file path.")?;
         log::debug!("Reading Cargo-installed packages from {path:#?}...");
-        let info_str = fs::read_to_string(path)
-            .wrap_err("Failed to read Cargo's .crates.toml file.")
-            .note("This can happen for many reasons.")
-            .suggestion("Check if the file exists and has the correct permissions.")?;
+        let info_str = match fs::read_to_string(&path) {
+            Ok(info_str) => info_str,
+            Err(err) if root.is_some() && err.kind() == io::ErrorKind::NotFound => {
+                log::debug!("File not found: considering it empty.");
+                return Ok(Self::default());
+            }
+            Err(err) => {
+                return Err(err)
+                    .wrap_err("Failed to read Cargo's .crates.toml file.")
+                    .note("This can happen for many reasons.")
+                    .suggestion("Check if the file exists and has the correct permissions.");
+            }
+        };
         log::trace!("Read {} bytes.", info_str.len());
         log::trace!("Got: {info_str:#?}.");
         log::debug!("Deserializing packages...");
@@ -84,6 +101,46 @@ impl CargoCratesToml {
             .collect()
     }
 
+    /// Consumes the document and returns the map of installed package names
+    /// to their respective full identification.
+    pub fn into_name_packages(self) -> BTreeMap<String, CargoCratesPackage> {
+        self.package_bins
+            .into_keys()
+            .map(|pkg| (pkg.name.clone(), pkg))
+            .collect()
+    }
+
+    /// Consumes the document and returns the map of package names installed
+    /// from Git repositories to their respective installed commit.
+    pub fn into_name_git_commits(self) -> BTreeMap<String, String> {
+        self.package_bins
+            .into_keys()
+            .filter(|pkg| pkg.source.kind == SourceKind::Git)
+            .filter_map(|pkg| {
+                pkg.source
+                    .url
+                    .fragment()
+                    .map(|commit| (pkg.name, commit.to_owned()))
+            })
+            .collect()
+    }
+
+    /// Consumes the document and returns the map of package names installed
+    /// from local paths to their respective path.
+    pub fn into_name_paths(self) -> BTreeMap<String, PathBuf> {
+        self.package_bins
+            .into_keys()
+            .filter(|pkg| pkg.source.kind == SourceKind::Path)
+            .filter_map(|pkg| {
+                pkg.source
+                    .url
+                    .to_file_path()
+                    .ok()
+                    .map(|path| (pkg.name, path))
+            })
+            .collect()
+    }
+
     /// Converts this toml document into a custom user config by mapping
     /// listed packages using the given `pkg_map` function.
     ///
@@ -103,6 +160,10 @@ impl CargoCratesToml {
         keep_local: bool,
     ) -> UserConfig {
         UserConfig {
+            version: None,
+            strict: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
             packages: self
                 .package_bins
                 .into_iter()
@@ -112,7 +173,12 @@ impl CargoCratesToml {
                 })
                 .map(pkg_map)
                 .collect(),
+            templates: BTreeMap::new(),
+            groups: BTreeMap::new(),
+            target: BTreeMap::new(),
+            hosts: BTreeMap::new(),
             defaults: None,
+            root: None,
         }
     }
 
@@ -228,6 +294,13 @@ impl FromStr for PackageSource {
     }
 }
 
+/// Serialize as Cargo does, i.e. the kind and the URL joined by a plus sign.
+impl fmt::Display for PackageSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}+{}", self.kind, self.url)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, DeserializeFromStr)]
 pub enum SourceKind {
     Git,
@@ -250,8 +323,20 @@ impl FromStr for SourceKind {
     }
 }
 
+/// Inverse of [`SourceKind::from_str`].
+impl fmt::Display for SourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Git => "git",
+            Self::Path => "path",
+            Self::Registry => "registry",
+            Self::SparseRegistry => "sparse",
+        })
+    }
+}
+
 /// Converts the given version to a version requirement with the given operator.
-fn ver_to_req(ver: &Version, op: Op) -> VersionReq {
+pub fn ver_to_req(ver: &Version, op: Op) -> VersionReq {
     let mut req = ver.to_string().parse::<VersionReq>().unwrap();
     req.comparators[0].op = op;
     req
@@ -354,6 +439,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_packagesource_display_roundtrips() {
+        for source in [
+            "registry+https://github.com/rust-lang/crates.io-index",
+            "sparse+https://index.crates.io/",
+            "git+https://example.com/b.git?branch=dev#0123456789abcdef0123456789abcdef01234567",
+            "path+file:///a/b/c",
+        ] {
+            assert_eq!(source.parse::<PackageSource>().unwrap().to_string(), source);
+        }
+    }
+
+    #[test]
+    fn test_cargocrates_intonamegitcommits() {
+        assert_eq!(
+            toml::from_str::<CargoCratesToml>(
+                r#"
+                    [v1]
+                    "a 1.2.3 (registry+https://example.com/index)" = ["a"]
+                    "b 0.1.2 (git+https://example.com/b.git#0123456789abcdef0123456789abcdef01234567)" = ["b"]
+                    "c 0.0.0 (git+https://example.com/c.git?branch=dev#fedcba9876543210fedcba9876543210fedcba98)" = ["c"]
+                    "d 0.0.0 (path+file:///a/b/d)" = ["d"]
+                "#,
+            )
+            .unwrap()
+            .into_name_git_commits(),
+            BTreeMap::from([
+                (
+                    "b".to_owned(),
+                    "0123456789abcdef0123456789abcdef01234567".to_owned(),
+                ),
+                (
+                    "c".to_owned(),
+                    "fedcba9876543210fedcba9876543210fedcba98".to_owned(),
+                ),
+            ]),
+        );
+    }
+
+    #[test]
+    fn test_cargocrates_intonamepaths() {
+        assert_eq!(
+            cargocrates_example1().into_name_paths(),
+            BTreeMap::from([("c".to_owned(), PathBuf::from("/a/b/c"))]),
+        );
+    }
+
     #[test]
     fn test_cargocrates_intostarcfg_no_packages() {
         assert_eq!(
@@ -387,6 +519,10 @@ mod tests {
         assert_eq!(
             cargocrates_example1().into_star_version_config(false, false),
             UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
                 packages: [("a", "*"), ("b", "*")]
                     .into_iter()
                     .map(|(name, version)| (
@@ -394,7 +530,12 @@ mod tests {
                         PackageRequirement::Simple(VersionReq::parse(version).unwrap()),
                     ))
                     .collect::<BTreeMap<_, _>>(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
                 defaults: None,
+                root: None,
             },
         );
     }
@@ -424,6 +565,10 @@ mod tests {
         assert_eq!(
             cargocrates_example1().into_exact_version_config(false, false),
             UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
                 packages: [("a", "=1.2.3"), ("b", "=0.1.2")]
                     .into_iter()
                     .map(|(name, version)| (
@@ -431,7 +576,12 @@ mod tests {
                         PackageRequirement::Simple(VersionReq::parse(version).unwrap()),
                     ))
                     .collect::<BTreeMap<_, _>>(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
                 defaults: None,
+                root: None,
             },
         );
     }
@@ -461,6 +611,10 @@ mod tests {
         assert_eq!(
             cargocrates_example1().into_comp_version_config(false, false),
             UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
                 packages: [("a", "^1.2.3"), ("b", "^0.1.2")]
                     .into_iter()
                     .map(|(name, version)| (
@@ -468,7 +622,12 @@ mod tests {
                         PackageRequirement::Simple(VersionReq::parse(version).unwrap()),
                     ))
                     .collect::<BTreeMap<_, _>>(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
                 defaults: None,
+                root: None,
             },
         );
     }
@@ -498,6 +657,10 @@ mod tests {
         assert_eq!(
             cargocrates_example1().into_patch_version_config(false, false),
             UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
                 packages: [("a", "~1.2.3"), ("b", "~0.1.2")]
                     .into_iter()
                     .map(|(name, version)| (
@@ -505,7 +668,12 @@ mod tests {
                         PackageRequirement::Simple(VersionReq::parse(version).unwrap()),
                     ))
                     .collect::<BTreeMap<_, _>>(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
                 defaults: None,
+                root: None,
             },
         );
     }
@@ -543,6 +711,10 @@ mod tests {
         assert_eq!(
             cargocrates_example1().into_star_version_config(false, true),
             UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
                 packages: [("a", "*"), ("b", "*"), ("c", "*")]
                     .into_iter()
                     .map(|(name, version)| (
@@ -550,7 +722,12 @@ mod tests {
                         PackageRequirement::Simple(VersionReq::parse(version).unwrap()),
                     ))
                     .collect::<BTreeMap<_, _>>(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
                 defaults: None,
+                root: None,
             },
         );
     }
@@ -560,6 +737,10 @@ mod tests {
         assert_eq!(
             cargocrates_example1().into_star_version_config(true, false),
             UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
                 packages: [("a", "*"), ("b", "*"), (clap::crate_name!(), "*")]
                     .into_iter()
                     .map(|(name, version)| (
@@ -567,7 +748,12 @@ mod tests {
                         PackageRequirement::Simple(VersionReq::parse(version).unwrap()),
                     ))
                     .collect::<BTreeMap<_, _>>(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
                 defaults: None,
+                root: None,
             },
         );
     }
@@ -577,6 +763,10 @@ mod tests {
         assert_eq!(
             cargocrates_example1().into_star_version_config(true, true),
             UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
                 packages: [
                     ("a", "*"),
                     ("b", "*"),
@@ -589,7 +779,12 @@ mod tests {
                     PackageRequirement::Simple(VersionReq::parse(version).unwrap()),
                 ))
                 .collect::<BTreeMap<_, _>>(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
                 defaults: None,
+                root: None,
             },
         );
     }
@@ -619,6 +814,10 @@ mod tests {
         assert_eq!(
             cargocrates_example1().into_exact_version_config(true, false),
             UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
                 packages: [
                     ("a", "=1.2.3"),
                     ("b", "=0.1.2"),
@@ -630,7 +829,12 @@ mod tests {
                     PackageRequirement::Simple(VersionReq::parse(version).unwrap()),
                 ))
                 .collect::<BTreeMap<_, _>>(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
                 defaults: None,
+                root: None,
             },
         );
     }
@@ -660,6 +864,10 @@ mod tests {
         assert_eq!(
             cargocrates_example1().into_comp_version_config(true, false),
             UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
                 packages: [
                     ("a", "^1.2.3"),
                     ("b", "^0.1.2"),
@@ -671,7 +879,12 @@ mod tests {
                     PackageRequirement::Simple(VersionReq::parse(version).unwrap()),
                 ))
                 .collect::<BTreeMap<_, _>>(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
                 defaults: None,
+                root: None,
             },
         );
     }
@@ -701,6 +914,10 @@ mod tests {
         assert_eq!(
             cargocrates_example1().into_patch_version_config(true, false),
             UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
                 packages: [
                     ("a", "~1.2.3"),
                     ("b", "~0.1.2"),
@@ -712,7 +929,12 @@ mod tests {
                     PackageRequirement::Simple(VersionReq::parse(version).unwrap()),
                 ))
                 .collect::<BTreeMap<_, _>>(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
                 defaults: None,
+                root: None,
             },
         );
     }
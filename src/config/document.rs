@@ -0,0 +1,128 @@
+//! Format-preserving updates of TOML documents.
+//!
+//! See [`update_table`] in order to make an existing document represent new
+//! contents while keeping everything else it contains as-is.
+
+use toml_edit::{Item, TableLike};
+
+/// Updates the given table so that it holds the same contents as the new one,
+/// only touching the keys whose value actually changed.
+///
+/// Keys missing from the new table are removed, while new ones are appended
+/// as they are given. Comments, key order and the inline or standard style of
+/// the sub-tables that are kept are preserved. Values are compared by their
+/// meaning and not their representation, so that `'a'` and `"a"` are equal.
+pub fn update_table(old: &mut dyn TableLike, new: &dyn TableLike) {
+    let removed_keys = old
+        .iter()
+        .filter(|(key, _)| !new.contains_key(key))
+        .map(|(key, _)| key.to_owned())
+        .collect::<Vec<_>>();
+    for key in removed_keys {
+        log::trace!("Removing the {key:?} key...");
+        old.remove(&key);
+    }
+
+    for (key, new_item) in new.iter() {
+        if let Some(old_item) = old.get_mut(key) {
+            update_item(old_item, new_item);
+        } else {
+            log::trace!("Adding the {key:?} key...");
+            old.insert(key, new_item.clone());
+        }
+    }
+}
+
+/// Updates the given item so that it holds the same contents as the new one.
+fn update_item(old: &mut Item, new: &Item) {
+    if let (Some(old_table), Some(new_table)) = (old.as_table_like_mut(), new.as_table_like()) {
+        update_table(old_table, new_table);
+    } else if let (Some(old_value), Some(new_value)) = (old.as_value_mut(), new.as_value()) {
+        if !values_eq(old_value, new_value) {
+            // Keep the surrounding comments and spacing.
+            let decor = old_value.decor().clone();
+            *old_value = new_value.clone();
+            *old_value.decor_mut() = decor;
+        }
+    } else {
+        *old = new.clone();
+    }
+}
+
+/// Returns whether both values have the same meaning, regardless of their
+/// respective representation.
+fn values_eq(val1: &toml_edit::Value, val2: &toml_edit::Value) -> bool {
+    let parse = |val: &toml_edit::Value| {
+        let mut val = val.clone();
+        val.decor_mut().clear();
+        val.to_string().parse::<toml::Value>().ok()
+    };
+    parse(val1).is_some_and(|val1| Some(val1) == parse(val2))
+}
+
+#[cfg(test)]
+mod tests {
+    use toml_edit::DocumentMut;
+
+    use super::*;
+
+    /// Updates the first document with the second one and returns the result.
+    fn update(old: &str, new: &str) -> String {
+        let mut old = old.parse::<DocumentMut>().unwrap();
+        update_table(
+            old.as_table_mut(),
+            new.parse::<DocumentMut>().unwrap().as_table(),
+        );
+        old.to_string()
+    }
+
+    #[test]
+    fn test_updatetable_same_isunchanged() {
+        let doc = "# Comment.\n[a]\nb = 'c' # Other.\nd = { e = 1 }\n\n[a.f]\ng = [1, 2]\n";
+        assert_eq!(
+            update(doc, "[a]\nb = \"c\"\nd = { e = 1 }\n[a.f]\ng = [1, 2]\n"),
+            doc,
+        );
+    }
+
+    #[test]
+    fn test_updatetable_changedvalue_keepsdecor() {
+        assert_eq!(
+            update(
+                "[a]\nb = 'c'   # Comment.\nd = 1\n",
+                "[a]\nb = 'x'\nd = 1\n"
+            ),
+            "[a]\nb = 'x'   # Comment.\nd = 1\n",
+        );
+    }
+
+    #[test]
+    fn test_updatetable_inlinetable_keepsstyle() {
+        assert_eq!(
+            update("a = { b = 1, c = 2 }\n", "[a]\nb = 1\nc = 3\n"),
+            "a = { b = 1, c = 3 }\n",
+        );
+    }
+
+    #[test]
+    fn test_updatetable_addremove() {
+        assert_eq!(
+            update(
+                "# Top.\n[a]\n# Kept.\nb = 1\n# Gone.\nc = 2\n",
+                "[a]\nb = 1\nd = 3\n"
+            ),
+            "# Top.\n[a]\n# Kept.\nb = 1\nd = 3\n",
+        );
+    }
+
+    #[test]
+    fn test_updatetable_changedkind_isreplaced() {
+        assert_eq!(
+            update(
+                "[a]\nb = '*'\n",
+                "[a]\nb = { version = '*', locked = true }\n"
+            ),
+            "[a]\nb = { version = '*', locked = true }\n",
+        );
+    }
+}
@@ -5,9 +5,14 @@
 //! parsing the various sources in split steps, doing it optionally, and also
 //! sometimes only partially.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
 
-use super::{DetailedPackageReq, UserConfig};
+use color_eyre::Section;
+use color_eyre::eyre::{Result, WrapErr, eyre};
+
+use super::{DetailedPackageReq, PackageSelection, Platform, UserConfig};
 use crate::cli::{BinstallChoice, JettisonArgs, ShipArgs};
 
 /// Effective merge of all `ship` configuration sources.
@@ -17,22 +22,111 @@ pub struct EffectiveShipConfig {
     pub packages: BTreeMap<String, DetailedPackageReq>,
     /// Effective arguments to use.
     pub args: EffectiveShipArgs,
+    /// Names of all the packages configured for the current machine, selected
+    /// or not, self included when installed to the default root.
+    pub configured: BTreeSet<String>,
+    /// Installation root taken from the [`UserConfig`], if not the default.
+    pub root: Option<PathBuf>,
 }
 
 impl EffectiveShipConfig {
     /// Merges all given sources and exports the result as public fields.
-    pub fn new(user_config: UserConfig, env_args: ShipArgs, cli_args: ShipArgs) -> Self {
+    ///
+    /// The overlays applying to the current machine are added to the packages
+    /// first, thus failing if one of them is invalid. Only the selected
+    /// packages are then kept, thus failing if one of the given names or
+    /// groups is unknown. The templates the packages inherit from are
+    /// resolved along the way, thus failing if one of them is unknown or
+    /// inherits from itself. The variables of their options are then expanded,
+    /// thus failing if one of them is undefined, and relative paths of a
+    /// project configuration are resolved against its directory. Self is
+    /// never installed to another root than the default one, and the
+    /// package-wide defaults do not apply to it unless it is explicitly
+    /// configured. It also fails if both `locked-config` and `update-lock` end
+    /// up enabled.
+    pub fn new(user_config: UserConfig, env_args: ShipArgs, cli_args: ShipArgs) -> Result<Self> {
         let args = EffectiveShipArgs::new(&user_config, env_args, cli_args);
-        Self {
+        if args.locked_config && args.update_lock {
+            return Err(eyre!(
+                "Cannot both install the locked versions and update the lock file."
+            ))
+            .suggestion(
+                "Disable either `locked-config` or `update-lock`, for example using \
+                 `--no-locked-config` or `--no-update-lock`.",
+            );
+        }
+        let pkg_defs = user_config
+            .defaults
+            .as_ref()
+            .map(|defs| defs.package.clone())
+            .unwrap_or_default();
+        let templates = user_config.templates.clone();
+        let root = user_config.root.clone();
+        // Project roots are always directly inside of their project.
+        let project_dir = root.as_deref().and_then(Path::parent).map(Path::to_owned);
+        let user_config = user_config
+            .with_overlays(&Platform::current())
+            .wrap_err("Failed to apply the conditional packages.")
+            .suggestion("Check the `target` section of the configuration file.")?;
+        let implicit_self = !user_config.packages.contains_key(clap::crate_name!());
+        let mut configured = user_config
+            .packages
+            .keys()
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        if root.is_none() {
+            configured.insert(clap::crate_name!().to_owned());
+        } else {
+            configured.remove(clap::crate_name!());
+        }
+        let mut user_config = user_config
+            .self_update(!args.no_self)
+            .select(&args.selection())
+            .wrap_err("Failed to select the packages to ship.")
+            .suggestion("Check the given package names and groups against the configuration file.")?
+            .update_others(!args.only_self);
+        if root.is_some() {
+            user_config = user_config.self_update(false);
+        }
+        Ok(Self {
             packages: user_config
-                .self_update(!args.no_self)
-                .update_others(!args.only_self)
                 .packages
                 .into_iter()
-                .map(|(pkg_name, pkg)| (pkg_name, pkg.into()))
-                .collect::<BTreeMap<String, DetailedPackageReq>>(),
+                .map(|(pkg_name, pkg)| {
+                    let pkg = DetailedPackageReq::from(pkg)
+                        .with_templates(&templates)
+                        .wrap_err_with(|| {
+                            format!("Failed to resolve the templates of the {pkg_name:?} package.")
+                        })
+                        .suggestion(
+                            "Check the `template` keys and the `templates` section of the \
+                             configuration file.",
+                        )?;
+                    let pkg = if implicit_self && pkg_name == clap::crate_name!() {
+                        pkg
+                    } else {
+                        pkg.with_defaults(&pkg_defs)
+                    };
+                    let pkg = pkg
+                        .interpolated()
+                        .wrap_err_with(|| {
+                            format!("Failed to expand the variables of the {pkg_name:?} package.")
+                        })
+                        .suggestion(
+                            "Set the missing environment variables or give them a default value \
+                             using `${VAR:-default}`.",
+                        )?;
+                    let pkg = match &project_dir {
+                        Some(dir) => pkg.relative_to(dir),
+                        None => pkg,
+                    };
+                    Ok((pkg_name, pkg))
+                })
+                .collect::<Result<BTreeMap<String, DetailedPackageReq>>>()?,
             args,
-        }
+            configured,
+            root,
+        })
     }
 }
 
@@ -44,6 +138,8 @@ impl EffectiveShipConfig {
     reason = "This includes all the `ship` CLI boolean flags, which are not in small number."
 )]
 pub struct EffectiveShipArgs {
+    /// Names of the packages to ship, all of them when empty.
+    pub packages: Vec<String>,
     pub no_self: bool,
     pub only_self: bool,
     pub skip_check: bool,
@@ -51,13 +147,23 @@ pub struct EffectiveShipArgs {
     pub force: bool,
     pub dry_run: bool,
     pub target: Option<String>,
+    pub group: Vec<String>,
+    pub exclude_group: Vec<String>,
+    pub locked_config: bool,
+    pub update_lock: bool,
     pub binstall: BinstallChoice,
+    /// Number of parallel installations, unset meaning one at a time.
+    pub jobs: Option<NonZeroUsize>,
+    /// Number of parallel version lookups, unset meaning the default one.
+    pub lookup_jobs: Option<NonZeroUsize>,
 }
 
 impl EffectiveShipArgs {
     fn new(user_config: &UserConfig, env_args: ShipArgs, cli_args: ShipArgs) -> Self {
         let cfg_defs = user_config.defaults.as_ref();
         Self {
+            // Only meaningful on the command line.
+            packages: cli_args.packages,
             no_self: cli_args
                 .no_self
                 .or(env_args.no_self)
@@ -92,11 +198,49 @@ impl EffectiveShipArgs {
                 .target
                 .or(env_args.target)
                 .or_else(|| cfg_defs.and_then(|defs| defs.ship_cmd.target.clone())),
+            group: cli_args
+                .group
+                .or(env_args.group)
+                .or_else(|| cfg_defs.and_then(|defs| defs.ship_cmd.group.clone()))
+                .unwrap_or_default(),
+            exclude_group: cli_args
+                .exclude_group
+                .or(env_args.exclude_group)
+                .or_else(|| cfg_defs.and_then(|defs| defs.ship_cmd.exclude_group.clone()))
+                .unwrap_or_default(),
+            locked_config: cli_args
+                .locked_config
+                .or(env_args.locked_config)
+                .or_else(|| cfg_defs.and_then(|defs| defs.ship_cmd.locked_config.as_ref().copied()))
+                .unwrap_or_default(),
+            update_lock: cli_args
+                .update_lock
+                .or(env_args.update_lock)
+                .or_else(|| cfg_defs.and_then(|defs| defs.ship_cmd.update_lock.as_ref().copied()))
+                .unwrap_or_default(),
             binstall: cli_args
                 .binstall
                 .or(env_args.binstall)
                 .or_else(|| cfg_defs.and_then(|defs| defs.ship_cmd.binstall.as_ref().copied()))
                 .unwrap_or_default(),
+            jobs: cli_args
+                .jobs
+                .or(env_args.jobs)
+                .or_else(|| cfg_defs.and_then(|defs| defs.ship_cmd.jobs)),
+            lookup_jobs: cli_args
+                .lookup_jobs
+                .or(env_args.lookup_jobs)
+                .or_else(|| cfg_defs.and_then(|defs| defs.ship_cmd.lookup_jobs)),
+        }
+    }
+
+    /// Returns the selection of the packages to ship described by the
+    /// arguments.
+    pub fn selection(&self) -> PackageSelection {
+        PackageSelection {
+            names: self.packages.clone(),
+            groups: self.group.clone(),
+            excluded_groups: self.exclude_group.clone(),
         }
     }
 }
@@ -108,21 +252,36 @@ pub struct EffectiveJettisonConfig {
     pub packages: BTreeMap<String, DetailedPackageReq>,
     /// Effective arguments to use.
     pub args: EffectiveJettisonArgs,
+    /// Installation root taken from the [`UserConfig`], if not the default.
+    pub root: Option<PathBuf>,
 }
 
 impl EffectiveJettisonConfig {
     /// Merges all given sources and exports the result as public fields.
-    pub fn new(user_config: UserConfig, env_args: JettisonArgs, cli_args: JettisonArgs) -> Self {
+    ///
+    /// The overlays applying to the current machine are added to the packages,
+    /// thus failing if one of them is invalid, so that the packages of the
+    /// other ones are not considered as configured.
+    pub fn new(
+        user_config: UserConfig,
+        env_args: JettisonArgs,
+        cli_args: JettisonArgs,
+    ) -> Result<Self> {
         let args = EffectiveJettisonArgs::new(&user_config, env_args, cli_args);
-        Self {
-            // Don't perform any particular filtering here: not needed.
+        let user_config = user_config
+            .with_overlays(&Platform::current())
+            .wrap_err("Failed to apply the conditional packages.")
+            .suggestion("Check the `target` section of the configuration file.")?;
+        Ok(Self {
+            root: user_config.root,
+            // Don't perform any other filtering here: not needed.
             packages: user_config
                 .packages
                 .into_iter()
                 .map(|(pkg_name, pkg)| (pkg_name, pkg.into()))
                 .collect::<BTreeMap<String, DetailedPackageReq>>(),
             args,
-        }
+        })
     }
 }
 
@@ -174,8 +333,13 @@ mod tests {
     )]
     use std::fmt::Debug;
 
+    use indoc::indoc;
+    use semver::VersionReq;
+
     use super::*;
+    use crate::config::template::TemplateNames;
     use crate::config::user_config::DefaultsSection;
+    use crate::config::{PackageDefaults, PackageRequirement, PackageTemplate};
 
     /// HACK: exploit the `Debug` implementation in order to get the field
     /// names. Use `Default` in order to get a value to feed into `Debug`.
@@ -203,8 +367,17 @@ mod tests {
         assert_eq!(
             EffectiveShipArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: None,
+                    root: None,
                 },
                 ShipArgs::default(),
                 ShipArgs::default(),
@@ -218,11 +391,21 @@ mod tests {
         assert_eq!(
             EffectiveShipArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs::default(),
                         jettison_cmd: JettisonArgs::default(),
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 ShipArgs::default(),
                 ShipArgs::default(),
@@ -236,7 +419,15 @@ mod tests {
         assert_eq!(
             EffectiveShipArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs {
                             force: Some(false),
@@ -247,7 +438,9 @@ mod tests {
                             ..Default::default()
                         },
                         jettison_cmd: JettisonArgs::default(),
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 ShipArgs::default(),
                 ShipArgs::default(),
@@ -268,11 +461,21 @@ mod tests {
         assert_eq!(
             EffectiveShipArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs::default(),
                         jettison_cmd: JettisonArgs::default(),
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 ShipArgs {
                     force: Some(false),
@@ -300,11 +503,21 @@ mod tests {
         assert_eq!(
             EffectiveShipArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs::default(),
                         jettison_cmd: JettisonArgs::default(),
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 ShipArgs::default(),
                 ShipArgs {
@@ -332,7 +545,15 @@ mod tests {
         assert_eq!(
             EffectiveShipArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs {
                             force: Some(true),
@@ -340,7 +561,9 @@ mod tests {
                             ..Default::default()
                         },
                         jettison_cmd: JettisonArgs::default(),
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 ShipArgs {
                     only_self: Some(true),
@@ -374,7 +597,15 @@ mod tests {
         assert_eq!(
             EffectiveShipArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs {
                             force: Some(true),
@@ -387,7 +618,9 @@ mod tests {
                             ..Default::default()
                         },
                         jettison_cmd: JettisonArgs::default(),
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 ShipArgs {
                     force: Some(false),
@@ -419,8 +652,17 @@ mod tests {
         assert_eq!(
             EffectiveShipArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: None,
+                    root: None,
                 },
                 ShipArgs {
                     force: Some(true),
@@ -461,7 +703,15 @@ mod tests {
         assert_eq!(
             EffectiveShipArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs {
                             force: Some(true),
@@ -474,7 +724,9 @@ mod tests {
                             ..Default::default()
                         },
                         jettison_cmd: JettisonArgs::default(),
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 ShipArgs::default(),
                 ShipArgs {
@@ -506,7 +758,15 @@ mod tests {
         assert_eq!(
             EffectiveShipArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs {
                             force: Some(true),
@@ -516,16 +776,20 @@ mod tests {
                             dry_run: Some(false),
                             target: Some("example-target-triple-string1".to_owned()),
                             binstall: Some(BinstallChoice::Never),
+                            jobs: NonZeroUsize::new(2),
                             ..Default::default()
                         },
                         jettison_cmd: JettisonArgs::default(),
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 ShipArgs {
                     only_self: Some(false),
                     dry_run: Some(true),
                     target: Some("example-target-triple-string2".to_owned()),
                     binstall: Some(BinstallChoice::Auto),
+                    jobs: NonZeroUsize::new(3),
                     ..Default::default()
                 },
                 ShipArgs {
@@ -537,6 +801,7 @@ mod tests {
                     dry_run: Some(false),
                     target: Some("example-target-triple-string3".to_owned()),
                     binstall: Some(BinstallChoice::Always),
+                    jobs: NonZeroUsize::new(4),
                     ..Default::default()
                 },
             ),
@@ -549,11 +814,437 @@ mod tests {
                 dry_run: false,
                 target: Some("example-target-triple-string3".to_owned()),
                 binstall: BinstallChoice::Always,
+                jobs: NonZeroUsize::new(4),
                 ..Default::default()
             },
         );
     }
 
+    #[test]
+    fn test_effectiveshipconfig_packagedefaults_merged() {
+        let config = EffectiveShipConfig::new(
+            UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
+                packages: [
+                    ("abc".to_owned(), PackageRequirement::SIMPLE_STAR),
+                    (
+                        "def".to_owned(),
+                        PackageRequirement::Detailed(Box::new(DetailedPackageReq {
+                            version: "1.2".parse().unwrap(),
+                            git: Some("https://example.com/def".to_owned()),
+                            environment: [("A".to_owned(), "def".to_owned())].into(),
+                            extra_arguments: vec!["--debug".to_owned()],
+                            binstall: Some(BinstallChoice::Never),
+                            ..Default::default()
+                        })),
+                    ),
+                ]
+                .into(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
+                defaults: Some(DefaultsSection {
+                    package: PackageDefaults {
+                        registry: Some("reg".to_owned()),
+                        locked: Some(true),
+                        environment: Some(
+                            [
+                                ("A".to_owned(), "abc".to_owned()),
+                                ("B".to_owned(), "abc".to_owned()),
+                            ]
+                            .into(),
+                        ),
+                        extra_arguments: Some(vec!["--profile=dist".to_owned()]),
+                        binstall: Some(BinstallChoice::Always),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }),
+                root: None,
+            },
+            ShipArgs::default(),
+            ShipArgs {
+                no_self: Some(true),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.packages,
+            [
+                (
+                    "abc".to_owned(),
+                    DetailedPackageReq {
+                        version: VersionReq::STAR,
+                        registry: Some("reg".to_owned()),
+                        locked: Some(true),
+                        environment: [
+                            ("A".to_owned(), "abc".to_owned()),
+                            ("B".to_owned(), "abc".to_owned()),
+                        ]
+                        .into(),
+                        extra_arguments: vec!["--profile=dist".to_owned()],
+                        binstall: Some(BinstallChoice::Always),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "def".to_owned(),
+                    DetailedPackageReq {
+                        version: "1.2".parse().unwrap(),
+                        git: Some("https://example.com/def".to_owned()),
+                        locked: Some(true),
+                        environment: [
+                            ("A".to_owned(), "def".to_owned()),
+                            ("B".to_owned(), "abc".to_owned()),
+                        ]
+                        .into(),
+                        extra_arguments: vec!["--debug".to_owned()],
+                        binstall: Some(BinstallChoice::Never),
+                        ..Default::default()
+                    },
+                ),
+            ]
+            .into(),
+        );
+    }
+
+    #[test]
+    fn test_effectiveshipconfig_packagedefaults_implicitselfexempt() {
+        let user_config = |pkgs: &[&str]| UserConfig {
+            packages: pkgs
+                .iter()
+                .map(|pkg| ((*pkg).to_owned(), PackageRequirement::SIMPLE_STAR))
+                .collect(),
+            defaults: Some(DefaultsSection {
+                package: PackageDefaults {
+                    locked: Some(true),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let locked = |pkg_names: &[&str]| {
+            EffectiveShipConfig::new(
+                user_config(pkg_names),
+                ShipArgs::default(),
+                ShipArgs::default(),
+            )
+            .unwrap()
+            .packages
+            .into_iter()
+            .map(|(pkg_name, pkg)| (pkg_name, pkg.locked))
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            locked(&["abc"]),
+            [
+                ("abc".to_owned(), Some(true)),
+                (clap::crate_name!().to_owned(), None),
+            ],
+        );
+        assert_eq!(
+            locked(&["abc", clap::crate_name!()]),
+            [
+                ("abc".to_owned(), Some(true)),
+                (clap::crate_name!().to_owned(), Some(true)),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_effectiveshipconfig_templates_merged() {
+        let config = EffectiveShipConfig::new(
+            UserConfig {
+                packages: [
+                    (
+                        "abc".to_owned(),
+                        PackageRequirement::Detailed(Box::new(DetailedPackageReq {
+                            template: TemplateNames::One("base".to_owned()),
+                            ..Default::default()
+                        })),
+                    ),
+                    (
+                        "def".to_owned(),
+                        PackageRequirement::Detailed(Box::new(DetailedPackageReq {
+                            tag: Some("v1".to_owned()),
+                            template: TemplateNames::Many(vec![
+                                "base".to_owned(),
+                                "git".to_owned(),
+                            ]),
+                            ..Default::default()
+                        })),
+                    ),
+                ]
+                .into(),
+                templates: [
+                    (
+                        "base".to_owned(),
+                        PackageTemplate {
+                            registry: Some("reg".to_owned()),
+                            locked: Some(true),
+                            environment: Some([("A".to_owned(), "base".to_owned())].into()),
+                            binstall: Some(BinstallChoice::Never),
+                            ..Default::default()
+                        },
+                    ),
+                    (
+                        "git".to_owned(),
+                        PackageTemplate {
+                            template: TemplateNames::One("base".to_owned()),
+                            git: Some("https://example.com/git".to_owned()),
+                            branch: Some("dev".to_owned()),
+                            environment: Some([("A".to_owned(), "git".to_owned())].into()),
+                            ..Default::default()
+                        },
+                    ),
+                ]
+                .into(),
+                defaults: Some(DefaultsSection {
+                    package: PackageDefaults {
+                        target: Some("def-target".to_owned()),
+                        binstall: Some(BinstallChoice::Always),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ShipArgs::default(),
+            ShipArgs {
+                no_self: Some(true),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.packages,
+            [
+                (
+                    "abc".to_owned(),
+                    DetailedPackageReq {
+                        registry: Some("reg".to_owned()),
+                        locked: Some(true),
+                        environment: [("A".to_owned(), "base".to_owned())].into(),
+                        target: Some("def-target".to_owned()),
+                        binstall: Some(BinstallChoice::Never),
+                        template: TemplateNames::One("base".to_owned()),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "def".to_owned(),
+                    DetailedPackageReq {
+                        git: Some("https://example.com/git".to_owned()),
+                        tag: Some("v1".to_owned()),
+                        locked: Some(true),
+                        environment: [("A".to_owned(), "git".to_owned())].into(),
+                        target: Some("def-target".to_owned()),
+                        binstall: Some(BinstallChoice::Never),
+                        template: TemplateNames::Many(vec!["base".to_owned(), "git".to_owned()]),
+                        ..Default::default()
+                    },
+                ),
+            ]
+            .into(),
+        );
+    }
+
+    #[test]
+    fn test_effectiveshipconfig_templates_unknown_iserr() {
+        let err = EffectiveShipConfig::new(
+            UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
+                packages: [(
+                    "abc".to_owned(),
+                    PackageRequirement::Detailed(Box::new(DetailedPackageReq {
+                        template: TemplateNames::One("nope".to_owned()),
+                        ..Default::default()
+                    })),
+                )]
+                .into(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
+                defaults: None,
+                root: None,
+            },
+            ShipArgs::default(),
+            ShipArgs::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            r#"Failed to resolve the templates of the "abc" package."#,
+        );
+        assert_eq!(err.root_cause().to_string(), "Unknown template `nope`.");
+    }
+
+    #[test]
+    fn test_effectiveshipconfig_lockmodes_iserr() {
+        let user_config = toml::from_str::<UserConfig>(indoc!(
+            r#"
+                [packages]
+                abc = "*"
+                [defaults.ship]
+                locked-config = true
+            "#
+        ))
+        .unwrap();
+        let new_config = |env_args, cli_args| {
+            EffectiveShipConfig::new(user_config.clone(), env_args, cli_args)
+                .map(|config| (config.args.locked_config, config.args.update_lock))
+        };
+
+        assert_eq!(
+            new_config(ShipArgs::default(), ShipArgs::default()).unwrap(),
+            (true, false),
+        );
+        assert!(
+            new_config(
+                ShipArgs {
+                    update_lock: Some(true),
+                    ..Default::default()
+                },
+                ShipArgs::default(),
+            )
+            .is_err()
+        );
+        assert_eq!(
+            new_config(
+                ShipArgs {
+                    update_lock: Some(true),
+                    ..Default::default()
+                },
+                ShipArgs {
+                    locked_config: Some(false),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+            (false, true),
+        );
+    }
+
+    #[test]
+    fn test_effectiveshipconfig_selection() {
+        let user_config = toml::from_str::<UserConfig>(indoc!(
+            r#"
+                [packages]
+                abc = "*"
+                def = { version = "*", groups = ["release"] }
+                ghi = "*"
+                [groups]
+                heavy = ["def", "ghi"]
+                [defaults.ship]
+                exclude-group = ["heavy"]
+            "#
+        ))
+        .unwrap();
+        let pkg_names = |cli_args| {
+            EffectiveShipConfig::new(user_config.clone(), ShipArgs::default(), cli_args)
+                .map(|config| config.packages.into_keys().collect::<Vec<_>>())
+        };
+
+        assert_eq!(
+            pkg_names(ShipArgs::default()).unwrap(),
+            ["abc", "cargo-liner"]
+        );
+        assert_eq!(
+            pkg_names(ShipArgs {
+                group: Some(vec!["release".to_owned()]),
+                exclude_group: Some(Vec::new()),
+                ..Default::default()
+            })
+            .unwrap(),
+            ["def"],
+        );
+        assert_eq!(
+            pkg_names(ShipArgs {
+                packages: vec!["ghi".to_owned()],
+                ..Default::default()
+            })
+            .unwrap(),
+            ["ghi"],
+        );
+        assert_eq!(
+            pkg_names(ShipArgs {
+                only_self: Some(true),
+                ..Default::default()
+            })
+            .unwrap(),
+            ["cargo-liner"],
+        );
+        assert_eq!(
+            pkg_names(ShipArgs {
+                group: Some(vec!["nope".to_owned()]),
+                ..Default::default()
+            })
+            .unwrap_err()
+            .root_cause()
+            .to_string(),
+            "Unknown group `nope`.",
+        );
+    }
+
+    #[test]
+    fn test_effectiveconfigs_overlays() {
+        let user_config = toml::from_str::<UserConfig>(&format!(
+            indoc!(
+                r#"
+                    [packages]
+                    abc = "*"
+                    [target.'cfg(all())'.packages]
+                    def = "*"
+                    [target.'cfg(any())'.packages]
+                    ghi = "*"
+                    [hosts.{:?}.packages]
+                    jkl = "*"
+                "#
+            ),
+            Platform::current().hostname.unwrap(),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            EffectiveShipConfig::new(
+                user_config.clone(),
+                ShipArgs::default(),
+                ShipArgs::default()
+            )
+            .unwrap()
+            .packages
+            .into_keys()
+            .collect::<Vec<_>>(),
+            ["abc", "cargo-liner", "def", "jkl"],
+        );
+        assert_eq!(
+            EffectiveJettisonConfig::new(
+                user_config,
+                JettisonArgs::default(),
+                JettisonArgs::default()
+            )
+            .unwrap()
+            .packages
+            .into_keys()
+            .collect::<Vec<_>>(),
+            ["abc", "def", "jkl"],
+        );
+    }
+
     /// Refactor safe-guard: check that both [`crate::cli::JettisonArgs`] and
     /// [`EffectiveJettisonArgs`] have the exact same fields.
     #[test]
@@ -569,8 +1260,17 @@ mod tests {
         assert_eq!(
             EffectiveJettisonArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: None,
+                    root: None,
                 },
                 JettisonArgs::default(),
                 JettisonArgs::default(),
@@ -584,11 +1284,21 @@ mod tests {
         assert_eq!(
             EffectiveJettisonArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs::default(),
                         jettison_cmd: JettisonArgs::default(),
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 JettisonArgs::default(),
                 JettisonArgs::default(),
@@ -602,7 +1312,15 @@ mod tests {
         assert_eq!(
             EffectiveJettisonArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs::default(),
                         jettison_cmd: JettisonArgs {
@@ -610,7 +1328,9 @@ mod tests {
                             no_fail_fast: Some(false),
                             ..Default::default()
                         },
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 JettisonArgs::default(),
                 JettisonArgs::default(),
@@ -628,11 +1348,21 @@ mod tests {
         assert_eq!(
             EffectiveJettisonArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs::default(),
                         jettison_cmd: JettisonArgs::default(),
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 JettisonArgs {
                     no_confirm: Some(false),
@@ -654,11 +1384,21 @@ mod tests {
         assert_eq!(
             EffectiveJettisonArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs::default(),
                         jettison_cmd: JettisonArgs::default(),
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 JettisonArgs::default(),
                 JettisonArgs {
@@ -680,14 +1420,24 @@ mod tests {
         assert_eq!(
             EffectiveJettisonArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs::default(),
                         jettison_cmd: JettisonArgs {
                             dry_run: Some(true),
                             ..Default::default()
                         },
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 JettisonArgs {
                     no_fail_fast: Some(true),
@@ -712,7 +1462,15 @@ mod tests {
         assert_eq!(
             EffectiveJettisonArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs::default(),
                         jettison_cmd: JettisonArgs {
@@ -720,7 +1478,9 @@ mod tests {
                             no_fail_fast: Some(false),
                             ..Default::default()
                         },
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 JettisonArgs {
                     no_confirm: Some(true),
@@ -743,8 +1503,17 @@ mod tests {
         assert_eq!(
             EffectiveJettisonArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: None,
+                    root: None,
                 },
                 JettisonArgs {
                     no_confirm: Some(true),
@@ -771,7 +1540,15 @@ mod tests {
         assert_eq!(
             EffectiveJettisonArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs::default(),
                         jettison_cmd: JettisonArgs {
@@ -779,7 +1556,9 @@ mod tests {
                             no_fail_fast: Some(true),
                             ..Default::default()
                         },
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 JettisonArgs::default(),
                 JettisonArgs {
@@ -802,14 +1581,24 @@ mod tests {
         assert_eq!(
             EffectiveJettisonArgs::new(
                 &UserConfig {
+                    version: None,
+                    strict: false,
+                    include: Vec::new(),
+                    exclude: Vec::new(),
                     packages: BTreeMap::new(),
+                    templates: BTreeMap::new(),
+                    groups: BTreeMap::new(),
+                    target: BTreeMap::new(),
+                    hosts: BTreeMap::new(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs::default(),
                         jettison_cmd: JettisonArgs {
                             no_confirm: Some(false),
                             ..Default::default()
                         },
+                        package: PackageDefaults::default(),
                     }),
+                    root: None,
                 },
                 JettisonArgs {
                     no_fail_fast: Some(false),
@@ -2,6 +2,7 @@
 
 use std::env::{self, VarError};
 use std::error::Error;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use color_eyre::Section;
@@ -12,6 +13,7 @@ use crate::cli::{JettisonArgs, ShipArgs};
 const SELF_ENV_PREFIX: &str = "CARGO_LINER";
 const SHIP_ENV_PREFIX: &str = "SHIP";
 const JETTISON_ENV_PREFIX: &str = "JETTISON";
+const CONFIG_ENV_SUFFIX: &str = "CONFIG";
 
 /// Returns the full environment variable name from the given suffix to `ship`.
 #[inline]
@@ -37,6 +39,18 @@ where
     get_arg(&ship_var_name(suffix))
 }
 
+/// Retrieves a comma-separated list of `ship` argument values from the
+/// environment, empty items being ignored.
+fn get_ship_list(suffix: &str) -> Result<Option<Vec<String>>> {
+    Ok(get_ship_arg::<String>(suffix)?.map(|val| {
+        val.split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(ToOwned::to_owned)
+            .collect()
+    }))
+}
+
 /// Retrieves a single `jettison` argument value from the environment.
 ///
 /// Supports any destination type that implements string parsing.
@@ -64,7 +78,7 @@ where
                         "Could not parse an argument value from the {var_name:?} environment variable."
                     )
                 })
-                .note("Only the `true` or `false` values are accepted here, except for `binstall`.")
+                .note("Only the `true` or `false` values are accepted here, except for `binstall` and the jobs.")
                 .suggestion("Analyze your environment variables and correct the value.")?,
         )),
         Err(VarError::NotPresent) => Ok(None),
@@ -79,9 +93,19 @@ where
     }
 }
 
+/// Returns the path to the configuration file fetched from the environment, if
+/// any and not empty.
+pub fn config_env_path() -> Result<Option<PathBuf>> {
+    Ok(
+        get_arg::<PathBuf>(&format!("{SELF_ENV_PREFIX}_{CONFIG_ENV_SUFFIX}"))?
+            .filter(|path| !path.as_os_str().is_empty()),
+    )
+}
+
 /// Returns the [`ShipArgs`] fetched from the environment.
 pub fn ship_env_args() -> Result<ShipArgs> {
     Ok(ShipArgs {
+        packages: Vec::new(),
         no_self: get_ship_arg("NO_SELF")?,
         only_self: get_ship_arg("ONLY_SELF")?,
         skip_check: get_ship_arg("SKIP_CHECK")?,
@@ -89,7 +113,13 @@ pub fn ship_env_args() -> Result<ShipArgs> {
         force: get_ship_arg("FORCE")?,
         dry_run: get_ship_arg("DRY_RUN")?,
         target: get_ship_arg("TARGET")?,
+        group: get_ship_list("GROUP")?,
+        exclude_group: get_ship_list("EXCLUDE_GROUP")?,
+        locked_config: get_ship_arg("LOCKED_CONFIG")?,
+        update_lock: get_ship_arg("UPDATE_LOCK")?,
         binstall: get_ship_arg("BINSTALL")?,
+        jobs: get_ship_arg("JOBS")?,
+        lookup_jobs: get_ship_arg("LOOKUP_JOBS")?,
     })
 }
 
@@ -133,6 +163,7 @@ mod tests {
             ("CARGO_LINER_SHIP_FORCE", "false"),
             ("CARGO_LINER_SHIP_DRY_RUN", "true"),
             ("CARGO_LINER_SHIP_TARGET", "abcdef"),
+            ("CARGO_LINER_SHIP_UPDATE_LOCK", "true"),
         ];
         set_vars(&var_vals);
 
@@ -143,6 +174,28 @@ mod tests {
                 force: Some(false),
                 dry_run: Some(true),
                 target: Some("abcdef".to_owned()),
+                update_lock: Some(true),
+                ..Default::default()
+            }
+        );
+
+        remove_vars(&var_vals);
+    }
+
+    #[test]
+    fn test_singlethreaded_ship_groups_ok() {
+        let _lk = LOCK.lock().unwrap();
+        let var_vals = [
+            ("CARGO_LINER_SHIP_GROUP", "release, dev,"),
+            ("CARGO_LINER_SHIP_EXCLUDE_GROUP", ""),
+        ];
+        set_vars(&var_vals);
+
+        assert_eq!(
+            ship_env_args().unwrap(),
+            ShipArgs {
+                group: Some(vec!["release".to_owned(), "dev".to_owned()]),
+                exclude_group: Some(Vec::new()),
                 ..Default::default()
             }
         );
@@ -161,6 +214,8 @@ mod tests {
             ("CARGO_LINER_SHIP_NO_SELF", "abc"),
             ("CARGO_LINER_SHIP_FORCE", "\x01"),
             ("CARGO_LINER_SHIP_DRY_RUN", "xyz"),
+            ("CARGO_LINER_SHIP_LOCKED_CONFIG", "no"),
+            ("CARGO_LINER_SHIP_UPDATE_LOCK", "0"),
         ] {
             assert_eq!(ship_env_args().unwrap(), ShipArgs::default());
             set_vars(&[(var, val)]);
@@ -208,6 +263,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_singlethreaded_ship_jobs_ok() {
+        let _lk = LOCK.lock().unwrap();
+
+        for (val_str, val) in [("1", 1), ("4", 4), ("32", 32)] {
+            assert_eq!(ship_env_args().unwrap(), ShipArgs::default());
+            set_vars(&[("CARGO_LINER_SHIP_JOBS", val_str)]);
+            assert_eq!(ship_env_args().unwrap().jobs.unwrap().get(), val);
+            remove_vars(&[("CARGO_LINER_SHIP_JOBS", val_str)]);
+        }
+    }
+
+    #[test]
+    fn test_singlethreaded_ship_jobs_errs() {
+        let _lk = LOCK.lock().unwrap();
+
+        for val in ["", " ", "0", "-1", "a", "\x01"] {
+            assert_eq!(ship_env_args().unwrap(), ShipArgs::default());
+            set_vars(&[("CARGO_LINER_SHIP_JOBS", val)]);
+            assert!(ship_env_args().is_err());
+            remove_vars(&[("CARGO_LINER_SHIP_JOBS", val)]);
+        }
+    }
+
+    #[test]
+    fn test_singlethreaded_ship_lookupjobs_ok() {
+        let _lk = LOCK.lock().unwrap();
+
+        for (val_str, val) in [("1", 1), ("8", 8), ("64", 64)] {
+            assert_eq!(ship_env_args().unwrap(), ShipArgs::default());
+            set_vars(&[("CARGO_LINER_SHIP_LOOKUP_JOBS", val_str)]);
+            assert_eq!(ship_env_args().unwrap().lookup_jobs.unwrap().get(), val);
+            remove_vars(&[("CARGO_LINER_SHIP_LOOKUP_JOBS", val_str)]);
+        }
+    }
+
+    #[test]
+    fn test_singlethreaded_ship_lookupjobs_errs() {
+        let _lk = LOCK.lock().unwrap();
+
+        for val in ["", "0", "-1", "a"] {
+            assert_eq!(ship_env_args().unwrap(), ShipArgs::default());
+            set_vars(&[("CARGO_LINER_SHIP_LOOKUP_JOBS", val)]);
+            assert!(ship_env_args().is_err());
+            remove_vars(&[("CARGO_LINER_SHIP_LOOKUP_JOBS", val)]);
+        }
+    }
+
     #[test]
     fn test_singlethreaded_jettison_flags_ok() {
         let _lk = LOCK.lock().unwrap();
@@ -244,4 +347,20 @@ mod tests {
             remove_vars(&[(var, val)]);
         }
     }
+
+    #[test]
+    fn test_singlethreaded_config_path() {
+        let _lk = LOCK.lock().unwrap();
+        assert_eq!(config_env_path().unwrap(), None);
+
+        for (val, path) in [
+            ("", None),
+            ("-", Some(PathBuf::from("-"))),
+            ("ci-tools.toml", Some(PathBuf::from("ci-tools.toml"))),
+        ] {
+            set_vars(&[("CARGO_LINER_CONFIG", val)]);
+            assert_eq!(config_env_path().unwrap(), path);
+            remove_vars(&[("CARGO_LINER_CONFIG", val)]);
+        }
+    }
 }
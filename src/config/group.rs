@@ -0,0 +1,130 @@
+//! Named sets of packages, as found in the `groups` section and in the
+//! `groups` key of the packages.
+//!
+//! See [`PackageSelection`] in order to choose the packages to operate on by
+//! their names and groups.
+
+use std::collections::BTreeSet;
+
+/// Packages to operate on among the configured ones, chosen by name or group.
+///
+/// Nothing being given selects all the packages, which is the default.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PackageSelection {
+    /// Names of the packages to select.
+    pub names: Vec<String>,
+    /// Groups whose packages to select.
+    pub groups: Vec<String>,
+    /// Groups whose packages to leave out, unless selected by name.
+    pub excluded_groups: Vec<String>,
+}
+
+impl PackageSelection {
+    /// Builds the selection of only the given package names.
+    pub fn names<'name>(names: impl IntoIterator<Item = &'name str>) -> Self {
+        Self {
+            names: names.into_iter().map(ToOwned::to_owned).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns whether all the packages are selected, whatever their groups.
+    pub fn is_all(&self) -> bool {
+        self.names.is_empty() && self.groups.is_empty() && self.excluded_groups.is_empty()
+    }
+
+    /// Returns whether the package of the given name, belonging to the given
+    /// groups, is selected.
+    ///
+    /// Packages given by name are always selected. The other ones are when
+    /// they belong to none of the excluded groups and either to one of the
+    /// selected groups or when no name nor group is selected at all.
+    pub fn selects(&self, pkg_name: &str, pkg_groups: &BTreeSet<&str>) -> bool {
+        if self.names.iter().any(|name| name == pkg_name) {
+            return true;
+        }
+        let in_any = |groups: &[String]| {
+            groups
+                .iter()
+                .any(|group| pkg_groups.contains(group.as_str()))
+        };
+        !in_any(&self.excluded_groups)
+            && ((self.names.is_empty() && self.groups.is_empty()) || in_any(&self.groups))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the packages selected among some example ones.
+    fn selected(selection: &PackageSelection) -> Vec<&'static str> {
+        [
+            ("abc", BTreeSet::new()),
+            ("def", BTreeSet::from(["release"])),
+            ("ghi", BTreeSet::from(["release", "heavy"])),
+            ("jkl", BTreeSet::from(["heavy"])),
+        ]
+        .into_iter()
+        .filter_map(|(pkg_name, pkg_groups)| {
+            selection.selects(pkg_name, &pkg_groups).then_some(pkg_name)
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_selects_all() {
+        assert!(PackageSelection::default().is_all());
+        assert_eq!(
+            selected(&PackageSelection::default()),
+            ["abc", "def", "ghi", "jkl"],
+        );
+    }
+
+    #[test]
+    fn test_selects_names() {
+        assert!(!PackageSelection::names(["abc"]).is_all());
+        assert_eq!(
+            selected(&PackageSelection::names(["abc", "jkl"])),
+            ["abc", "jkl"],
+        );
+    }
+
+    #[test]
+    fn test_selects_groups() {
+        assert_eq!(
+            selected(&PackageSelection {
+                groups: vec!["release".to_owned()],
+                ..Default::default()
+            }),
+            ["def", "ghi"],
+        );
+        assert_eq!(
+            selected(&PackageSelection {
+                names: vec!["abc".to_owned()],
+                groups: vec!["heavy".to_owned()],
+                ..Default::default()
+            }),
+            ["abc", "ghi", "jkl"],
+        );
+    }
+
+    #[test]
+    fn test_selects_excludedgroups() {
+        assert_eq!(
+            selected(&PackageSelection {
+                excluded_groups: vec!["heavy".to_owned()],
+                ..Default::default()
+            }),
+            ["abc", "def"],
+        );
+        assert_eq!(
+            selected(&PackageSelection {
+                names: vec!["jkl".to_owned()],
+                groups: vec!["release".to_owned()],
+                excluded_groups: vec!["heavy".to_owned()],
+            }),
+            ["def", "jkl"],
+        );
+    }
+}
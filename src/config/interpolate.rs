@@ -0,0 +1,154 @@
+//! Expansion of the environment variables and of the home directory in the
+//! string options of the packages.
+//!
+//! See [`expand`] for the supported syntax.
+
+use std::env::{self, VarError};
+use std::path;
+
+use color_eyre::eyre::{Result, WrapErr, bail, eyre};
+
+/// Expands the given string using the current environment.
+///
+/// The following forms are supported:
+///  * `~` at the very start, alone or followed by a path separator, is replaced
+///    by the home directory;
+///  * `${VAR}` is replaced by the value of the `VAR` environment variable,
+///    failing if it is not set;
+///  * `${VAR:-default}` is replaced by the value of `VAR` if set and not empty,
+///    by `default` otherwise, taken literally;
+///  * `$$` is replaced by a single `$`, in order to escape the above.
+///
+/// `HOME` and `CARGO_HOME` are always defined, falling back to the default
+/// home directory and Cargo home respectively. Any other `$` is left as-is.
+pub fn expand(value: &str) -> Result<String> {
+    expand_with(value, lookup)
+}
+
+/// Returns the value of the given environment variable, if set.
+fn lookup(name: &str) -> Result<Option<String>> {
+    match env::var(name) {
+        Ok(val) => Ok(Some(val)),
+        Err(VarError::NotPresent) => Ok(match name {
+            "HOME" => home::home_dir(),
+            "CARGO_HOME" => home::cargo_home().ok(),
+            _ => None,
+        }
+        .map(|path| path.to_string_lossy().into_owned())),
+        Err(err) => {
+            Err(err).wrap_err_with(|| format!("Failed to read the `{name}` environment variable."))
+        }
+    }
+}
+
+/// Expands the given string as [`expand`] does, using the given function in
+/// order to look the variables up.
+fn expand_with(value: &str, lookup: impl Fn(&str) -> Result<Option<String>>) -> Result<String> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+
+    if let Some(after) = rest.strip_prefix('~')
+        && (after.is_empty() || after.starts_with(path::is_separator))
+    {
+        expanded.push_str(
+            &lookup("HOME")?.ok_or_else(|| eyre!("Failed to get the home directory for `~`."))?,
+        );
+        rest = after;
+    }
+
+    while let Some(idx) = rest.find('$') {
+        expanded.push_str(&rest[..idx]);
+        rest = &rest[idx + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            expanded.push('$');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('{') {
+            let Some((expr, after)) = after.split_once('}') else {
+                bail!("Unterminated `${{` in `{value}`.");
+            };
+            let (name, default) = match expr.split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (expr, None),
+            };
+            if name.is_empty() {
+                bail!("Missing variable name in `{value}`.");
+            }
+
+            match (lookup(name)?, default) {
+                (Some(val), None) => expanded.push_str(&val),
+                (Some(val), Some(_)) if !val.is_empty() => expanded.push_str(&val),
+                (_, Some(default)) => expanded.push_str(default),
+                (None, None) => bail!("Undefined environment variable `{name}`."),
+            }
+            rest = after;
+        } else {
+            expanded.push('$');
+        }
+    }
+
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Expands the given string with a fixed set of variables.
+    fn expand_test(value: &str) -> Result<String> {
+        expand_with(value, |name| {
+            Ok(match name {
+                "HOME" => Some("/home/user".to_owned()),
+                "VAR" => Some("val".to_owned()),
+                "EMPTY" => Some(String::new()),
+                _ => None,
+            })
+        })
+    }
+
+    #[test]
+    fn test_expand_literal() {
+        for value in ["", "abc", "a~b", "~user/abc", "$", "a$b", "$VAR", "100$"] {
+            assert_eq!(expand_test(value).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_expand_home() {
+        assert_eq!(expand_test("~").unwrap(), "/home/user");
+        assert_eq!(expand_test("~/abc").unwrap(), "/home/user/abc");
+        assert_eq!(expand_test("~/a/~/b").unwrap(), "/home/user/a/~/b");
+        assert_eq!(expand_test("${HOME}/abc").unwrap(), "/home/user/abc");
+        assert!(expand_with("~/abc", |_| Ok(None)).is_err());
+    }
+
+    #[test]
+    fn test_expand_vars() {
+        assert_eq!(expand_test("${VAR}").unwrap(), "val");
+        assert_eq!(expand_test("a${VAR}b${VAR}").unwrap(), "avalbval");
+        assert_eq!(expand_test("${EMPTY}").unwrap(), "");
+        assert_eq!(expand_test("${VAR:-def}").unwrap(), "val");
+        assert_eq!(expand_test("${EMPTY:-def}").unwrap(), "def");
+        assert_eq!(expand_test("${NOPE:-def}").unwrap(), "def");
+        assert_eq!(expand_test("${NOPE:-}").unwrap(), "");
+        assert_eq!(expand_test("${NOPE:-a b:-c}").unwrap(), "a b:-c");
+        assert_eq!(expand_test("$${VAR}").unwrap(), "${VAR}");
+        assert_eq!(expand_test("$$$${VAR}").unwrap(), "$${VAR}");
+    }
+
+    #[test]
+    fn test_expand_invalid_iserr() {
+        assert_eq!(
+            expand_test("a${NOPE}").unwrap_err().to_string(),
+            "Undefined environment variable `NOPE`.",
+        );
+        assert_eq!(
+            expand_test("${VAR").unwrap_err().to_string(),
+            "Unterminated `${` in `${VAR`.",
+        );
+        assert_eq!(
+            expand_test("${:-def}").unwrap_err().to_string(),
+            "Missing variable name in `${:-def}`.",
+        );
+    }
+}
@@ -0,0 +1,459 @@
+//! Layering of the several configuration files merged into a single one.
+//!
+//! See [`read_all`] in order to retrieve all the layers surrounding the user
+//! configuration file and [`merge`] in order to combine them.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use color_eyre::Section;
+use color_eyre::eyre::{Result, WrapErr, eyre};
+
+use super::UserConfig;
+
+/// Path to the system-wide configuration file, merged first when it exists.
+pub const SYSTEM_FILE_PATH: &str = "/etc/cargo-liner/liner.toml";
+
+/// Name of the directory next to the user configuration file whose TOML files
+/// are merged after it, in the order of their names.
+pub const FRAGMENTS_DIR_NAME: &str = "liner.d";
+
+/// Tables whose entries are replaced as a whole by later layers instead of
+/// being merged key by key, at the top level or nested such as in overlays.
+const WHOLE_ENTRIES_KEYS: &[&str] = &["packages", "templates"];
+
+/// Sections holding overlays, each with its own `packages` table.
+const OVERLAY_SECTIONS: &[&str] = &["target", "hosts"];
+
+/// Configuration file read as one of the layers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layer {
+    /// Path to the file the layer was read from.
+    pub path: PathBuf,
+    /// Raw contents of the file.
+    pub contents: String,
+}
+
+impl Layer {
+    /// Parses the contents of the layer as a TOML table.
+    pub fn parse(&self) -> Result<toml::Table> {
+        self.contents
+            .parse::<toml::Table>()
+            .wrap_err_with(|| format!("Failed to parse the configuration file at {:?}.", self.path))
+            .note("This can easily happen as the file is edited manually.")
+            .suggestion("Check the file for any typos and syntax errors.")
+    }
+
+    /// Returns the paths the layer includes, relative ones being resolved
+    /// from the directory containing it.
+    fn includes(&self, table: &toml::Table) -> Result<Vec<PathBuf>> {
+        let Some(includes) = table.get("include") else {
+            return Ok(Vec::new());
+        };
+        let dir = self.path.parent().unwrap_or_else(|| Path::new(""));
+        Ok(includes
+            .clone()
+            .try_into::<Vec<PathBuf>>()
+            .wrap_err_with(|| format!("Invalid `include` in the file at {:?}.", self.path))
+            .suggestion("Use a list of paths to other configuration files.")?
+            .into_iter()
+            .map(|path| dir.join(path))
+            .collect())
+    }
+}
+
+/// Reads all the configuration layers in order of increasing precedence,
+/// given the already-read user configuration file.
+///
+/// The system-wide file comes first, then the user file, then the files of
+/// the fragments directory and finally the included ones. Only the included
+/// files must exist, and each file is read at most once. When the user file
+/// is read from the standard input, there is no fragments directory and its
+/// inclusions are resolved from the current directory. Layers that cannot
+/// be parsed are kept as-is without following their inclusions, so that the
+/// error is reported when merging them.
+///
+/// A project configuration, as told by `is_project`, only gets its included
+/// files merged: neither the system-wide file nor a fragments directory next
+/// to it are read, as their packages are meant for Cargo's default root.
+pub fn read_all(user_path: &Path, user_str: String, is_project: bool) -> Result<Vec<Layer>> {
+    let mut layers = Vec::new();
+    if is_project {
+        log::debug!("Project configuration: skipping the system and fragment layers.");
+    } else if let Some(layer) = read_optional(Path::new(SYSTEM_FILE_PATH))? {
+        layers.push(layer);
+    }
+    layers.push(Layer {
+        path: user_path.to_owned(),
+        contents: user_str,
+    });
+    if !is_project && !UserConfig::is_stdin(user_path) {
+        for path in fragment_paths(&user_path.with_file_name(FRAGMENTS_DIR_NAME))? {
+            layers.extend(read_optional(&path)?);
+        }
+    }
+
+    let mut seen = layers
+        .iter()
+        .map(|layer| canonical(&layer.path))
+        .collect::<BTreeSet<_>>();
+    // Included files can themselves include others.
+    let mut idx = 0;
+    while let Some(layer) = layers.get(idx) {
+        idx += 1;
+        let Ok(table) = layer.parse() else {
+            continue;
+        };
+        for path in layer.includes(&table)? {
+            if !seen.insert(canonical(&path)) {
+                log::debug!("Skipping {path:#?}: already read.");
+                continue;
+            }
+            let layer = read_optional(&path)?
+                .ok_or_else(|| eyre!("The included configuration file {path:?} does not exist."))
+                .suggestion("Check the `include` paths of the configuration files.")?;
+            layers.push(layer);
+        }
+    }
+
+    log::debug!(
+        "Configuration layers: {:#?}.",
+        layers.iter().map(|layer| &layer.path).collect::<Vec<_>>(),
+    );
+    Ok(layers)
+}
+
+/// Merges the given layers, the later ones taking precedence, into a single
+/// table representing the whole configuration.
+///
+/// Entries of the `packages` and `templates` tables, including the `packages`
+/// tables of the overlays, are replaced as a whole, while other tables are
+/// merged key by key. The packages listed by the `exclude` key of a layer are
+/// removed from the earlier ones, overlays included. The `include` and
+/// `exclude` keys are consumed and thus absent from the result.
+pub fn merge<'layer>(layers: impl IntoIterator<Item = &'layer Layer>) -> Result<toml::Table> {
+    let mut merged = toml::Table::new();
+
+    for layer in layers {
+        let mut table = layer.parse()?;
+        table.remove("include");
+        if let Some(excluded) = table.remove("exclude") {
+            let excluded = excluded
+                .try_into::<Vec<String>>()
+                .wrap_err_with(|| format!("Invalid `exclude` in the file at {:?}.", layer.path))
+                .suggestion("Use a list of package names.")?;
+            exclude_packages(&mut merged, &excluded);
+            for section in OVERLAY_SECTIONS {
+                for overlay in merged
+                    .get_mut(*section)
+                    .and_then(toml::Value::as_table_mut)
+                    .into_iter()
+                    .flat_map(toml::Table::iter_mut)
+                    .filter_map(|(_, overlay)| overlay.as_table_mut())
+                {
+                    exclude_packages(overlay, &excluded);
+                }
+            }
+        }
+        merge_tables(&mut merged, table, WHOLE_ENTRIES_KEYS);
+    }
+
+    Ok(merged)
+}
+
+/// Removes the given packages from the `packages` table of the given one.
+fn exclude_packages(table: &mut toml::Table, excluded: &[String]) {
+    if let Some(pkgs) = table
+        .get_mut("packages")
+        .and_then(toml::Value::as_table_mut)
+    {
+        for pkg_name in excluded {
+            pkgs.remove(pkg_name);
+        }
+    }
+}
+
+/// Merges the table of a later layer into the given one, recursively except
+/// for the tables of the given keys, at any depth, that only get their
+/// entries replaced.
+fn merge_tables(base: &mut toml::Table, layer: toml::Table, whole_entries_keys: &[&str]) {
+    for (key, val) in layer {
+        match (base.get_mut(&key), val) {
+            (Some(toml::Value::Table(base_tbl)), toml::Value::Table(tbl)) => {
+                if whole_entries_keys.contains(&key.as_str()) {
+                    base_tbl.extend(tbl);
+                } else {
+                    merge_tables(base_tbl, tbl, whole_entries_keys);
+                }
+            }
+            (_, val) => {
+                base.insert(key, val);
+            }
+        }
+    }
+}
+
+/// Reads the file at the given path as a layer, returning `None` if it does
+/// not exist.
+fn read_optional(path: &Path) -> Result<Option<Layer>> {
+    log::debug!("Reading configuration layer from {path:#?}...");
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(Layer {
+            path: path.to_owned(),
+            contents,
+        })),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err)
+            .wrap_err_with(|| format!("Failed to read the configuration file at {path:?}."))
+            .note("This can happen for many reasons.")
+            .suggestion("Check if the file has the correct permissions."),
+    }
+}
+
+/// Returns the paths to the TOML files of the given directory, sorted, or
+/// nothing if it does not exist.
+fn fragment_paths(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => {
+            return Err(err)
+                .wrap_err_with(|| format!("Failed to read the directory at {dir:?}."))
+                .suggestion("Check if the directory has the correct permissions.");
+        }
+    };
+    let mut paths = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()
+        .wrap_err_with(|| format!("Failed to list the directory at {dir:?}."))?;
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "toml") && path.is_file());
+    paths.sort();
+    Ok(paths)
+}
+
+/// Returns the canonical form of the given path, or the path itself if it
+/// cannot be resolved.
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    /// Builds a layer from the given contents, at a dummy path.
+    fn layer(contents: &str) -> Layer {
+        Layer {
+            path: PathBuf::from("liner.toml"),
+            contents: contents.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_merge_laterwins() {
+        assert_eq!(
+            merge(&[
+                layer(indoc!(
+                    r#"
+                        strict = true
+                        [packages]
+                        abc = "1"
+                        def = { version = "2", locked = true }
+                        [templates.tpl]
+                        locked = true
+                        [defaults.ship]
+                        no-self = true
+                        skip-check = true
+                    "#
+                )),
+                layer(indoc!(
+                    r#"
+                        strict = false
+                        [packages]
+                        def = { version = "3" }
+                        ghi = "4"
+                        [templates.tpl]
+                        frozen = true
+                        [defaults.ship]
+                        skip-check = false
+                    "#
+                )),
+            ])
+            .unwrap(),
+            indoc!(
+                r#"
+                    strict = false
+                    [packages]
+                    abc = "1"
+                    def = { version = "3" }
+                    ghi = "4"
+                    [templates.tpl]
+                    frozen = true
+                    [defaults.ship]
+                    no-self = true
+                    skip-check = false
+                "#
+            )
+            .parse::<toml::Table>()
+            .unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_merge_overlays() {
+        assert_eq!(
+            merge(&[
+                layer(indoc!(
+                    r#"
+                        [packages]
+                        abc = "1"
+                        [target.'cfg(unix)'.packages]
+                        def = { version = "2", locked = true }
+                        [hosts.abc.packages]
+                        ghi = "3"
+                    "#
+                )),
+                layer(indoc!(
+                    r#"
+                        [target.'cfg(unix)'.packages]
+                        def = { version = "4" }
+                        jkl = "5"
+                        [hosts.def.packages]
+                        mno = "6"
+                    "#
+                )),
+            ])
+            .unwrap(),
+            indoc!(
+                r#"
+                    [packages]
+                    abc = "1"
+                    [target.'cfg(unix)'.packages]
+                    def = { version = "4" }
+                    jkl = "5"
+                    [hosts.abc.packages]
+                    ghi = "3"
+                    [hosts.def.packages]
+                    mno = "6"
+                "#
+            )
+            .parse::<toml::Table>()
+            .unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_merge_exclude() {
+        assert_eq!(
+            merge(&[
+                layer("[packages]\nabc = '1'\ndef = '2'\n"),
+                layer(
+                    "exclude = ['abc', 'xyz']\ninclude = ['other.toml']\n[packages]\nghi = '3'\n"
+                ),
+                layer("[packages]\nabc = '4'\n"),
+            ])
+            .unwrap(),
+            "[packages]\nabc = '4'\ndef = '2'\nghi = '3'\n"
+                .parse::<toml::Table>()
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_merge_exclude_overlays() {
+        assert_eq!(
+            merge(&[
+                layer(indoc!(
+                    r#"
+                        [target.'cfg(unix)'.packages]
+                        abc = "1"
+                        def = "2"
+                        [hosts.abc.packages]
+                        abc = "3"
+                    "#
+                )),
+                layer("exclude = ['abc']\n"),
+            ])
+            .unwrap(),
+            indoc!(
+                r#"
+                    [target.'cfg(unix)'.packages]
+                    def = "2"
+                    [hosts.abc.packages]
+                "#
+            )
+            .parse::<toml::Table>()
+            .unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_merge_invalid_iserr() {
+        assert!(merge(&[layer("[packages]\nabc = '1'\n"), layer("[packages")]).is_err());
+        assert!(merge(&[layer("exclude = 'abc'\n")]).is_err());
+    }
+
+    #[test]
+    fn test_readall_fragmentsandincludes() {
+        let dir = tempfile::tempdir().unwrap();
+        let user_path = dir.path().join("liner.toml");
+        let frags_dir = dir.path().join(FRAGMENTS_DIR_NAME);
+        fs::create_dir(&frags_dir).unwrap();
+        fs::write(frags_dir.join("b.toml"), "include = ['../inc.toml']\n").unwrap();
+        fs::write(frags_dir.join("a.toml"), "[packages]\n").unwrap();
+        fs::write(frags_dir.join("c.txt"), "ignored").unwrap();
+        fs::write(dir.path().join("inc.toml"), "include = ['liner.toml']\n").unwrap();
+
+        let layers = read_all(&user_path, "include = ['inc.toml']\n".to_owned(), false).unwrap();
+        // The system file may exist on the host running the tests.
+        let paths = layers
+            .iter()
+            .map(|layer| layer.path.clone())
+            .skip_while(|path| path != &user_path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                user_path.clone(),
+                frags_dir.join("a.toml"),
+                frags_dir.join("b.toml"),
+                dir.path().join("inc.toml"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_readall_project_onlyincludes() {
+        let dir = tempfile::tempdir().unwrap();
+        let user_path = dir.path().join("liner.toml");
+        let frags_dir = dir.path().join(FRAGMENTS_DIR_NAME);
+        fs::create_dir(&frags_dir).unwrap();
+        fs::write(frags_dir.join("a.toml"), "[packages]\n").unwrap();
+        fs::write(dir.path().join("inc.toml"), "[packages]\n").unwrap();
+
+        let layers = read_all(&user_path, "include = ['inc.toml']\n".to_owned(), true).unwrap();
+        assert_eq!(
+            layers
+                .iter()
+                .map(|layer| layer.path.clone())
+                .collect::<Vec<_>>(),
+            [user_path.clone(), dir.path().join("inc.toml")],
+        );
+    }
+
+    #[test]
+    fn test_readall_missinginclude_iserr() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(
+            read_all(
+                &dir.path().join("liner.toml"),
+                "include = ['missing.toml']\n".to_owned(),
+                false,
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,427 @@
+//! The lock file, e.g. `liner.lock`, recording what the configured packages
+//! were actually installed as, for reproducible sets of tools.
+//!
+//! See [`LinerLock::record`] in order to fill it from Cargo's own save files
+//! and [`LinerLock::pin`] in order to install the recorded versions again.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use color_eyre::Section;
+use color_eyre::eyre::{Result, WrapErr, bail, eyre};
+use semver::{Op, Version};
+use serde::{Deserialize, Serialize};
+
+use super::cargo_crates_toml::{self, SourceKind};
+use super::{CargoCratesToml, DetailedPackageReq, InstallInfo, UserConfig, project};
+
+/// Representation of the lock file written next to the configuration file,
+/// e.g. `liner.lock` for `liner.toml`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LinerLock {
+    /// Version of the layout of the file.
+    pub version: u32,
+    /// The name-to-entry map of the locked packages.
+    #[serde(default)]
+    pub packages: BTreeMap<String, LockedPackage>,
+}
+
+/// What a package was installed as, according to Cargo.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LockedPackage {
+    /// The exact installed version.
+    pub version: Version,
+    /// The source it was installed from, as Cargo writes it, without the
+    /// commit.
+    pub source: String,
+    /// The installed commit, for packages installed from Git repositories.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// The features it was installed with.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub features: BTreeSet<String>,
+    /// The target it was compiled for, recorded for reference only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+impl Default for LinerLock {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            packages: BTreeMap::new(),
+        }
+    }
+}
+
+impl LinerLock {
+    /// The extension of the lock file, replacing the one of the configuration
+    /// file next to which it is written.
+    pub const EXTENSION: &'static str = "lock";
+
+    /// The name of the lock file of a configuration held by a Cargo manifest,
+    /// as `Cargo.lock` is already Cargo's.
+    pub const MANIFEST_FILE_NAME: &'static str = "liner.lock";
+
+    /// Version of the layout of the file written by this version of Cargo
+    /// Liner.
+    pub const VERSION: u32 = 1;
+
+    /// Header written at the top of the file.
+    const HEADER: &'static str = "# This file is automatically generated by Cargo Liner.\n\
+                                  # It is not intended for manual editing.\n";
+
+    /// Returns the [`PathBuf`] pointing to the lock file of the given or
+    /// default configuration file, or nothing if the configuration is read
+    /// from the standard input: see [`UserConfig::location`].
+    pub fn file_path(config_path: Option<&Path>) -> Result<Option<PathBuf>> {
+        let path = UserConfig::file_path(config_path)
+            .wrap_err("Failed to build the configuration file path.")?;
+        Ok((!UserConfig::is_stdin(&path)).then(|| Self::config_lock_path(&path)))
+    }
+
+    /// Returns the path of the lock file of the configuration file at the
+    /// given path: the same file stem with the `lock` extension, next to it.
+    fn config_lock_path(config_path: &Path) -> PathBuf {
+        if project::is_cargo_manifest(config_path) {
+            config_path.with_file_name(Self::MANIFEST_FILE_NAME)
+        } else {
+            config_path.with_extension(Self::EXTENSION)
+        }
+    }
+
+    /// Parses the lock file at the given path, returning `None` if it does not
+    /// exist.
+    pub fn parse_file(path: &Path) -> Result<Option<Self>> {
+        log::debug!("Reading the lock file from {path:#?}...");
+        let lock_str = match fs::read_to_string(path) {
+            Ok(lock_str) => lock_str,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                log::debug!("File not found.");
+                return Ok(None);
+            }
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| format!("Failed to read the lock file at {path:?}."))
+                    .note("This can happen for many reasons.")
+                    .suggestion("Check if the file has the correct permissions.");
+            }
+        };
+        log::trace!("Read {} bytes.", lock_str.len());
+        log::debug!("Deserializing the lock file...");
+        let lock = toml::from_str::<Self>(&lock_str)
+            .wrap_err_with(|| format!("Failed to deserialize the lock file at {path:?}."))
+            .note("This should not easily happen as the file is automatically maintained.")
+            .suggestion("Delete the file in order to start again from scratch.")?;
+        if lock.version > Self::VERSION {
+            return Err(eyre!(
+                "The lock file is written for version {} of its layout, but only up to version \
+                 {} is supported.",
+                lock.version,
+                Self::VERSION,
+            ))
+            .suggestion("Update Cargo Liner to its latest version.");
+        }
+        log::trace!("Got: {lock:#?}.");
+        Ok(Some(lock))
+    }
+
+    /// Writes the lock file to the given path.
+    pub fn save_file(&self, path: &Path) -> Result<()> {
+        log::debug!("Serializing the lock file...");
+        let lock_str = toml::to_string_pretty(self)
+            .wrap_err("Failed to serialize the lock file.")
+            .note("This should not easily happen.")
+            .suggestion(crate::OPEN_ISSUE_MSG)?;
+        log::debug!("Writing the lock file to {path:#?}...");
+        fs::write(path, format!("{}\n{lock_str}", Self::HEADER))
+            .wrap_err_with(|| format!("Failed to write the lock file to {path:?}."))
+            .note("This can happen for many reasons.")
+            .suggestion("Check the permissions of the directory and of the file.")
+    }
+
+    /// Records the given packages as found installed in the given documents,
+    /// returning whether anything changed.
+    ///
+    /// Locked packages that are not among the given configured ones anymore
+    /// are dropped. Packages already locked are left as-is unless `update` is
+    /// `true`, and the ones not installed are skipped.
+    pub fn record(
+        &mut self,
+        pkgs: &BTreeMap<String, DetailedPackageReq>,
+        configured: &BTreeSet<String>,
+        cct: CargoCratesToml,
+        mut infos: BTreeMap<String, InstallInfo>,
+        update: bool,
+    ) -> bool {
+        let mut installed = cct.into_name_packages();
+        let locked_count = self.packages.len();
+
+        self.packages.retain(|pkg_name, _| {
+            let is_configured = configured.contains(pkg_name);
+            if !is_configured {
+                log::debug!("{pkg_name:?} is not configured anymore: unlocking it.");
+            }
+            is_configured
+        });
+        let mut changed = self.packages.len() != locked_count;
+
+        for pkg_name in pkgs.keys() {
+            if !update && self.packages.contains_key(pkg_name) {
+                continue;
+            }
+            let Some(mut pkg) = installed.remove(pkg_name) else {
+                log::debug!("{pkg_name:?} is not installed: not locking it.");
+                continue;
+            };
+
+            let commit = if pkg.source.kind == SourceKind::Git {
+                let commit = pkg.source.url.fragment().map(ToOwned::to_owned);
+                pkg.source.url.set_fragment(None);
+                commit
+            } else {
+                None
+            };
+            let info = infos.remove(pkg_name).unwrap_or_default();
+            let locked = LockedPackage {
+                version: pkg.version,
+                source: pkg.source.to_string(),
+                commit,
+                features: info.features,
+                target: info.target,
+            };
+
+            if self.packages.get(pkg_name) != Some(&locked) {
+                log::debug!("Locking {pkg_name:?} to {locked:?}.");
+                self.packages.insert(pkg_name.clone(), locked);
+                changed = true;
+            }
+        }
+
+        changed
+    }
+
+    /// Returns the given package requirement pinned to its locked version, or
+    /// commit if sourced from a Git repository, failing if it is not locked.
+    ///
+    /// Packages sourced from local paths are left unchanged as their contents
+    /// cannot be pinned.
+    pub fn pin(&self, pkg_name: &str, mut pkg: DetailedPackageReq) -> Result<DetailedPackageReq> {
+        let Some(locked) = self.packages.get(pkg_name) else {
+            bail!("The {pkg_name:?} package is not locked.");
+        };
+
+        if pkg.features.iter().cloned().collect::<BTreeSet<_>>() != locked.features {
+            log::warn!(
+                "The features of {pkg_name:?} differ from its locked ones: run `cargo liner ship \
+                 --update-lock` in order to record the new ones.",
+            );
+        }
+
+        if pkg.path.is_some() {
+            log::debug!("{pkg_name:?} is sourced from a local path: not pinning it.");
+        } else if pkg.git.is_some() {
+            let Some(commit) = &locked.commit else {
+                bail!("The {pkg_name:?} package is locked without any commit.");
+            };
+            pkg.branch = None;
+            pkg.tag = None;
+            pkg.rev = Some(commit.clone());
+        } else {
+            pkg.version = cargo_crates_toml::ver_to_req(&locked.version, Op::Exact);
+        }
+
+        Ok(pkg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use semver::VersionReq;
+
+    use super::*;
+
+    /// Returns an example document of installed packages.
+    fn example_cct() -> CargoCratesToml {
+        toml::from_str(indoc!(
+            r#"
+                [v1]
+                "abc 1.2.3 (registry+https://github.com/rust-lang/crates.io-index)" = ["abc"]
+                "def 0.1.0 (git+https://example.com/def.git?branch=dev#0123456789abcdef0123456789abcdef01234567)" = ["def"]
+                "ghi 0.0.1 (path+file:///a/b/ghi)" = ["ghi"]
+                "jkl 2.0.0 (registry+https://github.com/rust-lang/crates.io-index)" = ["jkl"]
+            "#
+        ))
+        .unwrap()
+    }
+
+    /// Returns the given packages with default requirements.
+    fn example_pkgs(pkg_names: &[&str]) -> BTreeMap<String, DetailedPackageReq> {
+        pkg_names
+            .iter()
+            .map(|pkg_name| ((*pkg_name).to_owned(), DetailedPackageReq::default()))
+            .collect()
+    }
+
+    /// Returns the names of the given packages.
+    fn names(pkgs: &BTreeMap<String, DetailedPackageReq>) -> BTreeSet<String> {
+        pkgs.keys().cloned().collect()
+    }
+
+    #[test]
+    fn test_linerlock_configlockpath() {
+        assert_eq!(
+            LinerLock::config_lock_path(Path::new("/a/liner.toml")),
+            Path::new("/a/liner.lock"),
+        );
+        assert_eq!(
+            LinerLock::config_lock_path(Path::new("/a/ci-tools.toml")),
+            Path::new("/a/ci-tools.lock"),
+        );
+        assert_eq!(
+            LinerLock::config_lock_path(Path::new("/a/tools")),
+            Path::new("/a/tools.lock"),
+        );
+        assert_eq!(
+            LinerLock::config_lock_path(Path::new("/a/Cargo.toml")),
+            Path::new("/a/liner.lock"),
+        );
+    }
+
+    #[test]
+    fn test_linerlock_record() {
+        let mut lock = LinerLock::default();
+        let infos = BTreeMap::from([(
+            "abc".to_owned(),
+            InstallInfo {
+                features: BTreeSet::from(["feat".to_owned()]),
+                target: Some("x86_64-unknown-linux-gnu".to_owned()),
+                ..Default::default()
+            },
+        )]);
+        let pkgs = example_pkgs(&["abc", "def", "ghi", "nope"]);
+        assert!(lock.record(&pkgs, &names(&pkgs), example_cct(), infos.clone(), false,));
+        assert_eq!(
+            toml::to_string_pretty(&lock).unwrap(),
+            indoc!(
+                r#"
+                    version = 1
+
+                    [packages.abc]
+                    version = "1.2.3"
+                    source = "registry+https://github.com/rust-lang/crates.io-index"
+                    features = ["feat"]
+                    target = "x86_64-unknown-linux-gnu"
+
+                    [packages.def]
+                    version = "0.1.0"
+                    source = "git+https://example.com/def.git?branch=dev"
+                    commit = "0123456789abcdef0123456789abcdef01234567"
+
+                    [packages.ghi]
+                    version = "0.0.1"
+                    source = "path+file:///a/b/ghi"
+                "#
+            ),
+        );
+        assert!(!lock.record(
+            &example_pkgs(&["abc", "def"]),
+            &names(&pkgs),
+            example_cct(),
+            infos.clone(),
+            true,
+        ));
+    }
+
+    #[test]
+    fn test_linerlock_record_dropsunconfigured() {
+        let mut lock = LinerLock::default();
+        let pkgs = example_pkgs(&["abc", "def", "ghi"]);
+        lock.record(&pkgs, &names(&pkgs), example_cct(), BTreeMap::new(), false);
+
+        let configured = BTreeSet::from(["abc".to_owned(), "def".to_owned()]);
+        assert!(lock.record(
+            &example_pkgs(&["abc"]),
+            &configured,
+            example_cct(),
+            BTreeMap::new(),
+            false,
+        ));
+        assert_eq!(lock.packages.keys().collect::<Vec<_>>(), ["abc", "def"]);
+        assert!(!lock.record(
+            &example_pkgs(&["abc"]),
+            &configured,
+            example_cct(),
+            BTreeMap::new(),
+            false,
+        ));
+    }
+
+    #[test]
+    fn test_linerlock_record_keepslocked() {
+        let mut lock = LinerLock::default();
+        lock.packages.insert(
+            "abc".to_owned(),
+            LockedPackage {
+                version: Version::new(1, 0, 0),
+                source: "registry+https://github.com/rust-lang/crates.io-index".to_owned(),
+                commit: None,
+                features: BTreeSet::new(),
+                target: None,
+            },
+        );
+        let pkgs = example_pkgs(&["abc", "jkl"]);
+
+        assert!(lock.record(&pkgs, &names(&pkgs), example_cct(), BTreeMap::new(), false));
+        assert_eq!(lock.packages["abc"].version, Version::new(1, 0, 0));
+        assert_eq!(lock.packages["jkl"].version, Version::new(2, 0, 0));
+
+        assert!(lock.record(&pkgs, &names(&pkgs), example_cct(), BTreeMap::new(), true));
+        assert_eq!(lock.packages["abc"].version, Version::new(1, 2, 3));
+    }
+
+    #[test]
+    fn test_linerlock_pin() {
+        let mut lock = LinerLock::default();
+        let pkgs = example_pkgs(&["abc", "def", "ghi"]);
+        lock.record(&pkgs, &names(&pkgs), example_cct(), BTreeMap::new(), false);
+
+        assert_eq!(
+            lock.pin("abc", DetailedPackageReq::default())
+                .unwrap()
+                .version,
+            "=1.2.3".parse::<VersionReq>().unwrap(),
+        );
+        let git_pkg = DetailedPackageReq {
+            git: Some("https://example.com/def.git".to_owned()),
+            branch: Some("dev".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            lock.pin("def", git_pkg.clone()).unwrap(),
+            DetailedPackageReq {
+                branch: None,
+                rev: Some("0123456789abcdef0123456789abcdef01234567".to_owned()),
+                ..git_pkg
+            },
+        );
+        let path_pkg = DetailedPackageReq {
+            path: Some("/a/b/ghi".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(lock.pin("ghi", path_pkg.clone()).unwrap(), path_pkg);
+        assert!(lock.pin("jkl", DetailedPackageReq::default()).is_err());
+    }
+
+    #[test]
+    fn test_deser_linerlock() {
+        assert_eq!(
+            toml::from_str::<LinerLock>("version = 1").unwrap(),
+            LinerLock::default(),
+        );
+        assert!(toml::from_str::<LinerLock>("").is_err());
+        assert!(toml::from_str::<LinerLock>("version = 1\n[packages.abc]\nversion = '1'").is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! Versioning of the layout of the configuration files and migration of the
+//! outdated ones to the current layout.
+//!
+//! See [`version`] in order to read the version a configuration file is
+//! written for and [`migrate`] in order to rewrite it to the current one.
+
+use color_eyre::Section;
+use color_eyre::eyre::{Result, bail, eyre};
+use toml_edit::{DocumentMut, value};
+
+/// Version of the layout of the configuration files supported by this version
+/// of Cargo Liner. Files without any `version` key are of the first version.
+pub const CURRENT_VERSION: u32 = 1;
+
+/// Rewrite of the layout of a version of the configuration files into the one
+/// of the next version.
+#[derive(Debug)]
+struct Migration {
+    /// Version of the layout the migration applies to.
+    from: u32,
+    /// Short description of what the migration changes.
+    summary: &'static str,
+    /// Rewrites the given document in place.
+    apply: fn(&mut DocumentMut),
+}
+
+/// All the migrations, in order of increasing versions.
+const MIGRATIONS: &[Migration] = &[];
+
+/// Returns the version of the layout the given configuration table is written
+/// for, failing if it is invalid or not supported by this version of Cargo
+/// Liner.
+pub fn version(table: &toml::Table) -> Result<u32> {
+    parse_version(
+        table
+            .get("version")
+            .map(|version| integer_version(version.as_integer()))
+            .transpose()?,
+        CURRENT_VERSION,
+    )
+}
+
+/// Migrates the given configuration document in place to the current layout,
+/// only touching what changed, and returns the summaries of the migrations
+/// applied, if any.
+pub fn migrate(doc: &mut DocumentMut) -> Result<Vec<&'static str>> {
+    migrate_with(doc, MIGRATIONS, CURRENT_VERSION)
+}
+
+/// Returns the given `version` value, failing if it is not an integer.
+fn integer_version(version: Option<i64>) -> Result<i64> {
+    version.ok_or_else(|| eyre!("The `version` key must be an integer."))
+}
+
+/// Checks the given optional `version` value, failing if it is outside of the
+/// supported versions, up to the given current one.
+fn parse_version(version: Option<i64>, current: u32) -> Result<u32> {
+    let Some(version) = version else {
+        return Ok(1);
+    };
+    match u32::try_from(version) {
+        Ok(version @ 1..) if version <= current => Ok(version),
+        Ok(1..) => Err(eyre!(
+            "The configuration file is written for version {version} of the layout, but only up \
+             to version {current} is supported."
+        ))
+        .suggestion("Update Cargo Liner to its latest version."),
+        _ => bail!("Invalid version {version}: versions start at 1."),
+    }
+}
+
+/// Migrates the given document as [`migrate`] does, using the given
+/// migrations up to the given current version.
+fn migrate_with(
+    doc: &mut DocumentMut,
+    migrations: &[Migration],
+    current: u32,
+) -> Result<Vec<&'static str>> {
+    let mut version = parse_version(
+        doc.get("version")
+            .map(|version| integer_version(version.as_integer()))
+            .transpose()?,
+        current,
+    )?;
+    let mut summaries = Vec::new();
+
+    while version < current {
+        let migration = migrations
+            .iter()
+            .find(|migration| migration.from == version)
+            .ok_or_else(|| eyre!("No migration from version {version} of the layout."))
+            .suggestion(crate::OPEN_ISSUE_MSG)?;
+        log::debug!("Migrating from version {version}: {}...", migration.summary);
+        (migration.apply)(doc);
+        summaries.push(migration.summary);
+        version += 1;
+    }
+
+    if !summaries.is_empty() {
+        doc.insert("version", value(i64::from(current)));
+    }
+    Ok(summaries)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use toml_edit::Item;
+
+    use super::*;
+
+    /// Example migrations renaming a package option, then a top-level key.
+    const TEST_MIGRATIONS: &[Migration] = &[
+        Migration {
+            from: 1,
+            summary: "rename `keep-going` to `no-fail-fast`",
+            apply: |doc| {
+                let Some(pkgs) = doc.get_mut("packages").and_then(Item::as_table_like_mut) else {
+                    return;
+                };
+                for (_, pkg) in pkgs.iter_mut() {
+                    if let Some(pkg) = pkg.as_table_like_mut()
+                        && let Some(item) = pkg.remove("keep-going")
+                    {
+                        pkg.insert("no-fail-fast", item);
+                    }
+                }
+            },
+        },
+        Migration {
+            from: 2,
+            summary: "remove `retired`",
+            apply: |doc| {
+                doc.remove("retired");
+            },
+        },
+    ];
+
+    #[test]
+    fn test_version() {
+        assert_eq!(version(&toml::Table::new()).unwrap(), 1);
+        assert_eq!(
+            version(&"version = 1".parse::<toml::Table>().unwrap()).unwrap(),
+            1
+        );
+        for config_str in [
+            "version = 0",
+            "version = -1",
+            "version = 2",
+            "version = '1'",
+        ] {
+            assert!(
+                version(&config_str.parse::<toml::Table>().unwrap()).is_err(),
+                "{config_str:?}",
+            );
+        }
+    }
+
+    #[test]
+    fn test_migrate_current_isunchanged() {
+        for config_str in ["[packages]\nabc = '1'\n", "version = 1\n[packages]\n"] {
+            let mut doc = config_str.parse::<DocumentMut>().unwrap();
+            assert!(migrate(&mut doc).unwrap().is_empty());
+            assert_eq!(doc.to_string(), config_str);
+        }
+    }
+
+    #[test]
+    fn test_migratewith_all() {
+        let mut doc = indoc!(
+            r#"
+                # Comment.
+                retired = true
+
+                [packages]
+                abc = "1"
+                def = { version = "2", keep-going = true } # Other.
+            "#
+        )
+        .parse::<DocumentMut>()
+        .unwrap();
+        assert_eq!(
+            migrate_with(&mut doc, TEST_MIGRATIONS, 3).unwrap(),
+            ["rename `keep-going` to `no-fail-fast`", "remove `retired`"],
+        );
+        assert_eq!(
+            doc.to_string(),
+            indoc!(
+                r#"
+                    version = 3
+
+                    [packages]
+                    abc = "1"
+                    def = { version = "2", no-fail-fast = true } # Other.
+                "#
+            ),
+        );
+    }
+
+    #[test]
+    fn test_migratewith_partial() {
+        let mut doc = "version = 2\nretired = 1\n[packages]\n"
+            .parse::<DocumentMut>()
+            .unwrap();
+        assert_eq!(
+            migrate_with(&mut doc, TEST_MIGRATIONS, 3).unwrap(),
+            ["remove `retired`"],
+        );
+        assert_eq!(doc.to_string(), "version = 3\n[packages]\n");
+    }
+
+    #[test]
+    fn test_migratewith_missing_iserr() {
+        let mut doc = "[packages]\n".parse::<DocumentMut>().unwrap();
+        assert!(migrate_with(&mut doc, &TEST_MIGRATIONS[1..], 3).is_err());
+        let mut doc = "version = 4\n[packages]\n".parse::<DocumentMut>().unwrap();
+        assert!(migrate_with(&mut doc, TEST_MIGRATIONS, 3).is_err());
+    }
+}
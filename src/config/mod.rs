@@ -3,13 +3,29 @@
 //! See [`UserConfig::parse_file`] in order to retrieve such configuration
 //! settings from the default file.
 
+mod cargo_crates2_json;
 mod cargo_crates_toml;
+mod document;
 mod effective_config;
 pub mod env;
+mod group;
+mod interpolate;
+mod layers;
+mod liner_lock;
+mod migration;
 mod package;
+mod platform;
+mod project;
+mod template;
 mod user_config;
+mod validation;
 
 pub use cargo_crates_toml::CargoCratesToml;
+pub use cargo_crates2_json::{CargoCrates2Json, InstallInfo};
 pub use effective_config::{EffectiveJettisonConfig, EffectiveShipConfig};
-pub use package::{DetailedPackageReq, PackageRequirement};
+pub use group::PackageSelection;
+pub use liner_lock::LinerLock;
+pub use package::{DetailedPackageReq, PackageDefaults, PackageRequirement};
+pub use platform::{PackageOverlay, Platform};
+pub use template::PackageTemplate;
 pub use user_config::UserConfig;
@@ -1,90 +1,102 @@
 use std::collections::BTreeMap;
+use std::path::Path;
 
+use color_eyre::eyre::{Result, WrapErr};
+use schemars::JsonSchema;
 use semver::VersionReq;
 use serde::{Deserialize, Serialize};
 
+use super::interpolate;
+use super::template::{self, PackageTemplate, TemplateNames};
 use crate::cli::BinstallChoice;
 
-/// Small helper function that returns true to work around
-/// <https://github.com/serde-rs/serde/issues/368>.
-#[expect(
-    clippy::inline_always,
-    reason = "A simple literal is a perfect candidate for inlining."
-)]
-#[inline(always)]
-const fn serde_default_true() -> bool {
-    true
-}
-
 /// Package requirement with additional options set.
 ///
 /// See <https://doc.rust-lang.org/cargo/reference/specifying-dependencies.html>.
-#[expect(
-    clippy::struct_excessive_bools,
-    reason = "This is configuration, so needs to represent all possible items, which includes flags."
-)]
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct DetailedPackageReq {
     // Options from the Cargo Install CLI.
+    /// Version requirement of the package to install.
+    #[schemars(with = "String")]
     pub version: VersionReq,
 
-    #[serde(default = "serde_default_true")]
-    pub default_features: bool,
+    /// Whether to activate the default features, or to pass
+    /// `--no-default-features` otherwise: activated when unset.
+    #[serde(default)]
+    pub default_features: Option<bool>,
 
+    /// Activate all available features: `--all-features`.
     #[serde(default)]
-    pub all_features: bool,
+    pub all_features: Option<bool>,
 
+    /// Features to activate: `--features`.
     #[serde(default)]
     pub features: Vec<String>,
 
+    /// Registry index to install from: `--index`.
     #[serde(default)]
     pub index: Option<String>,
 
+    /// Registry to use: `--registry`.
     #[serde(default)]
     pub registry: Option<String>,
 
+    /// Git URL to install the package from: `--git`.
     #[serde(default)]
     pub git: Option<String>,
 
+    /// Branch to use when installing from Git: `--branch`.
     #[serde(default)]
     pub branch: Option<String>,
 
+    /// Tag to use when installing from Git: `--tag`.
     #[serde(default)]
     pub tag: Option<String>,
 
+    /// Specific commit to use when installing from Git: `--rev`.
     #[serde(default)]
     pub rev: Option<String>,
 
+    /// Filesystem path to the local package to install: `--path`.
     #[serde(default)]
     pub path: Option<String>,
 
+    /// Binaries to install: `--bin`.
     #[serde(default)]
     pub bins: Vec<String>,
 
+    /// Install all binaries: `--bins`.
     #[serde(default)]
-    pub all_bins: bool,
+    pub all_bins: Option<bool>,
 
+    /// Examples to install: `--example`.
     #[serde(default)]
     pub examples: Vec<String>,
 
+    /// Install all examples: `--examples`.
     #[serde(default)]
-    pub all_examples: bool,
+    pub all_examples: Option<bool>,
 
+    /// Force overwriting existing crates or binaries: `--force`.
     #[serde(default)]
-    pub force: bool,
+    pub force: Option<bool>,
 
+    /// Ignore the `rust-version` of the package: `--ignore-rust-version`.
     #[serde(default)]
-    pub ignore_rust_version: bool,
+    pub ignore_rust_version: Option<bool>,
 
+    /// Require `Cargo.lock` and cache to be up-to-date: `--frozen`.
     #[serde(default)]
-    pub frozen: bool,
+    pub frozen: Option<bool>,
 
+    /// Require `Cargo.lock` to be up-to-date: `--locked`.
     #[serde(default)]
-    pub locked: bool,
+    pub locked: Option<bool>,
 
+    /// Run without accessing the network: `--offline`.
     #[serde(default)]
-    pub offline: bool,
+    pub offline: Option<bool>,
 
     // Additional options.
     /// Additional CLI arguments that must be passed onto the associated `cargo
@@ -99,11 +111,11 @@ pub struct DetailedPackageReq {
 
     /// Do the same as the global `--skip-check` but only for this package.
     #[serde(default)]
-    pub skip_check: bool,
+    pub skip_check: Option<bool>,
 
     /// Do the same as the global `--no-fail-fast` but only for this package.
     #[serde(default)]
-    pub no_fail_fast: bool,
+    pub no_fail_fast: Option<bool>,
 
     /// Do the same as the global `--target` but only for this package.
     #[serde(default)]
@@ -112,42 +124,16 @@ pub struct DetailedPackageReq {
     /// Do the same as the global `--binstall` but only for this package.
     #[serde(default)]
     pub binstall: Option<BinstallChoice>,
-}
 
-// Should be kept in-sync with the above definition with regards to Serde.
-impl Default for DetailedPackageReq {
-    fn default() -> Self {
-        Self {
-            // Manual defaults.
-            default_features: true,
-            // Usual defaults.
-            version: VersionReq::default(),
-            all_features: bool::default(),
-            features: Vec::default(),
-            index: Option::default(),
-            registry: Option::default(),
-            git: Option::default(),
-            branch: Option::default(),
-            tag: Option::default(),
-            rev: Option::default(),
-            path: Option::default(),
-            bins: Vec::default(),
-            all_bins: bool::default(),
-            examples: Vec::default(),
-            all_examples: bool::default(),
-            force: bool::default(),
-            ignore_rust_version: bool::default(),
-            frozen: bool::default(),
-            locked: bool::default(),
-            offline: bool::default(),
-            extra_arguments: Vec::default(),
-            environment: BTreeMap::default(),
-            skip_check: bool::default(),
-            no_fail_fast: bool::default(),
-            target: Option::default(),
-            binstall: Option::default(),
-        }
-    }
+    /// Templates to inherit the options left unset from, the last ones taking
+    /// precedence.
+    #[serde(default, skip_serializing_if = "TemplateNames::is_empty")]
+    pub template: TemplateNames,
+
+    /// Groups the package belongs to, in addition to the ones listing it in
+    /// the `groups` section, in order to select it using `ship --group`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
 }
 
 impl From<PackageRequirement> for DetailedPackageReq {
@@ -165,20 +151,308 @@ impl From<PackageRequirement> for DetailedPackageReq {
 impl DetailedPackageReq {
     /// Returns whether the package should be version-checked beyond only the
     /// raw `skip_check` element by taking other fields into consideration.
+    ///
+    /// Git-sourced packages are checked by commit and local ones by manifest,
+    /// but Git references given without any repository cannot be resolved.
     pub fn effective_skip_check(&self) -> bool {
-        self.skip_check
-            || self.path.is_some()
-            || self.git.is_some()
-            || self.branch.is_some()
-            || self.tag.is_some()
-            || self.rev.is_some()
+        self.skip_check.unwrap_or_default()
+            || (self.git.is_none()
+                && (self.branch.is_some() || self.tag.is_some() || self.rev.is_some()))
+    }
+
+    /// Returns the pairs of options that are set together while they cannot,
+    /// by their configuration names, the second one of each pair being the
+    /// one conflicting with the first.
+    pub fn conflicting_options(&self) -> Vec<(&'static str, &'static str)> {
+        let sources = [
+            ("git", self.git.is_some()),
+            ("path", self.path.is_some()),
+            ("registry", self.registry.is_some()),
+            ("index", self.index.is_some()),
+        ];
+        let git_refs = [
+            ("branch", self.branch.is_some()),
+            ("tag", self.tag.is_some()),
+            ("rev", self.rev.is_some()),
+        ];
+        let mut conflicts = Vec::new();
+
+        for group in [&sources[..], &git_refs[..]] {
+            for (idx, (name1, is_set1)) in group.iter().enumerate() {
+                for (name2, is_set2) in &group[idx + 1..] {
+                    if *is_set1 && *is_set2 {
+                        conflicts.push((*name1, *name2));
+                    }
+                }
+            }
+        }
+        for (name1, is_set1, name2, is_set2) in [
+            (
+                "all-features",
+                self.all_features.unwrap_or_default(),
+                "features",
+                !self.features.is_empty(),
+            ),
+            (
+                "all-bins",
+                self.all_bins.unwrap_or_default(),
+                "bins",
+                !self.bins.is_empty(),
+            ),
+            (
+                "all-examples",
+                self.all_examples.unwrap_or_default(),
+                "examples",
+                !self.examples.is_empty(),
+            ),
+        ] {
+            if is_set1 && is_set2 {
+                conflicts.push((name1, name2));
+            }
+        }
+
+        conflicts
+    }
+
+    /// Returns the Git reference options that are set while `git` is not, by
+    /// their configuration names.
+    pub fn git_refs_without_git(&self) -> Vec<&'static str> {
+        if self.git.is_some() {
+            return Vec::new();
+        }
+        [
+            ("branch", self.branch.is_some()),
+            ("tag", self.tag.is_some()),
+            ("rev", self.rev.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(name, is_set)| is_set.then_some(name))
+        .collect()
+    }
+
+    /// Fills the options that the package leaves unset with the given
+    /// package-wide defaults.
+    #[must_use]
+    pub fn with_defaults(self, defs: &PackageDefaults) -> Self {
+        self.with_template(&defs.clone().into())
+    }
+
+    /// Fills the options that the package leaves unset with the templates it
+    /// inherits from, as found in the given ones by name.
+    ///
+    /// It fails if a template is unknown or inherits from itself.
+    pub fn with_templates(self, templates: &BTreeMap<String, PackageTemplate>) -> Result<Self> {
+        let names = self.template.clone();
+        Ok(template::resolve(templates, &names)?
+            .into_iter()
+            .fold(self, Self::with_template))
+    }
+
+    /// Fills the options that the package leaves unset with the given
+    /// template, without following the ones it inherits from.
+    ///
+    /// Booleans are only used when the package leaves them unset, lists when
+    /// it has none and maps are merged key by key. Sources only apply to
+    /// packages not having any, and Git references to those not having any
+    /// either.
+    #[must_use]
+    pub fn with_template(mut self, tpl: &PackageTemplate) -> Self {
+        // Full destructuring to avoid forgetting to update this function.
+        let PackageTemplate {
+            template: _,
+            default_features,
+            all_features,
+            features,
+            index,
+            registry,
+            git,
+            branch,
+            tag,
+            rev,
+            path,
+            bins,
+            all_bins,
+            examples,
+            all_examples,
+            force,
+            ignore_rust_version,
+            frozen,
+            locked,
+            offline,
+            extra_arguments,
+            environment,
+            skip_check,
+            no_fail_fast,
+            target,
+            binstall,
+        } = tpl;
+
+        // Sources would conflict with one another, and Git references only
+        // make sense along with their repository.
+        if self.git.is_none()
+            && self.path.is_none()
+            && self.registry.is_none()
+            && self.index.is_none()
+        {
+            if self.branch.is_none() && self.tag.is_none() && self.rev.is_none() {
+                self.branch.clone_from(branch);
+                self.tag.clone_from(tag);
+                self.rev.clone_from(rev);
+            }
+            self.index.clone_from(index);
+            self.registry.clone_from(registry);
+            self.git.clone_from(git);
+            self.path.clone_from(path);
+        }
+        for (opt, def) in [
+            (&mut self.default_features, default_features),
+            (&mut self.all_features, all_features),
+            (&mut self.all_bins, all_bins),
+            (&mut self.all_examples, all_examples),
+            (&mut self.force, force),
+            (&mut self.ignore_rust_version, ignore_rust_version),
+            (&mut self.frozen, frozen),
+            (&mut self.locked, locked),
+            (&mut self.offline, offline),
+            (&mut self.skip_check, skip_check),
+            (&mut self.no_fail_fast, no_fail_fast),
+        ] {
+            *opt = opt.or(*def);
+        }
+        for (opt, def) in [
+            (&mut self.features, features),
+            (&mut self.bins, bins),
+            (&mut self.examples, examples),
+            (&mut self.extra_arguments, extra_arguments),
+        ] {
+            if opt.is_empty()
+                && let Some(def) = def
+            {
+                opt.clone_from(def);
+            }
+        }
+        if let Some(environment) = environment {
+            for (var, val) in environment {
+                self.environment
+                    .entry(var.clone())
+                    .or_insert_with(|| val.clone());
+            }
+        }
+        if self.target.is_none() {
+            self.target.clone_from(target);
+        }
+        self.binstall = self.binstall.or(*binstall);
+
+        self
+    }
+
+    /// Expands the environment variables and the home directory in the
+    /// `index`, `git`, `path`, `extra-arguments` and `environment` options:
+    /// see [`interpolate::expand`].
+    ///
+    /// It fails on the first variable that is not defined, naming the option
+    /// containing it.
+    pub fn interpolated(mut self) -> Result<Self> {
+        let expand = |opt: &str, value: &mut String| -> Result<()> {
+            *value = interpolate::expand(value)
+                .wrap_err_with(|| format!("Failed to expand the `{opt}` option."))?;
+            Ok(())
+        };
+
+        for (opt, value) in [
+            ("index", &mut self.index),
+            ("git", &mut self.git),
+            ("path", &mut self.path),
+        ] {
+            if let Some(value) = value {
+                expand(opt, value)?;
+            }
+        }
+        for arg in &mut self.extra_arguments {
+            expand("extra-arguments", arg)?;
+        }
+        for (name, value) in &mut self.environment {
+            expand(&format!("environment.{name}"), value)?;
+        }
+
+        Ok(self)
+    }
+
+    /// Resolves the `path` option against the given directory when relative.
+    #[must_use]
+    pub fn relative_to(mut self, dir: &Path) -> Self {
+        if let Some(path) = &mut self.path
+            && Path::new(path).is_relative()
+        {
+            *path = dir.join(&*path).to_string_lossy().into_owned();
+        }
+        self
+    }
+
+    /// Returns the name of the profile the package is built with, as selected
+    /// by the `--profile` or `--debug` extra arguments, or `release` otherwise.
+    pub fn effective_profile(&self) -> &str {
+        let mut profile = "release";
+        let mut args = self.extra_arguments.iter();
+
+        // The last one wins, as with Cargo's CLI.
+        while let Some(arg) = args.next() {
+            if arg == "--debug" {
+                profile = "dev";
+            } else if arg == "--profile" {
+                if let Some(name) = args.next() {
+                    profile = name;
+                }
+            } else if let Some(name) = arg.strip_prefix("--profile=") {
+                profile = name;
+            }
+        }
+
+        profile
     }
 }
 
+/// Options applied to all configured packages, as found in the
+/// `defaults.package` section.
+///
+/// Values set by a package itself take precedence over these, except for maps
+/// that are merged key by key, and registries that only apply to packages not
+/// having any other source.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct PackageDefaults {
+    /// Registry index to install from: `--index`.
+    pub index: Option<String>,
+    /// Registry to use: `--registry`.
+    pub registry: Option<String>,
+    /// Force overwriting existing crates or binaries: `--force`.
+    pub force: Option<bool>,
+    /// Ignore the `rust-version` of the packages: `--ignore-rust-version`.
+    pub ignore_rust_version: Option<bool>,
+    /// Require `Cargo.lock` and cache to be up-to-date: `--frozen`.
+    pub frozen: Option<bool>,
+    /// Require `Cargo.lock` to be up-to-date: `--locked`.
+    pub locked: Option<bool>,
+    /// Run without accessing the network: `--offline`.
+    pub offline: Option<bool>,
+    /// Additional CLI arguments passed onto `cargo install`.
+    pub extra_arguments: Option<Vec<String>>,
+    /// Environment variables set for the `cargo install` processes.
+    pub environment: Option<BTreeMap<String, String>>,
+    /// Do the same as the global `--skip-check`.
+    pub skip_check: Option<bool>,
+    /// Do the same as the global `--no-fail-fast`.
+    pub no_fail_fast: Option<bool>,
+    /// Do the same as the global `--target`.
+    pub target: Option<String>,
+    /// Do the same as the global `--binstall`, but with precedence over it.
+    pub binstall: Option<BinstallChoice>,
+}
+
 /// Represents the requirement setting configured for a package.
 ///
 /// The enumeration is deserialized from an untagged form.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum PackageRequirement {
     #[expect(
@@ -186,7 +460,7 @@ pub enum PackageRequirement {
         reason = "SemVer is not a Rust type here, but a proper noun."
     )]
     /// Simple form: only a SemVer requirement string.
-    Simple(VersionReq),
+    Simple(#[schemars(with = "String")] VersionReq),
     /// Detailed form: all supported options made available.
     Detailed(Box<DetailedPackageReq>),
 }
@@ -194,12 +468,293 @@ pub enum PackageRequirement {
 impl PackageRequirement {
     /// Convenience shortcut for simple and star version requirement package.
     pub const SIMPLE_STAR: Self = Self::Simple(VersionReq::STAR);
+
+    /// Returns the groups the package declares itself as part of, none for
+    /// the simple form.
+    pub fn groups(&self) -> &[String] {
+        match self {
+            Self::Simple(_) => &[],
+            Self::Detailed(det_pkg) => &det_pkg.groups,
+        }
+    }
+
+    /// Converts the requirement to an editable TOML value.
+    ///
+    /// The detailed form is rendered as an inline table that only contains
+    /// the version and the options that differ from their default, in the
+    /// order of their definition.
+    pub fn to_toml_value(&self) -> Result<toml_edit::Value> {
+        match self {
+            Self::Simple(ver_req) => Ok(ver_req.to_string().into()),
+            Self::Detailed(det_pkg) => {
+                let default_table = toml::Table::try_from(DetailedPackageReq::default())
+                    .wrap_err("Failed to serialize the default package requirement.")?;
+                let pkg_table = toml::Table::try_from(det_pkg)
+                    .wrap_err("Failed to serialize the package requirement.")?;
+                // Go through the string form in order to keep the field order.
+                let mut pkg_doc = toml::to_string(det_pkg)
+                    .wrap_err("Failed to serialize the package requirement.")?
+                    .parse::<toml_edit::DocumentMut>()
+                    .wrap_err("Failed to parse the serialized package requirement.")?;
+                pkg_doc.retain(|key, _| {
+                    key == "version" || pkg_table.get(key) != default_table.get(key)
+                });
+                let mut pkg_inline = pkg_doc.as_table().clone().into_inline_table();
+                pkg_inline.fmt();
+                Ok(pkg_inline.into())
+            }
+        }
+    }
+}
+
+impl From<DetailedPackageReq> for PackageRequirement {
+    /// Wraps the given package requirement, using the simple form when only
+    /// its version is set.
+    fn from(det_pkg: DetailedPackageReq) -> Self {
+        if det_pkg
+            == (DetailedPackageReq {
+                version: det_pkg.version.clone(),
+                ..Default::default()
+            })
+        {
+            Self::Simple(det_pkg.version)
+        } else {
+            Self::Detailed(Box::new(det_pkg))
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_detailedpackagereq_conflictingoptions() {
+        assert!(
+            DetailedPackageReq::default()
+                .conflicting_options()
+                .is_empty()
+        );
+        assert_eq!(
+            DetailedPackageReq {
+                git: Some("https://example.com/abc".to_owned()),
+                path: Some("/a/b/c".to_owned()),
+                branch: Some("main".to_owned()),
+                rev: Some("abc123".to_owned()),
+                all_features: Some(true),
+                features: vec!["foo".to_owned()],
+                ..Default::default()
+            }
+            .conflicting_options(),
+            [
+                ("git", "path"),
+                ("branch", "rev"),
+                ("all-features", "features")
+            ],
+        );
+    }
+
+    #[test]
+    fn test_detailedpackagereq_gitrefswithoutgit() {
+        let pkg = DetailedPackageReq {
+            branch: Some("main".to_owned()),
+            tag: Some("v1".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(pkg.git_refs_without_git(), ["branch", "tag"]);
+        assert!(
+            DetailedPackageReq {
+                git: Some("https://example.com/abc".to_owned()),
+                ..pkg
+            }
+            .git_refs_without_git()
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_detailedpackagereq_withdefaults_empty_isunchanged() {
+        let pkg = DetailedPackageReq {
+            version: "1.2".parse().unwrap(),
+            features: vec!["foo".to_owned()],
+            ..Default::default()
+        };
+        assert_eq!(pkg.clone().with_defaults(&PackageDefaults::default()), pkg);
+    }
+
+    #[test]
+    fn test_detailedpackagereq_withdefaults_packagewins() {
+        let defs = PackageDefaults {
+            index: Some("https://example.com/index".to_owned()),
+            force: Some(true),
+            offline: Some(false),
+            target: Some("def-target".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            DetailedPackageReq {
+                registry: Some("reg".to_owned()),
+                offline: Some(true),
+                target: Some("pkg-target".to_owned()),
+                ..Default::default()
+            }
+            .with_defaults(&defs),
+            DetailedPackageReq {
+                registry: Some("reg".to_owned()),
+                force: Some(true),
+                offline: Some(true),
+                target: Some("pkg-target".to_owned()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            DetailedPackageReq {
+                path: Some("/a/b/c".to_owned()),
+                ..Default::default()
+            }
+            .with_defaults(&defs),
+            DetailedPackageReq {
+                path: Some("/a/b/c".to_owned()),
+                force: Some(true),
+                offline: Some(false),
+                target: Some("def-target".to_owned()),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn test_detailedpackagereq_withdefaults_packagedisables() {
+        let defs = PackageDefaults {
+            force: Some(true),
+            locked: Some(true),
+            skip_check: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            DetailedPackageReq {
+                force: Some(false),
+                skip_check: Some(false),
+                ..Default::default()
+            }
+            .with_defaults(&defs),
+            DetailedPackageReq {
+                force: Some(false),
+                locked: Some(true),
+                skip_check: Some(false),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            DetailedPackageReq {
+                default_features: Some(true),
+                all_bins: Some(false),
+                ..Default::default()
+            }
+            .with_template(&PackageTemplate {
+                default_features: Some(false),
+                all_bins: Some(true),
+                ..Default::default()
+            }),
+            DetailedPackageReq {
+                default_features: Some(true),
+                all_bins: Some(false),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn test_detailedpackagereq_withtemplate() {
+        let tpl = PackageTemplate {
+            default_features: Some(false),
+            features: Some(vec!["tpl".to_owned()]),
+            bins: Some(vec!["tpl".to_owned()]),
+            git: Some("https://example.com/tpl".to_owned()),
+            branch: Some("dev".to_owned()),
+            all_examples: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            DetailedPackageReq {
+                features: vec!["pkg".to_owned()],
+                ..Default::default()
+            }
+            .with_template(&tpl),
+            DetailedPackageReq {
+                default_features: Some(false),
+                features: vec!["pkg".to_owned()],
+                bins: vec!["tpl".to_owned()],
+                git: Some("https://example.com/tpl".to_owned()),
+                branch: Some("dev".to_owned()),
+                all_examples: Some(true),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            DetailedPackageReq {
+                rev: Some("abc123".to_owned()),
+                ..Default::default()
+            }
+            .with_template(&tpl)
+            .git,
+            tpl.git,
+        );
+        assert_eq!(
+            DetailedPackageReq {
+                path: Some("/a/b/c".to_owned()),
+                ..Default::default()
+            }
+            .with_template(&tpl)
+            .conflicting_options(),
+            [],
+        );
+    }
+
+    #[test]
+    fn test_detailedpackagereq_interpolated() {
+        let home = std::env::var("HOME")
+            .unwrap_or_else(|_| home::home_dir().unwrap().to_string_lossy().into_owned());
+        assert_eq!(
+            DetailedPackageReq {
+                git: Some("https://example.com/${CARGO_LINER_TEST_NOPE:-abc}".to_owned()),
+                path: Some("~/src/abc".to_owned()),
+                extra_arguments: vec!["--root".to_owned(), "${HOME}/.local".to_owned()],
+                environment: [("ABC".to_owned(), "$${HOME}".to_owned())].into(),
+                tag: Some("${HOME}".to_owned()),
+                ..Default::default()
+            }
+            .interpolated()
+            .unwrap(),
+            DetailedPackageReq {
+                git: Some("https://example.com/abc".to_owned()),
+                path: Some(format!("{home}/src/abc")),
+                extra_arguments: vec!["--root".to_owned(), format!("{home}/.local")],
+                environment: [("ABC".to_owned(), "${HOME}".to_owned())].into(),
+                tag: Some("${HOME}".to_owned()),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn test_detailedpackagereq_interpolated_undefined_iserr() {
+        let err = DetailedPackageReq {
+            environment: [("ABC".to_owned(), "${CARGO_LINER_TEST_NOPE}".to_owned())].into(),
+            ..Default::default()
+        }
+        .interpolated()
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to expand the `environment.ABC` option."
+        );
+        assert_eq!(
+            err.root_cause().to_string(),
+            "Undefined environment variable `CARGO_LINER_TEST_NOPE`.",
+        );
+    }
+
     #[test]
     fn test_detailedpackagereq_effectiveskipcheck_default() {
         assert!(!DetailedPackageReq::default().effective_skip_check());
@@ -209,7 +764,7 @@ mod tests {
     fn test_detailedpackagereq_effectiveskipcheck_skipcheck() {
         assert!(
             DetailedPackageReq {
-                skip_check: true,
+                skip_check: Some(true),
                 ..Default::default()
             }
             .effective_skip_check()
@@ -219,7 +774,7 @@ mod tests {
     #[test]
     fn test_detailedpackagereq_effectiveskipcheck_path() {
         assert!(
-            DetailedPackageReq {
+            !DetailedPackageReq {
                 path: Some("/a/b/c".to_owned()),
                 ..Default::default()
             }
@@ -230,7 +785,7 @@ mod tests {
     #[test]
     fn test_detailedpackagereq_effectiveskipcheck_git() {
         assert!(
-            DetailedPackageReq {
+            !DetailedPackageReq {
                 git: Some("ssh://git@example.com/user/repo.git".to_owned()),
                 ..Default::default()
             }
@@ -271,11 +826,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_detailedpackagereq_effectiveprofile() {
+        for (args, profile) in [
+            (&[][..], "release"),
+            (&["--locked"][..], "release"),
+            (&["--debug"][..], "dev"),
+            (&["--profile", "custom"][..], "custom"),
+            (&["--profile=custom"][..], "custom"),
+            (&["--debug", "--profile=custom"][..], "custom"),
+            (&["--profile", "custom", "--debug"][..], "dev"),
+        ] {
+            assert_eq!(
+                DetailedPackageReq {
+                    extra_arguments: args.iter().map(ToString::to_string).collect(),
+                    ..Default::default()
+                }
+                .effective_profile(),
+                profile,
+            );
+        }
+    }
+
     #[test]
     fn test_detailedpackagereq_effectiveskipcheck_combination_1() {
         assert!(
             DetailedPackageReq {
-                skip_check: true,
+                skip_check: Some(true),
                 path: Some("/a/b/c".to_owned()),
                 ..Default::default()
             }
@@ -286,7 +863,7 @@ mod tests {
     #[test]
     fn test_detailedpackagereq_effectiveskipcheck_combination_2() {
         assert!(
-            DetailedPackageReq {
+            !DetailedPackageReq {
                 git: Some("ssh://git@example.com/user/repo.git".to_owned()),
                 branch: Some("example".to_owned()),
                 ..Default::default()
@@ -294,4 +871,68 @@ mod tests {
             .effective_skip_check()
         );
     }
+
+    #[test]
+    fn test_detailedpackagereq_effectiveskipcheck_combination_3() {
+        assert!(
+            DetailedPackageReq {
+                git: Some("ssh://git@example.com/user/repo.git".to_owned()),
+                skip_check: Some(true),
+                ..Default::default()
+            }
+            .effective_skip_check()
+        );
+    }
+
+    #[test]
+    fn test_packagerequirement_fromdetailed_simple() {
+        assert_eq!(
+            PackageRequirement::from(DetailedPackageReq {
+                version: VersionReq::parse("1.2").unwrap(),
+                ..Default::default()
+            }),
+            PackageRequirement::Simple(VersionReq::parse("1.2").unwrap()),
+        );
+    }
+
+    #[test]
+    fn test_packagerequirement_fromdetailed_detailed() {
+        let det_pkg = DetailedPackageReq {
+            locked: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            PackageRequirement::from(det_pkg.clone()),
+            PackageRequirement::Detailed(Box::new(det_pkg)),
+        );
+    }
+
+    #[test]
+    fn test_packagerequirement_totomlvalue_simple() {
+        assert_eq!(
+            PackageRequirement::SIMPLE_STAR
+                .to_toml_value()
+                .unwrap()
+                .to_string(),
+            r#""*""#,
+        );
+    }
+
+    #[test]
+    fn test_packagerequirement_totomlvalue_detailed() {
+        assert_eq!(
+            PackageRequirement::Detailed(Box::new(DetailedPackageReq {
+                version: VersionReq::parse("^1.2").unwrap(),
+                default_features: Some(false),
+                features: vec!["a".to_owned(), "b".to_owned()],
+                git: Some("https://example.com/repo.git".to_owned()),
+                environment: BTreeMap::from([("A".to_owned(), "b".to_owned())]),
+                ..Default::default()
+            }))
+            .to_toml_value()
+            .unwrap()
+            .to_string(),
+            r#"{ version = "^1.2", default-features = false, features = ["a", "b"], git = "https://example.com/repo.git", environment = { A = "b" } }"#,
+        );
+    }
 }
@@ -0,0 +1,360 @@
+//! Packages only configured on some machines, as found in the `target` and
+//! `hosts` sections.
+//!
+//! See [`Platform::current`] in order to describe the running machine and
+//! [`Platform::matches_target`] in order to evaluate the `cfg(...)` keys of
+//! the `target` section against it.
+
+use std::collections::BTreeMap;
+use std::env::consts;
+use std::str::FromStr;
+
+use color_eyre::Report;
+use color_eyre::eyre::{Result, bail, eyre};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::PackageRequirement;
+
+/// Section of packages added on top of the `packages` one on the machines it
+/// applies to.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, Clone, PartialEq, Eq)]
+pub struct PackageOverlay {
+    /// The name-to-setting map of the packages added by the overlay.
+    #[serde(default)]
+    pub packages: BTreeMap<String, PackageRequirement>,
+}
+
+/// Description of a machine the overlays are evaluated against.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Platform {
+    /// Configuration options of the target, as names with optional values.
+    pub cfgs: Vec<(String, Option<String>)>,
+    /// Name of the host, if it could be determined.
+    pub hostname: Option<String>,
+}
+
+impl Platform {
+    /// Describes the running machine.
+    ///
+    /// The options are the ones the current executable was compiled for,
+    /// which are the ones of the host for all intents and purposes.
+    pub fn current() -> Self {
+        let mut cfgs = vec![
+            ("target_arch", consts::ARCH.to_owned()),
+            ("target_os", consts::OS.to_owned()),
+            ("target_family", consts::FAMILY.to_owned()),
+            ("target_env", Self::target_env().to_owned()),
+            ("target_vendor", Self::target_vendor().to_owned()),
+            ("target_pointer_width", usize::BITS.to_string()),
+            (
+                "target_endian",
+                if cfg!(target_endian = "little") {
+                    "little"
+                } else {
+                    "big"
+                }
+                .to_owned(),
+            ),
+        ]
+        .into_iter()
+        .map(|(key, val)| (key.to_owned(), Some(val)))
+        .collect::<Vec<_>>();
+        if !consts::FAMILY.is_empty() {
+            cfgs.push((consts::FAMILY.to_owned(), None));
+        }
+
+        Self {
+            cfgs,
+            hostname: gethostname::gethostname().into_string().ok(),
+        }
+    }
+
+    /// Returns the `target_env` option of the running machine.
+    fn target_env() -> &'static str {
+        if cfg!(target_env = "gnu") {
+            "gnu"
+        } else if cfg!(target_env = "musl") {
+            "musl"
+        } else if cfg!(target_env = "msvc") {
+            "msvc"
+        } else {
+            ""
+        }
+    }
+
+    /// Returns the `target_vendor` option of the running machine.
+    fn target_vendor() -> &'static str {
+        if cfg!(target_vendor = "apple") {
+            "apple"
+        } else if cfg!(target_vendor = "pc") {
+            "pc"
+        } else {
+            "unknown"
+        }
+    }
+
+    /// Returns whether the given key of the `target` section applies to the
+    /// machine, failing if it is not a valid `cfg(...)` expression.
+    pub fn matches_target(&self, spec: &str) -> Result<bool> {
+        Ok(spec.parse::<CfgExpr>()?.matches(&self.cfgs))
+    }
+
+    /// Returns whether the given key of the `hosts` section applies to the
+    /// machine, host names being compared case-insensitively.
+    pub fn matches_host(&self, hostname: &str) -> bool {
+        self.hostname
+            .as_ref()
+            .is_some_and(|name| name.eq_ignore_ascii_case(hostname))
+    }
+}
+
+/// Predicate of a `cfg(...)` expression, as supported by Cargo in the keys
+/// of its own `target` tables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CfgExpr {
+    /// Set option without value, such as `unix`.
+    Name(String),
+    /// Option set to the given value, such as `target_os = "linux"`.
+    KeyValue(String, String),
+    /// Whether all the predicates are true: `all(...)`.
+    All(Vec<CfgExpr>),
+    /// Whether any of the predicates is true: `any(...)`.
+    Any(Vec<CfgExpr>),
+    /// Whether the predicate is false: `not(...)`.
+    Not(Box<CfgExpr>),
+}
+
+impl CfgExpr {
+    /// Evaluates the predicate against the given configuration options.
+    pub fn matches(&self, cfgs: &[(String, Option<String>)]) -> bool {
+        match self {
+            Self::Name(name) => cfgs.iter().any(|(key, val)| key == name && val.is_none()),
+            Self::KeyValue(name, value) => cfgs
+                .iter()
+                .any(|(key, val)| key == name && val.as_ref() == Some(value)),
+            Self::All(exprs) => exprs.iter().all(|expr| expr.matches(cfgs)),
+            Self::Any(exprs) => exprs.iter().any(|expr| expr.matches(cfgs)),
+            Self::Not(expr) => !expr.matches(cfgs),
+        }
+    }
+}
+
+impl FromStr for CfgExpr {
+    type Err = Report;
+
+    /// Parses a whole `cfg(...)` expression into its predicate.
+    fn from_str(spec: &str) -> Result<Self> {
+        let mut parser = Parser { rest: spec };
+        if parser.ident().ok() != Some("cfg") || !parser.eat('(') {
+            bail!("Only `cfg(...)` expressions are supported, got `{spec}`.");
+        }
+        let expr = parser.expr()?;
+        parser.expect(')')?;
+        if !parser.rest.trim().is_empty() {
+            bail!("Unexpected {} after the expression.", parser.found());
+        }
+        Ok(expr)
+    }
+}
+
+/// Minimal recursive descent parser of `cfg(...)` expressions.
+struct Parser<'spec> {
+    /// What remains to be parsed.
+    rest: &'spec str,
+}
+
+impl<'spec> Parser<'spec> {
+    /// Parses a single predicate.
+    fn expr(&mut self) -> Result<CfgExpr> {
+        let ident = self.ident()?;
+        if self.eat('=') {
+            return Ok(CfgExpr::KeyValue(
+                ident.to_owned(),
+                self.string()?.to_owned(),
+            ));
+        }
+        if !self.eat('(') {
+            return Ok(CfgExpr::Name(ident.to_owned()));
+        }
+
+        let mut exprs = Vec::new();
+        while !self.eat(')') {
+            exprs.push(self.expr()?);
+            if !self.eat(',') {
+                self.expect(')')?;
+                break;
+            }
+        }
+        Ok(match ident {
+            "all" => CfgExpr::All(exprs),
+            "any" => CfgExpr::Any(exprs),
+            "not" => match <[_; 1]>::try_from(exprs) {
+                Ok([expr]) => CfgExpr::Not(Box::new(expr)),
+                Err(_) => bail!("`not` expects exactly one predicate."),
+            },
+            _ => bail!("Unknown operator `{ident}`: expected `all`, `any` or `not`."),
+        })
+    }
+
+    /// Parses an option name or an operator.
+    fn ident(&mut self) -> Result<&'spec str> {
+        self.rest = self.rest.trim_start();
+        let end = self
+            .rest
+            .find(|chr: char| !(chr.is_ascii_alphanumeric() || chr == '_'))
+            .unwrap_or(self.rest.len());
+        if end == 0 || self.rest.starts_with(|chr: char| chr.is_ascii_digit()) {
+            bail!("Expected an identifier, found {}.", self.found());
+        }
+        let (ident, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Ok(ident)
+    }
+
+    /// Parses a double-quoted option value.
+    fn string(&mut self) -> Result<&'spec str> {
+        self.expect('"')?;
+        let (value, rest) = self
+            .rest
+            .split_once('"')
+            .ok_or_else(|| eyre!("Unterminated string."))?;
+        self.rest = rest;
+        Ok(value)
+    }
+
+    /// Consumes the given character if it comes next, returning whether it
+    /// did.
+    fn eat(&mut self, chr: char) -> bool {
+        self.rest = self.rest.trim_start();
+        match self.rest.strip_prefix(chr) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Consumes the given character, failing if it does not come next.
+    fn expect(&mut self, chr: char) -> Result<()> {
+        if !self.eat(chr) {
+            bail!("Expected `{chr}`, found {}.", self.found());
+        }
+        Ok(())
+    }
+
+    /// Describes what comes next for error messages.
+    fn found(&self) -> String {
+        self.rest
+            .trim_start()
+            .chars()
+            .next()
+            .map_or_else(|| "the end".to_owned(), |chr| format!("`{chr}`"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns an example Linux machine.
+    fn linux() -> Platform {
+        Platform {
+            cfgs: vec![
+                ("target_arch".to_owned(), Some("x86_64".to_owned())),
+                ("target_os".to_owned(), Some("linux".to_owned())),
+                ("target_family".to_owned(), Some("unix".to_owned())),
+                ("unix".to_owned(), None),
+            ],
+            hostname: Some("Workstation".to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_parse_cfgexpr() {
+        assert_eq!(
+            "cfg(unix)".parse::<CfgExpr>().unwrap(),
+            CfgExpr::Name("unix".to_owned()),
+        );
+        assert_eq!(
+            r#" cfg ( all ( target_os = "linux" , not(target_arch="aarch64"), ) ) "#
+                .parse::<CfgExpr>()
+                .unwrap(),
+            CfgExpr::All(vec![
+                CfgExpr::KeyValue("target_os".to_owned(), "linux".to_owned()),
+                CfgExpr::Not(Box::new(CfgExpr::KeyValue(
+                    "target_arch".to_owned(),
+                    "aarch64".to_owned(),
+                ))),
+            ]),
+        );
+        assert_eq!(
+            "cfg(any())".parse::<CfgExpr>().unwrap(),
+            CfgExpr::Any(Vec::new()),
+        );
+    }
+
+    #[test]
+    fn test_parse_cfgexpr_invalid_iserr() {
+        for spec in [
+            "",
+            "x86_64-unknown-linux-gnu",
+            "cfg",
+            "cfg()",
+            "cfg(unix",
+            "cfg(unix) extra",
+            "cfg(target_os = linux)",
+            r#"cfg(target_os = "linux)"#,
+            "cfg(not(unix, windows))",
+            "cfg(one(unix))",
+            "cfg(unix windows)",
+        ] {
+            assert!(spec.parse::<CfgExpr>().is_err(), "{spec:?}");
+        }
+    }
+
+    #[test]
+    fn test_platform_matchestarget() {
+        let platform = linux();
+        for (spec, expected) in [
+            ("cfg(unix)", true),
+            ("cfg(windows)", false),
+            ("cfg(target_family)", false),
+            (r#"cfg(target_os = "linux")"#, true),
+            (r#"cfg(target_arch = "aarch64")"#, false),
+            (r#"cfg(not(target_arch = "aarch64"))"#, true),
+            (r#"cfg(all(unix, target_arch = "x86_64"))"#, true),
+            (r#"cfg(any(windows, target_os = "macos"))"#, false),
+            ("cfg(all())", true),
+            ("cfg(any())", false),
+        ] {
+            assert_eq!(platform.matches_target(spec).unwrap(), expected, "{spec:?}");
+        }
+        assert!(platform.matches_target("x86_64-unknown-linux-gnu").is_err());
+    }
+
+    #[test]
+    fn test_platform_matcheshost() {
+        let platform = linux();
+        assert!(platform.matches_host("workstation"));
+        assert!(platform.matches_host("Workstation"));
+        assert!(!platform.matches_host("ci-runner"));
+        assert!(!Platform::default().matches_host("workstation"));
+    }
+
+    #[test]
+    fn test_platform_current() {
+        let platform = Platform::current();
+        assert!(
+            platform
+                .matches_target(&format!(r#"cfg(target_os = "{}")"#, consts::OS))
+                .unwrap()
+        );
+        assert_eq!(platform.matches_target("cfg(unix)").unwrap(), cfg!(unix),);
+        assert_eq!(
+            platform.matches_target("cfg(windows)").unwrap(),
+            cfg!(windows),
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Project-local configurations, discovered from the current directory.
+//!
+//! See [`Project::discover`] in order to find the one applying to a given
+//! directory, if any.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::Section;
+use color_eyre::eyre::{Result, WrapErr};
+
+use super::UserConfig;
+
+/// Name of Cargo's manifest files, that may hold a project configuration in
+/// their `workspace.metadata.liner` table.
+pub const CARGO_MANIFEST_NAME: &str = "Cargo.toml";
+
+/// Keys leading to the configuration table in a Cargo manifest.
+const METADATA_KEYS: &[&str] = &["workspace", "metadata", "liner"];
+
+/// Configuration found in the directory of a project, whose packages are
+/// installed in a root dedicated to it instead of Cargo's default one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Project {
+    /// Directory of the project.
+    pub dir: PathBuf,
+    /// Path to the file holding the configuration: either a `liner.toml` or
+    /// a Cargo manifest with a `workspace.metadata.liner` table.
+    pub config_path: PathBuf,
+}
+
+impl Project {
+    /// Name of the directory the packages are installed to in the project.
+    pub const ROOT_DIR_NAME: &'static str = ".cargo-liner";
+
+    /// Walks up from the given directory and returns the first project
+    /// configuration found, if any.
+    ///
+    /// A `liner.toml` file takes precedence over a Cargo manifest of the same
+    /// directory. The given `cargo_home` is skipped, as its configuration file
+    /// is the global one.
+    pub fn discover(start: &Path, cargo_home: &Path) -> Result<Option<Self>> {
+        log::debug!("Discovering a project configuration from {start:#?}...");
+        let cargo_home = fs::canonicalize(cargo_home).unwrap_or_else(|_| cargo_home.to_owned());
+
+        for dir in start.ancestors() {
+            if fs::canonicalize(dir).is_ok_and(|dir| dir == cargo_home) {
+                continue;
+            }
+
+            let config_path = dir.join(UserConfig::FILE_NAME);
+            if config_path.is_file() {
+                return Ok(Some(Self::found(dir, config_path)));
+            }
+
+            let manifest_path = dir.join(CARGO_MANIFEST_NAME);
+            if manifest_path.is_file() {
+                let manifest_str = fs::read_to_string(&manifest_path)
+                    .wrap_err_with(|| format!("Failed to read the manifest at {manifest_path:?}."))
+                    .suggestion("Check if the file has the correct permissions.")?;
+                match metadata_table(&manifest_str) {
+                    Ok(Some(_)) => return Ok(Some(Self::found(dir, manifest_path))),
+                    Ok(None) => {}
+                    // Not this tool's concern: let Cargo report it instead.
+                    Err(err) => log::debug!("Skipping {manifest_path:#?}: {err}"),
+                }
+            }
+        }
+
+        log::debug!("No project configuration found.");
+        Ok(None)
+    }
+
+    /// Builds the project found in the given directory.
+    fn found(dir: &Path, config_path: PathBuf) -> Self {
+        log::debug!("Found project configuration at {config_path:#?}.");
+        Self {
+            dir: dir.to_owned(),
+            config_path,
+        }
+    }
+
+    /// Returns the root directory the packages of the project are installed
+    /// to, as passed to `cargo install --root`.
+    pub fn root(&self) -> PathBuf {
+        self.dir.join(Self::ROOT_DIR_NAME)
+    }
+}
+
+/// Returns whether the given path is the one of a Cargo manifest, in which
+/// case the configuration is held by its `workspace.metadata.liner` table.
+pub fn is_cargo_manifest(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == CARGO_MANIFEST_NAME)
+}
+
+/// Extracts the `workspace.metadata.liner` table of the given Cargo manifest
+/// contents, if present.
+pub fn metadata_table(manifest_str: &str) -> Result<Option<toml::Table>> {
+    let mut table = manifest_str
+        .parse::<toml::Table>()
+        .wrap_err("Failed to parse the Cargo manifest.")?;
+
+    for key in METADATA_KEYS {
+        match table.remove(*key) {
+            Some(toml::Value::Table(inner)) => table = inner,
+            _ => return Ok(None),
+        }
+    }
+    Ok(Some(table))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_metadatatable() {
+        assert_eq!(metadata_table("[package]\nname = 'abc'\n").unwrap(), None);
+        assert_eq!(
+            metadata_table("[workspace.metadata]\nother = 1\n").unwrap(),
+            None
+        );
+        assert_eq!(
+            metadata_table(indoc!(
+                r#"
+                    [workspace]
+                    members = ["abc"]
+                    [workspace.metadata.liner.packages]
+                    cargo-nextest = "=0.9.0"
+                "#
+            ))
+            .unwrap(),
+            Some("[packages]\ncargo-nextest = '=0.9.0'\n".parse().unwrap()),
+        );
+        assert!(metadata_table("[workspace").is_err());
+    }
+
+    #[test]
+    fn test_iscargomanifest() {
+        assert!(is_cargo_manifest(Path::new("a/Cargo.toml")));
+        assert!(!is_cargo_manifest(Path::new("a/liner.toml")));
+        assert!(!is_cargo_manifest(Path::new("-")));
+    }
+
+    #[test]
+    fn test_discover() {
+        let dir = tempfile::tempdir().unwrap();
+        let cargo_home = dir.path().join("home/.cargo");
+        let ws_dir = dir.path().join("ws");
+        let member_dir = ws_dir.join("member");
+        let proj_dir = member_dir.join("proj");
+        fs::create_dir_all(&cargo_home).unwrap();
+        fs::create_dir_all(&proj_dir).unwrap();
+        fs::write(cargo_home.join("liner.toml"), "[packages]\n").unwrap();
+        fs::write(
+            ws_dir.join("Cargo.toml"),
+            "[workspace]\n[workspace.metadata.liner.packages]\n",
+        )
+        .unwrap();
+        fs::write(
+            member_dir.join("Cargo.toml"),
+            "[package]\nname = 'member'\n",
+        )
+        .unwrap();
+        fs::write(proj_dir.join("liner.toml"), "[packages]\n").unwrap();
+
+        assert_eq!(Project::discover(&cargo_home, &cargo_home).unwrap(), None);
+        assert_eq!(
+            Project::discover(&member_dir, &cargo_home).unwrap(),
+            Some(Project {
+                dir: ws_dir.clone(),
+                config_path: ws_dir.join("Cargo.toml"),
+            }),
+        );
+        let proj = Project::discover(&proj_dir, &cargo_home).unwrap().unwrap();
+        assert_eq!(proj.config_path, proj_dir.join("liner.toml"));
+        assert_eq!(proj.root(), proj_dir.join(".cargo-liner"));
+    }
+}
@@ -0,0 +1,300 @@
+//! Reusable named sets of package options, as found in the `templates`
+//! section.
+//!
+//! See [`resolve`] in order to retrieve all the templates a package inherits
+//! from, in the order they must be applied in.
+
+use std::collections::BTreeMap;
+
+use color_eyre::eyre::{Result, bail};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::PackageDefaults;
+use crate::cli::BinstallChoice;
+
+/// Names of the templates to inherit from, either a single one or a list.
+///
+/// The enumeration is deserialized from an untagged form.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum TemplateNames {
+    /// Single template name.
+    One(String),
+    /// List of template names, the last ones taking precedence.
+    Many(Vec<String>),
+}
+
+impl Default for TemplateNames {
+    fn default() -> Self {
+        Self::Many(Vec::new())
+    }
+}
+
+impl TemplateNames {
+    /// Returns the names as a slice, in the order they were given.
+    pub fn as_slice(&self) -> &[String] {
+        match self {
+            Self::One(name) => std::slice::from_ref(name),
+            Self::Many(names) => names,
+        }
+    }
+
+    /// Returns whether no name is given at all.
+    pub fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+}
+
+/// Partial package requirement defined once under a name in the `templates`
+/// section and inherited by packages using its `template` key.
+///
+/// It supports the options of a detailed package requirement, except for the
+/// version, each of them being left unset by default.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct PackageTemplate {
+    /// Templates this one inherits from, the last ones taking precedence.
+    #[serde(skip_serializing_if = "TemplateNames::is_empty")]
+    pub template: TemplateNames,
+    /// Whether to activate the default features: `--no-default-features`.
+    pub default_features: Option<bool>,
+    /// Activate all available features: `--all-features`.
+    pub all_features: Option<bool>,
+    /// Features to activate: `--features`.
+    pub features: Option<Vec<String>>,
+    /// Registry index to install from: `--index`.
+    pub index: Option<String>,
+    /// Registry to use: `--registry`.
+    pub registry: Option<String>,
+    /// Git URL to install the packages from: `--git`.
+    pub git: Option<String>,
+    /// Branch to use when installing from Git: `--branch`.
+    pub branch: Option<String>,
+    /// Tag to use when installing from Git: `--tag`.
+    pub tag: Option<String>,
+    /// Specific commit to use when installing from Git: `--rev`.
+    pub rev: Option<String>,
+    /// Filesystem path to the local packages to install: `--path`.
+    pub path: Option<String>,
+    /// Binaries to install: `--bin`.
+    pub bins: Option<Vec<String>>,
+    /// Install all binaries: `--bins`.
+    pub all_bins: Option<bool>,
+    /// Examples to install: `--example`.
+    pub examples: Option<Vec<String>>,
+    /// Install all examples: `--examples`.
+    pub all_examples: Option<bool>,
+    /// Force overwriting existing crates or binaries: `--force`.
+    pub force: Option<bool>,
+    /// Ignore the `rust-version` of the packages: `--ignore-rust-version`.
+    pub ignore_rust_version: Option<bool>,
+    /// Require `Cargo.lock` and cache to be up-to-date: `--frozen`.
+    pub frozen: Option<bool>,
+    /// Require `Cargo.lock` to be up-to-date: `--locked`.
+    pub locked: Option<bool>,
+    /// Run without accessing the network: `--offline`.
+    pub offline: Option<bool>,
+    /// Additional CLI arguments passed onto `cargo install`.
+    pub extra_arguments: Option<Vec<String>>,
+    /// Environment variables set for the `cargo install` processes.
+    pub environment: Option<BTreeMap<String, String>>,
+    /// Do the same as the global `--skip-check`.
+    pub skip_check: Option<bool>,
+    /// Do the same as the global `--no-fail-fast`.
+    pub no_fail_fast: Option<bool>,
+    /// Do the same as the global `--target`.
+    pub target: Option<String>,
+    /// Do the same as the global `--binstall`, but with precedence over it.
+    pub binstall: Option<BinstallChoice>,
+}
+
+impl From<PackageDefaults> for PackageTemplate {
+    fn from(defs: PackageDefaults) -> Self {
+        // Full destructuring to avoid forgetting to update this function.
+        let PackageDefaults {
+            index,
+            registry,
+            force,
+            ignore_rust_version,
+            frozen,
+            locked,
+            offline,
+            extra_arguments,
+            environment,
+            skip_check,
+            no_fail_fast,
+            target,
+            binstall,
+        } = defs;
+        Self {
+            index,
+            registry,
+            force,
+            ignore_rust_version,
+            frozen,
+            locked,
+            offline,
+            extra_arguments,
+            environment,
+            skip_check,
+            no_fail_fast,
+            target,
+            binstall,
+            ..Default::default()
+        }
+    }
+}
+
+/// Returns the templates named by the given ones along with all those they
+/// inherit from, ordered by decreasing precedence.
+///
+/// A template takes precedence over the ones it inherits from, and the last
+/// given names over the first ones. It fails if a name is not defined in the
+/// given templates or if one of them ends up inheriting from itself.
+pub fn resolve<'tpl>(
+    templates: &'tpl BTreeMap<String, PackageTemplate>,
+    names: &TemplateNames,
+) -> Result<Vec<&'tpl PackageTemplate>> {
+    let mut resolved = Vec::new();
+    resolve_into(templates, names, &mut Vec::new(), &mut resolved)?;
+    Ok(resolved)
+}
+
+/// Recursive part of [`resolve`], `chain` being the names of the templates
+/// currently being resolved, from the outermost one.
+fn resolve_into<'tpl>(
+    templates: &'tpl BTreeMap<String, PackageTemplate>,
+    names: &TemplateNames,
+    chain: &mut Vec<&'tpl str>,
+    resolved: &mut Vec<&'tpl PackageTemplate>,
+) -> Result<()> {
+    for name in names.as_slice().iter().rev() {
+        let Some((name, tpl)) = templates.get_key_value(name) else {
+            bail!("Unknown template `{name}`.");
+        };
+        if let Some(start) = chain.iter().position(|other| other == name) {
+            bail!(
+                "Template `{name}` inherits from itself: {}.",
+                chain[start..]
+                    .iter()
+                    .chain([&name.as_str()])
+                    .map(|name| format!("`{name}`"))
+                    .collect::<Vec<_>>()
+                    .join(" -> "),
+            );
+        }
+
+        resolved.push(tpl);
+        chain.push(name);
+        resolve_into(templates, &tpl.template, chain, resolved)?;
+        chain.pop();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds the templates inheriting from the given ones, by name.
+    fn templates(inheritances: &[(&str, &[&str])]) -> BTreeMap<String, PackageTemplate> {
+        inheritances
+            .iter()
+            .map(|(name, parents)| {
+                (
+                    (*name).to_owned(),
+                    PackageTemplate {
+                        template: TemplateNames::Many(
+                            parents.iter().map(ToString::to_string).collect(),
+                        ),
+                        // Identify each template by its target.
+                        target: Some((*name).to_owned()),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the names of the resolved templates.
+    fn resolved_names(
+        templates: &BTreeMap<String, PackageTemplate>,
+        names: &TemplateNames,
+    ) -> Result<Vec<String>> {
+        Ok(resolve(templates, names)?
+            .into_iter()
+            .map(|tpl| tpl.target.clone().unwrap())
+            .collect())
+    }
+
+    #[test]
+    fn test_templatenames_asslice() {
+        assert!(TemplateNames::default().is_empty());
+        assert_eq!(TemplateNames::One("a".to_owned()).as_slice(), ["a"]);
+        assert_eq!(
+            TemplateNames::Many(vec!["a".to_owned(), "b".to_owned()]).as_slice(),
+            ["a", "b"],
+        );
+    }
+
+    #[test]
+    fn test_resolve_empty() {
+        assert!(
+            resolve(&templates(&[("a", &[])]), &TemplateNames::default())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_resolve_order() {
+        let tpls = templates(&[("a", &["b"]), ("b", &[]), ("c", &["b"]), ("d", &[])]);
+        assert_eq!(
+            resolved_names(&tpls, &TemplateNames::One("a".to_owned())).unwrap(),
+            ["a", "b"],
+        );
+        assert_eq!(
+            resolved_names(
+                &tpls,
+                &TemplateNames::Many(vec!["a".to_owned(), "d".to_owned(), "c".to_owned()])
+            )
+            .unwrap(),
+            ["c", "b", "d", "a", "b"],
+        );
+    }
+
+    #[test]
+    fn test_resolve_unknown_iserr() {
+        let tpls = templates(&[("a", &["b"])]);
+        assert_eq!(
+            resolve(&tpls, &TemplateNames::One("x".to_owned()))
+                .unwrap_err()
+                .to_string(),
+            "Unknown template `x`.",
+        );
+        assert_eq!(
+            resolve(&tpls, &TemplateNames::One("a".to_owned()))
+                .unwrap_err()
+                .to_string(),
+            "Unknown template `b`.",
+        );
+    }
+
+    #[test]
+    fn test_resolve_cycle_iserr() {
+        let tpls = templates(&[("a", &["b"]), ("b", &["c"]), ("c", &["b"]), ("d", &["d"])]);
+        assert_eq!(
+            resolve(&tpls, &TemplateNames::One("a".to_owned()))
+                .unwrap_err()
+                .to_string(),
+            "Template `b` inherits from itself: `b` -> `c` -> `b`.",
+        );
+        assert_eq!(
+            resolve(&tpls, &TemplateNames::One("d".to_owned()))
+                .unwrap_err()
+                .to_string(),
+            "Template `d` inherits from itself: `d` -> `d`.",
+        );
+    }
+}
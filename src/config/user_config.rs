@@ -1,31 +1,70 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::{self, File};
-use std::io::Write;
-use std::iter;
-use std::path::PathBuf;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::{env, mem};
 
 use color_eyre::Section;
-use color_eyre::eyre::{Result, WrapErr};
+use color_eyre::eyre::{Result, WrapErr, bail, eyre};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use toml_edit::{DocumentMut, Item, Table};
 
-use super::PackageRequirement;
+use super::layers::{self, Layer};
+use super::project::{self, Project};
+use super::{
+    PackageDefaults, PackageOverlay, PackageRequirement, PackageSelection, PackageTemplate,
+    Platform, document, migration, validation,
+};
 use crate::cargo;
 use crate::cli::{JettisonArgs, ShipArgs};
 
 /// Represents the user's configuration deserialized from its file.
-#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, Clone, PartialEq, Eq)]
 pub struct UserConfig {
+    /// Version of the layout the file is written for, the first one if unset:
+    /// see `cargo liner migrate`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    /// Whether to validate the file thoroughly each time it is parsed, thus
+    /// failing on issues that are otherwise ignored, such as unknown keys.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strict: bool,
+    /// Paths to other configuration files to merge after this one, relative
+    /// ones being resolved from its directory. Consumed during the merge.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<PathBuf>,
+    /// Names of the packages configured by earlier configuration files to
+    /// remove from the merged configuration. Consumed during the merge.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
     /// The name-to-setting map for the `packages` section of the config.
     pub packages: BTreeMap<String, PackageRequirement>,
+    /// The name-to-template map for the `templates` section of the config.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, PackageTemplate>,
+    /// The name-to-packages map for the `groups` section of the config.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, Vec<String>>,
+    /// The `cfg(...)`-to-overlay map for the `target` section of the config.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub target: BTreeMap<String, PackageOverlay>,
+    /// The hostname-to-overlay map for the `hosts` section of the config.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hosts: BTreeMap<String, PackageOverlay>,
     /// The option defaults section.
     #[serde(default)]
     // Use an option to have it be removed during serialization when `None`.
     pub defaults: Option<DefaultsSection>,
+    /// Root directory the packages are installed to instead of Cargo's default
+    /// one: set for project configurations. Not part of the file.
+    #[serde(skip)]
+    pub root: Option<PathBuf>,
 }
 
 /// Represents the section of the configuration dedicated to setting CLI option
 /// defaults.
-#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, Clone, PartialEq, Eq)]
 #[serde(default)]
 pub struct DefaultsSection {
     /// The sub-section supporting the `ship` command options.
@@ -34,28 +73,75 @@ pub struct DefaultsSection {
     /// The sub-section supporting the `jettison` command options.
     #[serde(rename = "jettison")]
     pub jettison_cmd: JettisonArgs,
+    /// The sub-section holding the options applied to all packages.
+    pub package: PackageDefaults,
 }
 
 impl UserConfig {
     /// The default name for the configuration file in Cargo's home.
     pub const FILE_NAME: &'static str = "liner.toml";
 
-    /// Returns the [`PathBuf`] pointing to the associated configuration file.
-    pub fn file_path() -> Result<PathBuf> {
+    /// The path designating the standard input instead of a file.
+    pub const STDIN_PATH: &'static str = "-";
+
+    /// Returns the [`PathBuf`] pointing to the associated configuration file:
+    /// see [`Self::location`].
+    pub fn file_path(config_path: Option<&Path>) -> Result<PathBuf> {
+        Ok(Self::location(config_path)?.0)
+    }
+
+    /// Returns the path to the configuration file to use along with the root
+    /// directory its packages are installed to, if not Cargo's default one.
+    ///
+    /// It is the given file if any, otherwise the project configuration
+    /// discovered from the current directory, otherwise the default file in
+    /// Cargo's home.
+    pub fn location(config_path: Option<&Path>) -> Result<(PathBuf, Option<PathBuf>)> {
         log::debug!("Building file path...");
-        Ok(cargo::home()?.join(Self::FILE_NAME))
+        if let Some(path) = config_path {
+            return Ok((path.to_owned(), None));
+        }
+
+        let home = cargo::home()?;
+        let cur_dir = env::current_dir()
+            .wrap_err("Failed to get the current directory.")
+            .suggestion("Check if the current directory still exists.")?;
+        Ok(
+            match Project::discover(&cur_dir, &home)
+                .wrap_err("Failed to discover the project configuration.")?
+            {
+                Some(project) => {
+                    let root = project.root();
+                    (project.config_path, Some(root))
+                }
+                None => (home.join(Self::FILE_NAME), None),
+            },
+        )
+    }
+
+    /// Returns whether the given path designates the standard input.
+    pub fn is_stdin(path: &Path) -> bool {
+        path == Path::new(Self::STDIN_PATH)
     }
 
-    /// Deserializes the user's configuration file and returns the result.
+    /// Deserializes the user's configuration file merged with all the other
+    /// configuration layers and returns the result.
     ///
-    /// It may fail on multiple occasions: if Cargo's home may not be found, if
-    /// the file does not exist, if it cannot be read from or if it is
-    /// malformed.
-    pub fn parse_file() -> Result<Self> {
-        let path = Self::file_path().wrap_err("Failed to build the configuration file path.")?;
+    /// The file is the given one if any, see [`Self::file_path`]. It may fail
+    /// on multiple occasions: if Cargo's home may not be found, if the file
+    /// does not exist, if it or another layer cannot be read from or if it is
+    /// malformed. See [`layers::read_all`] for the merged layers.
+    pub fn parse_file(config_path: Option<&Path>) -> Result<Self> {
+        let (path, root) =
+            Self::location(config_path).wrap_err("Failed to build the configuration file path.")?;
+        match (config_path, &root) {
+            (Some(_), _) => log::debug!("Using the given configuration."),
+            (None, Some(_)) => log::info!("Using the project configuration at {path:#?}."),
+            (None, None) => log::debug!("Using the global configuration."),
+        }
         log::debug!("Reading configuration from {path:#?}...");
-        let config_str = fs::read_to_string(path)
-            .wrap_err("Failed to read the configuration file.")
+        let config_str = Self::read_to_string(&path)
+            .wrap_err_with(|| format!("Failed to read the configuration file at {path:?}."))
             .note("This can happen for many reasons.")
             .suggestion("Check if the file exists and has the correct permissions.")
             .suggestion(
@@ -63,40 +149,142 @@ impl UserConfig {
             )?;
         log::trace!("Read {} bytes.", config_str.len());
         log::trace!("Got: {config_str:#?}.");
+        let layers = layers::read_all(
+            &path,
+            Self::own_contents(&path, config_str)?,
+            root.is_some(),
+        )
+        .wrap_err("Failed to read the configuration layers.")?;
+        let config = Self {
+            root,
+            ..Self::from_layers(&layers)?
+        };
+
+        if config.strict {
+            log::debug!("Strict mode enabled: validating contents...");
+            validation::validate(&layers)
+                .wrap_err("The configuration file is invalid in strict mode.")
+                .suggestion("Use `cargo liner check` to report the same issues at any time.")?;
+        }
+        Ok(config.self_update(true))
+    }
+
+    /// Thoroughly validates the user's configuration file along with all the
+    /// other configuration layers.
+    ///
+    /// Every issue found, including those that [`Self::parse_file`] would
+    /// otherwise ignore, is reported along with its location in the files.
+    pub fn check_file(config_path: Option<&Path>) -> Result<()> {
+        let (path, root) =
+            Self::location(config_path).wrap_err("Failed to build the configuration file path.")?;
+        let config_str = Self::read_file(&path)?
+            .ok_or_else(|| eyre!("The configuration file does not exist at {path:?}."))
+            .suggestion("It can be automatically created using `import`.")?;
+        let layers = layers::read_all(
+            &path,
+            Self::own_contents(&path, config_str)?,
+            root.is_some(),
+        )
+        .wrap_err("Failed to read the configuration layers.")?;
+        log::debug!("Validating contents...");
+        validation::validate(&layers).wrap_err("The configuration file is invalid.")
+    }
+
+    /// Returns the configuration held by the given contents of the file at the
+    /// given path: the whole contents, or only the `workspace.metadata.liner`
+    /// table for a Cargo manifest.
+    fn own_contents(path: &Path, config_str: String) -> Result<String> {
+        if !project::is_cargo_manifest(path) {
+            return Ok(config_str);
+        }
+        log::debug!("Extracting the configuration table from the Cargo manifest...");
+        let table = project::metadata_table(&config_str)
+            .wrap_err_with(|| format!("Failed to read the Cargo manifest at {path:?}."))?
+            .ok_or_else(|| {
+                eyre!("The Cargo manifest at {path:?} has no `workspace.metadata.liner` table.")
+            })
+            .suggestion("Add the configuration under that table or use a `liner.toml` file.")?;
+        toml::to_string(&table)
+            .wrap_err("Failed to serialize the configuration table of the Cargo manifest.")
+            .suggestion(crate::OPEN_ISSUE_MSG)
+    }
+
+    /// Merges the given configuration layers and deserializes the result.
+    ///
+    /// It fails if one of the layers is written for a layout that is not
+    /// supported and warns about the ones that need migrating.
+    fn from_layers(layers: &[Layer]) -> Result<Self> {
+        for layer in layers {
+            // Parsing errors are reported by the merge.
+            let Ok(table) = layer.parse() else {
+                continue;
+            };
+            let version = migration::version(&table)
+                .wrap_err_with(|| format!("Unsupported configuration file at {:?}.", layer.path))?;
+            if version < migration::CURRENT_VERSION {
+                log::warn!(
+                    "The configuration file at {:?} is written for version {version} of the \
+                     layout: run `cargo liner migrate` in order to update it to version {}.",
+                    layer.path,
+                    migration::CURRENT_VERSION,
+                );
+            }
+        }
+        let merged = layers::merge(layers).wrap_err("Failed to merge the configuration layers.")?;
         log::debug!("Deserializing contents...");
-        let config = toml::from_str::<Self>(&config_str)
+        let config = toml::Value::Table(merged)
+            .try_into::<Self>()
             .wrap_err("Failed to deserialize the configuration file contents.")
             .note("This can easily happen as the file is edited manually.")
-            .suggestion("Check the file for any typos and syntax errors.")?;
+            .suggestion("Check the file for any typos and syntax errors.")
+            .suggestion("Use `cargo liner check` to locate the issues in the files.")?;
         log::trace!("Got: {config:#?}.");
-        Ok(config.self_update(true))
+        Ok(config)
     }
 
-    /// Serializes the configuration and saves it to the default file.
+    /// Serializes the configuration and saves it to the given or default file.
     ///
     /// It creates the file if it does not already exist. If it already exists,
-    /// contents will be enterily overwritten. Just as [`Self::parse_file`], it
-    /// may fail on several occasions.
-    pub fn overwrite_file(&self) -> Result<()> {
-        let path = Self::file_path().wrap_err("Failed to build the configuration file path.")?;
-        let config_str = self.to_string_pretty()?;
+    /// contents will be updated in place: only the keys whose value changed are
+    /// rewritten, so comments, key order and the inline or standard style of
+    /// the tables are kept, while keys absent from the configuration are
+    /// removed. A file that is not even valid TOML is enterily overwritten.
+    /// Just as [`Self::parse_file`], it may fail on several occasions.
+    pub fn overwrite_file(&self, config_path: Option<&Path>) -> Result<()> {
+        let (path, _) = Self::writable_location(config_path)?;
+        let config_str = match Self::read_file(&path)?.map(|old_str| old_str.parse::<DocumentMut>())
+        {
+            Some(Ok(mut doc)) => {
+                log::debug!("Updating the existing configuration document...");
+                document::update_table(doc.as_table_mut(), self.to_document()?.as_table());
+                doc.to_string()
+            }
+            Some(Err(err)) => {
+                log::warn!("The existing configuration file is invalid: overwriting it entirely.");
+                log::debug!("Parsing error: {err}");
+                self.to_document()?.to_string()
+            }
+            None => self.to_document()?.to_string(),
+        };
         log::debug!("Overwriting configuration to {path:#?}...");
-        fs::write(path, config_str)
-            .wrap_err("Failed to write the new configuration file contents.")
+        fs::write(&path, config_str)
+            .wrap_err_with(|| {
+                format!("Failed to write the new configuration file contents to {path:?}.")
+            })
             .note("This can happen for many reasons.")
             .suggestion("Check the permissions of Cargo's directory and of the file.")?;
         Ok(())
     }
 
-    /// Serializes the configuration and saves it to the default file without
-    /// overwriting it.
+    /// Serializes the configuration and saves it to the given or default file
+    /// without overwriting it.
     ///
     /// It creates the file if it does not already exist. If it already exists,
     /// it will fail on an appropriate error. Just as [`Self::overwrite_file`],
     /// it may fail on several occasions.
-    pub fn save_file(&self) -> Result<()> {
-        let path = Self::file_path().wrap_err("Failed to build the configuration file path.")?;
-        let config_str = self.to_string_pretty()?;
+    pub fn save_file(&self, config_path: Option<&Path>) -> Result<()> {
+        let (path, _) = Self::writable_location(config_path)?;
+        let config_str = self.to_document()?.to_string();
         log::debug!("Writing configuration to {path:#?}...");
         File::options()
             .read(true)
@@ -115,6 +303,163 @@ impl UserConfig {
         Ok(())
     }
 
+    /// Edits the given or default configuration file in place using the given
+    /// function and returns the resulting configuration, merged with the other
+    /// layers.
+    ///
+    /// The file is handled as a TOML document, so everything that the edit
+    /// does not touch, comments and formatting included, is kept as-is. A
+    /// missing file is considered as an empty configuration and is created.
+    /// The edited document must still be a valid configuration in order to
+    /// be written.
+    pub fn edit_file(
+        config_path: Option<&Path>,
+        edit: impl FnOnce(&mut DocumentMut) -> Result<()>,
+    ) -> Result<Self> {
+        let (path, root) = Self::writable_location(config_path)?;
+        let mut doc = if let Some(config_str) = Self::read_file(&path)? {
+            log::debug!("Parsing contents as an editable document...");
+            config_str
+                .parse::<DocumentMut>()
+                .wrap_err("Failed to parse the configuration file contents.")
+                .note("This can easily happen as the file is edited manually.")
+                .suggestion("Check the file for any typos and syntax errors.")?
+        } else {
+            log::debug!("File not found: starting from an empty configuration.");
+            let mut doc = DocumentMut::new();
+            doc.insert("packages", Item::Table(Table::new()));
+            doc
+        };
+        edit(&mut doc)?;
+        let config_str = doc.to_string();
+        log::trace!("Edited into: {config_str:#?}.");
+
+        log::debug!("Validating the edited contents...");
+        toml::from_str::<Self>(&config_str)
+            .wrap_err("Failed to deserialize the edited configuration.")
+            .note("The file could already be invalid before the edit.")
+            .suggestion("Check the file for any typos and syntax errors.")?;
+        log::debug!("Writing configuration to {path:#?}...");
+        fs::write(&path, &config_str)
+            .wrap_err_with(|| {
+                format!("Failed to write the edited configuration file contents to {path:?}.")
+            })
+            .note("This can happen for many reasons.")
+            .suggestion("Check the permissions of Cargo's directory and of the file.")?;
+        let layers = layers::read_all(&path, config_str, root.is_some())
+            .wrap_err("Failed to read the configuration layers.")?;
+        Ok(Self {
+            root,
+            ..Self::from_layers(&layers)?
+        })
+    }
+
+    /// Migrates the given or default configuration file in place to the
+    /// current layout and returns its contents before and after, along with
+    /// the summaries of the migrations applied.
+    ///
+    /// Only what the migrations change is rewritten, so comments and
+    /// formatting are kept. The file must exist and is left untouched when
+    /// `dry_run` is `true` or when it is already up to date.
+    pub fn migrate_file(
+        config_path: Option<&Path>,
+        dry_run: bool,
+    ) -> Result<(String, String, Vec<&'static str>)> {
+        let (path, _) = Self::writable_location(config_path)?;
+        let old_str = Self::read_file(&path)?
+            .ok_or_else(|| eyre!("The configuration file does not exist at {path:?}."))
+            .suggestion("It can be automatically created using `import`.")?;
+        let mut doc = old_str
+            .parse::<DocumentMut>()
+            .wrap_err("Failed to parse the configuration file contents.")
+            .note("This can easily happen as the file is edited manually.")
+            .suggestion("Check the file for any typos and syntax errors.")?;
+        let summaries = migration::migrate(&mut doc)
+            .wrap_err_with(|| format!("Failed to migrate the configuration file at {path:?}."))?;
+        let new_str = doc.to_string();
+
+        if dry_run || summaries.is_empty() {
+            log::debug!("Not writing the configuration file.");
+        } else {
+            log::debug!("Writing configuration to {path:#?}...");
+            fs::write(&path, &new_str)
+                .wrap_err_with(|| {
+                    format!("Failed to write the migrated configuration file contents to {path:?}.")
+                })
+                .note("This can happen for many reasons.")
+                .suggestion("Check the permissions of Cargo's directory and of the file.")?;
+        }
+        Ok((old_str, new_str, summaries))
+    }
+
+    /// Returns the [`Self::location`] of the configuration file, failing if it
+    /// designates the standard input or a Cargo manifest as they cannot be
+    /// written to.
+    fn writable_location(config_path: Option<&Path>) -> Result<(PathBuf, Option<PathBuf>)> {
+        let (path, root) =
+            Self::location(config_path).wrap_err("Failed to build the configuration file path.")?;
+        if Self::is_stdin(&path) {
+            return Err(eyre!(
+                "Cannot write the configuration file as it is read from the standard input."
+            ))
+            .suggestion("Use `--config` with the path to an actual file instead.");
+        }
+        if project::is_cargo_manifest(&path) {
+            return Err(eyre!(
+                "Cannot write the configuration held by the Cargo manifest at {path:?}."
+            ))
+            .suggestion("Edit its `workspace.metadata.liner` table manually instead.");
+        }
+        Ok((path, root))
+    }
+
+    /// Reads the file at the given path to a string, or the standard input if
+    /// the path designates it.
+    fn read_to_string(path: &Path) -> io::Result<String> {
+        if Self::is_stdin(path) {
+            let mut config_str = String::new();
+            io::stdin().read_to_string(&mut config_str)?;
+            Ok(config_str)
+        } else {
+            fs::read_to_string(path)
+        }
+    }
+
+    /// Reads the file at the given path to a string, returning `None` if it
+    /// does not exist.
+    fn read_file(path: &Path) -> Result<Option<String>> {
+        log::debug!("Reading configuration from {path:#?}...");
+        match Self::read_to_string(path) {
+            Ok(config_str) => {
+                log::trace!("Read {} bytes.", config_str.len());
+                Ok(Some(config_str))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err)
+                .wrap_err_with(|| format!("Failed to read the configuration file at {path:?}."))
+                .note("This can happen for many reasons.")
+                .suggestion("Check if the file has the correct permissions."),
+        }
+    }
+
+    /// Converts the config to a TOML document, with the packages in their
+    /// most compact form: see [`PackageRequirement::to_toml_value`].
+    fn to_document(&self) -> Result<DocumentMut> {
+        let mut doc = self
+            .to_string_pretty()?
+            .parse::<DocumentMut>()
+            .wrap_err("Failed to parse the serialized user configuration.")
+            .suggestion(crate::OPEN_ISSUE_MSG)?;
+        // Always serialized as a table, even when empty.
+        if let Some(pkgs) = doc.get_mut("packages").and_then(Item::as_table_mut) {
+            pkgs.clear();
+            for (pkg_name, pkg_req) in &self.packages {
+                pkgs.insert(pkg_name, Item::Value(pkg_req.to_toml_value()?));
+            }
+        }
+        Ok(doc)
+    }
+
     /// Converts the config to a pretty TOML string with literal strings
     /// disabled.
     fn to_string_pretty(&self) -> Result<String> {
@@ -127,6 +472,31 @@ impl UserConfig {
         Ok(res)
     }
 
+    /// Adds the packages of the overlays applying to the given platform on
+    /// top of the configured ones: irreversible.
+    ///
+    /// The `target` overlays are applied first, then the `hosts` ones, each
+    /// package of an overlay replacing any previous one of the same name. It
+    /// fails if one of the `target` keys is not a valid `cfg(...)` expression.
+    pub fn with_overlays(mut self, platform: &Platform) -> Result<Self> {
+        for (spec, overlay) in mem::take(&mut self.target) {
+            if platform
+                .matches_target(&spec)
+                .wrap_err_with(|| format!("Invalid `target` key `{spec}`."))?
+            {
+                log::debug!("Applying the overlay of target `{spec}`...");
+                self.packages.extend(overlay.packages);
+            }
+        }
+        for (hostname, overlay) in mem::take(&mut self.hosts) {
+            if platform.matches_host(&hostname) {
+                log::debug!("Applying the overlay of host `{hostname}`...");
+                self.packages.extend(overlay.packages);
+            }
+        }
+        Ok(self)
+    }
+
     /// Enable or disable self-updating.
     ///
     /// If `sup` is `true` and the current crate is not already contained in
@@ -147,21 +517,88 @@ impl UserConfig {
     /// Enable or disable updating other packages.
     ///
     /// If `upo` is `false`, then the list of packages is reset to only contain
-    /// the current crate: irreversible. Otherwise, nothing is done.
+    /// the current crate, as if it were the only one selected by name:
+    /// irreversible. Otherwise, nothing is done.
     pub fn update_others(mut self, upo: bool) -> Self {
         if !upo {
-            self.packages = iter::once((
-                clap::crate_name!().to_owned(),
-                self.packages
-                    .get(clap::crate_name!())
-                    .unwrap_or(&PackageRequirement::SIMPLE_STAR)
-                    .clone(),
-            ))
-            .collect();
+            self.packages
+                .entry(clap::crate_name!().to_owned())
+                .or_insert(PackageRequirement::SIMPLE_STAR);
+            self.retain_selected(&PackageSelection::names([clap::crate_name!()]));
             log::debug!("Updating of other packages disabled.");
         }
         self
     }
+
+    /// Keeps only the packages chosen by the given selection: irreversible.
+    ///
+    /// It fails if one of the selected names is not configured or if one of
+    /// the selected groups is neither defined in the `groups` section nor by
+    /// any package.
+    pub fn select(mut self, selection: &PackageSelection) -> Result<Self> {
+        if selection.is_all() {
+            return Ok(self);
+        }
+        if let Some(pkg_name) = selection
+            .names
+            .iter()
+            .find(|pkg_name| !self.packages.contains_key(*pkg_name))
+        {
+            bail!("Package `{pkg_name}` is not configured.");
+        }
+        let group_names = self.group_names();
+        if let Some(group) = selection
+            .groups
+            .iter()
+            .find(|group| !group_names.contains(group.as_str()))
+        {
+            bail!("Unknown group `{group}`.");
+        }
+
+        self.retain_selected(selection);
+        Ok(self)
+    }
+
+    /// Returns the groups the given package belongs to, either through its
+    /// own `groups` key or through the `groups` section.
+    pub fn package_groups(&self, pkg_name: &str) -> BTreeSet<&str> {
+        self.packages
+            .get(pkg_name)
+            .map(PackageRequirement::groups)
+            .unwrap_or_default()
+            .iter()
+            .map(String::as_str)
+            .chain(
+                self.groups
+                    .iter()
+                    .filter(|(_, pkg_names)| pkg_names.iter().any(|name| name == pkg_name))
+                    .map(|(group, _)| group.as_str()),
+            )
+            .collect()
+    }
+
+    /// Returns the names of all the groups, defined in the `groups` section
+    /// or by any package.
+    fn group_names(&self) -> BTreeSet<&str> {
+        self.groups
+            .keys()
+            .chain(self.packages.values().flat_map(PackageRequirement::groups))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Removes the packages not chosen by the given selection.
+    fn retain_selected(&mut self, selection: &PackageSelection) {
+        let selected = self
+            .packages
+            .keys()
+            .filter(|pkg_name| selection.selects(pkg_name, &self.package_groups(pkg_name)))
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        log::debug!("Selected packages: {selected:?}.");
+        self.packages
+            .retain(|pkg_name, _| selected.contains(pkg_name));
+    }
 }
 
 #[cfg(test)]
@@ -171,6 +608,7 @@ mod tests {
         reason = "Some tests here explicitly detail all tested cases, which can be long."
     )]
     use std::io::{BufRead, BufReader};
+    use std::num::NonZeroUsize;
     use std::path::Path;
     use std::{env, iter};
 
@@ -180,6 +618,7 @@ mod tests {
     use super::*;
     use crate::cli::BinstallChoice;
     use crate::config::DetailedPackageReq;
+    use crate::config::template::TemplateNames;
 
     #[test]
     fn test_deser_userconfig_empty_iserr() {
@@ -227,6 +666,10 @@ mod tests {
             )
             .unwrap(),
             UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
                 packages: [
                     ("a", "1.2.3"),
                     ("b", "1.2"),
@@ -243,7 +686,12 @@ mod tests {
                     PackageRequirement::Simple(VersionReq::parse(version).unwrap()),
                 ))
                 .collect::<BTreeMap<_, _>>(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
                 defaults: None,
+                root: None,
             }
         );
     }
@@ -298,8 +746,8 @@ mod tests {
             DetailedPackageReq {
                 version: "^1.2.3".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -308,26 +756,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec!["foo".to_owned()],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -336,26 +786,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec!["foo".to_owned()],
-                all_features: false,
-                default_features: false,
+                all_features: None,
+                default_features: Some(false),
                 index: None,
                 registry: None,
                 git: None,
@@ -364,26 +816,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: true,
-                default_features: true,
+                all_features: Some(true),
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -392,26 +846,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -420,26 +876,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -448,26 +906,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -476,26 +936,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec!["--abc".to_owned(), "--def".to_owned()],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -504,26 +966,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -532,29 +996,31 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: [("ABC", "def"), ("XYZ", "123")]
                     .into_iter()
                     .map(|(k, v)| (k.to_owned(), v.to_owned()))
                     .collect(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: Some("http://abc123.com".to_owned()),
                 registry: None,
                 git: None,
@@ -563,26 +1029,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: Some("abc123".to_owned()),
                 git: None,
@@ -591,26 +1059,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: Some("https://mygit.com/abc/123".to_owned()),
@@ -619,26 +1089,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -647,26 +1119,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -675,26 +1149,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -703,26 +1179,28 @@ mod tests {
                 rev: Some("fakesha1".to_owned()),
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -731,26 +1209,28 @@ mod tests {
                 rev: None,
                 path: Some("/a/b/c".to_owned()),
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -759,26 +1239,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec!["bin1".to_owned(), "bin2".to_owned()],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -787,26 +1269,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: true,
+                all_bins: Some(true),
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -815,26 +1299,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec!["ex1".to_owned(), "ex2".to_owned()],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -843,26 +1329,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: true,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: Some(true),
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -871,26 +1359,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: true,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: Some(true),
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -899,26 +1389,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: true,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: Some(true),
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -927,26 +1419,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: true,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: Some(true),
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -955,26 +1449,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: true,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: Some(true),
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -983,26 +1479,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: true,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: Some(true),
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -1011,26 +1509,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: true,
-                no_fail_fast: false,
+                skip_check: Some(true),
+                no_fail_fast: None,
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -1039,26 +1539,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: true,
+                skip_check: None,
+                no_fail_fast: Some(true),
                 target: None,
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -1067,26 +1569,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: Some(BinstallChoice::Auto),
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -1095,26 +1599,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: Some(BinstallChoice::Always),
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -1123,26 +1629,28 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: None,
                 binstall: Some(BinstallChoice::Never),
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
             DetailedPackageReq {
                 version: "^1.2".parse().unwrap(),
                 features: vec![],
-                all_features: false,
-                default_features: true,
+                all_features: None,
+                default_features: None,
                 index: None,
                 registry: None,
                 git: None,
@@ -1151,20 +1659,22 @@ mod tests {
                 rev: None,
                 path: None,
                 bins: vec![],
-                all_bins: false,
+                all_bins: None,
                 examples: vec![],
-                all_examples: false,
-                force: false,
-                ignore_rust_version: false,
-                frozen: false,
-                locked: false,
-                offline: false,
+                all_examples: None,
+                force: None,
+                ignore_rust_version: None,
+                frozen: None,
+                locked: None,
+                offline: None,
                 extra_arguments: vec![],
                 environment: BTreeMap::new(),
-                skip_check: false,
-                no_fail_fast: false,
+                skip_check: None,
+                no_fail_fast: None,
                 target: Some("some-random-target-triple".to_owned()),
                 binstall: None,
+                template: TemplateNames::default(),
+                groups: Vec::new(),
             },
         ]
         .into_iter()
@@ -1189,7 +1699,12 @@ mod tests {
                 "```toml" => in_block = true,
                 "```" if in_block => {
                     in_block = false;
-                    configs.push(toml::from_str::<UserConfig>(&cur_block).unwrap());
+                    // Project manifests hold the configuration in a table.
+                    let config_str = match project::metadata_table(&cur_block).unwrap() {
+                        Some(table) => toml::to_string(&table).unwrap(),
+                        None => cur_block.clone(),
+                    };
+                    configs.push(toml::from_str::<UserConfig>(&config_str).unwrap());
                     cur_block.clear();
                 }
                 _ if in_block => {
@@ -1220,7 +1735,7 @@ mod tests {
                             "ripgrep".to_owned(),
                             PackageRequirement::Detailed(Box::new(DetailedPackageReq {
                                 version: "13.0.0".parse().unwrap(),
-                                all_features: true,
+                                all_features: Some(true),
                                 ..Default::default()
                             })),
                         ),
@@ -1228,7 +1743,7 @@ mod tests {
                             "sqlx-cli".to_owned(),
                             PackageRequirement::Detailed(Box::new(DetailedPackageReq {
                                 version: "0.6.2".parse().unwrap(),
-                                default_features: false,
+                                default_features: Some(false),
                                 features: vec!["native-tls".to_owned(), "postgres".to_owned()],
                                 ..Default::default()
                             })),
@@ -1239,6 +1754,10 @@ mod tests {
                     ..Default::default()
                 },
                 UserConfig {
+                    version: Some(1),
+                    strict: true,
+                    include: vec![PathBuf::from("path-1"), PathBuf::from("path-2")],
+                    exclude: vec!["package-name-4".to_owned()],
                     packages: [
                         (
                             "package-name-1".to_owned(),
@@ -1254,8 +1773,8 @@ mod tests {
                             "package-name-3".to_owned(),
                             PackageRequirement::Detailed(Box::new(DetailedPackageReq {
                                 version: "3".parse().unwrap(),
-                                all_features: true,
-                                default_features: false,
+                                all_features: Some(true),
+                                default_features: Some(false),
                                 features: vec!["feature-1".to_owned(), "feature-2".to_owned()],
                                 index: Some("http://example.com/".to_owned()),
                                 registry: Some("example-registry".to_owned()),
@@ -1265,14 +1784,14 @@ mod tests {
                                 rev: Some("SHA1".to_owned()),
                                 path: Some("/a/b/c".to_owned()),
                                 bins: vec!["bin1".to_owned(), "bin2".to_owned()],
-                                all_bins: true,
+                                all_bins: Some(true),
                                 examples: vec!["ex1".to_owned(), "ex2".to_owned()],
-                                all_examples: false,
-                                force: true,
-                                ignore_rust_version: false,
-                                frozen: true,
-                                locked: false,
-                                offline: true,
+                                all_examples: Some(false),
+                                force: Some(true),
+                                ignore_rust_version: Some(false),
+                                frozen: Some(true),
+                                locked: Some(false),
+                                offline: Some(true),
                                 extra_arguments: vec!["--arg1".to_owned(), "--arg2".to_owned()],
                                 environment: [
                                     ("ENV1".to_owned(), "abc".to_owned()),
@@ -1280,17 +1799,74 @@ mod tests {
                                 ]
                                 .into_iter()
                                 .collect(),
-                                skip_check: false,
-                                no_fail_fast: true,
+                                skip_check: Some(false),
+                                no_fail_fast: Some(true),
                                 target: Some("example-target-triple-string".to_owned()),
                                 binstall: Some(BinstallChoice::Never),
+                                template: TemplateNames::Many(vec!["template-name-1".to_owned()]),
+                                groups: vec!["group-name-1".to_owned()],
                             })),
                         ),
                     ]
                     .into_iter()
                     .collect(),
+                    templates: [
+                        (
+                            "template-name-1".to_owned(),
+                            PackageTemplate {
+                                template: TemplateNames::One("template-name-2".to_owned()),
+                                registry: Some("example-registry".to_owned()),
+                                locked: Some(true),
+                                binstall: Some(BinstallChoice::Never),
+                                ..Default::default()
+                            },
+                        ),
+                        (
+                            "template-name-2".to_owned(),
+                            PackageTemplate {
+                                environment: Some(
+                                    [("RUSTFLAGS".to_owned(), "-C target-cpu=native".to_owned())]
+                                        .into(),
+                                ),
+                                ..Default::default()
+                            },
+                        ),
+                    ]
+                    .into(),
+                    groups: [(
+                        "group-name-2".to_owned(),
+                        vec!["package-name-1".to_owned(), "package-name-2".to_owned()],
+                    )]
+                    .into(),
+                    target: [(
+                        r#"cfg(target_arch = "aarch64")"#.to_owned(),
+                        PackageOverlay {
+                            packages: [(
+                                "package-name-5".to_owned(),
+                                PackageRequirement::Simple("5".parse().unwrap()),
+                            )]
+                            .into(),
+                        },
+                    )]
+                    .into(),
+                    hosts: [(
+                        "host-name-1".to_owned(),
+                        PackageOverlay {
+                            packages: [(
+                                "package-name-6".to_owned(),
+                                PackageRequirement::Detailed(Box::new(DetailedPackageReq {
+                                    version: "6".parse().unwrap(),
+                                    locked: Some(true),
+                                    ..Default::default()
+                                })),
+                            )]
+                            .into(),
+                        },
+                    )]
+                    .into(),
                     defaults: Some(DefaultsSection {
                         ship_cmd: ShipArgs {
+                            packages: Vec::new(),
                             no_self: Some(true),
                             only_self: Some(false),
                             skip_check: Some(true),
@@ -1298,10 +1874,50 @@ mod tests {
                             force: Some(true),
                             dry_run: Some(false),
                             target: Some("example-target-triple-string".to_owned()),
+                            group: Some(vec!["group-name-1".to_owned(), "group-name-2".to_owned()]),
+                            exclude_group: Some(vec!["group-name-3".to_owned()]),
+                            locked_config: Some(false),
+                            update_lock: Some(false),
                             binstall: Some(BinstallChoice::Always),
+                            jobs: NonZeroUsize::new(4),
+                            lookup_jobs: NonZeroUsize::new(8),
+                        },
+                        package: PackageDefaults {
+                            locked: Some(true),
+                            environment: Some(
+                                [("RUSTFLAGS".to_owned(), "-C target-cpu=native".to_owned())]
+                                    .into(),
+                            ),
+                            ..Default::default()
                         },
                         ..Default::default()
                     }),
+                    root: None,
+                },
+                UserConfig {
+                    packages: [
+                        (
+                            "cargo-nextest".to_owned(),
+                            PackageRequirement::Simple("=0.9.72".parse().unwrap()),
+                        ),
+                        (
+                            "cargo-deny".to_owned(),
+                            PackageRequirement::Detailed(Box::new(DetailedPackageReq {
+                                version: "=0.14.24".parse().unwrap(),
+                                locked: Some(true),
+                                ..Default::default()
+                            })),
+                        ),
+                    ]
+                    .into_iter()
+                    .collect(),
+                    ..Default::default()
+                },
+                UserConfig {
+                    packages: [("cargo-liner".to_owned(), PackageRequirement::SIMPLE_STAR)]
+                        .into_iter()
+                        .collect(),
+                    ..Default::default()
                 },
                 UserConfig {
                     packages: [
@@ -1332,6 +1948,30 @@ mod tests {
                     .collect(),
                     ..Default::default()
                 },
+                UserConfig {
+                    packages: [
+                        (
+                            "bat".to_owned(),
+                            PackageRequirement::Detailed(Box::new(DetailedPackageReq {
+                                version: "~0.22.1".parse().unwrap(),
+                                default_features: Some(false),
+                                features: vec!["minimal-application".to_owned()],
+                                ..Default::default()
+                            })),
+                        ),
+                        (
+                            "cargo-make".to_owned(),
+                            PackageRequirement::Simple("~0.36.3".parse().unwrap()),
+                        ),
+                        (
+                            "cargo-outdated".to_owned(),
+                            PackageRequirement::Simple("~0.11.1".parse().unwrap()),
+                        ),
+                    ]
+                    .into_iter()
+                    .collect(),
+                    ..Default::default()
+                },
             ],
         );
     }
@@ -1408,6 +2048,7 @@ mod tests {
             .unwrap()
             .ship_cmd,
             ShipArgs {
+                packages: Vec::new(),
                 only_self: None,
                 no_self: Some(true),
                 no_fail_fast: Some(true),
@@ -1415,7 +2056,13 @@ mod tests {
                 force: Some(false),
                 dry_run: Some(true),
                 target: Some("helloarch128-yes-linux-nosql".to_owned()),
+                group: None,
+                exclude_group: None,
+                locked_config: None,
+                update_lock: None,
                 binstall: None,
+                jobs: None,
+                lookup_jobs: None,
             }
         );
     }
@@ -1448,6 +2095,52 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_userconfig_defaults_ship_jobs() {
+        assert_eq!(
+            toml::from_str::<UserConfig>(
+                r#"
+                    [packages]
+                    abc = "*"
+                    [defaults.ship]
+                    jobs = 4
+                    lookup-jobs = 8
+                "#
+            )
+            .unwrap()
+            .defaults
+            .unwrap()
+            .ship_cmd,
+            ShipArgs {
+                jobs: NonZeroUsize::new(4),
+                lookup_jobs: NonZeroUsize::new(8),
+                ..Default::default()
+            }
+        );
+        assert!(
+            toml::from_str::<UserConfig>(
+                r#"
+                    [packages]
+                    abc = "*"
+                    [defaults.ship]
+                    jobs = 0
+                "#
+            )
+            .is_err()
+        );
+        assert!(
+            toml::from_str::<UserConfig>(
+                r#"
+                    [packages]
+                    abc = "*"
+                    [defaults.ship]
+                    lookup-jobs = 0
+                "#
+            )
+            .is_err()
+        );
+    }
+
     #[test]
     fn test_userconfig_defaults_jettison() {
         assert_eq!(
@@ -1485,6 +2178,10 @@ mod tests {
     fn test_userconfig_tostringpretty_simple_versions() {
         assert_eq!(
             UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
                 packages: [
                     ("a", "1.2.3"),
                     ("b", "1.2"),
@@ -1501,7 +2198,12 @@ mod tests {
                     PackageRequirement::Simple(VersionReq::parse(version).unwrap()),
                 ))
                 .collect::<BTreeMap<_, _>>(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
                 defaults: None,
+                root: None,
             }
             .to_string_pretty()
             .unwrap(),
@@ -1540,8 +2242,17 @@ mod tests {
 
         assert_eq!(
             UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
                 packages: pkgs.clone(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
                 defaults: None,
+                root: None,
             }
             .self_update(true)
             .packages,
@@ -1564,12 +2275,21 @@ mod tests {
     fn test_userconfig_selfupdate_disable_nostar() {
         assert_eq!(
             UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
                 packages: iter::once((
                     "cargo-liner".to_owned(),
                     PackageRequirement::Simple(VersionReq::parse("1.2.3").unwrap()),
                 ))
                 .collect::<BTreeMap<_, _>>(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
                 defaults: None,
+                root: None,
             }
             .self_update(false)
             .packages,
@@ -1620,12 +2340,148 @@ mod tests {
 
         assert_eq!(
             UserConfig {
+                version: None,
+                strict: false,
+                include: Vec::new(),
+                exclude: Vec::new(),
                 packages: pkgs.clone(),
+                templates: BTreeMap::new(),
+                groups: BTreeMap::new(),
+                target: BTreeMap::new(),
+                hosts: BTreeMap::new(),
                 defaults: None,
+                root: None,
             }
             .update_others(false)
             .packages,
             pkgs,
         );
     }
+
+    #[test]
+    fn test_userconfig_packagegroups() {
+        let config = toml::from_str::<UserConfig>(indoc!(
+            r#"
+                [packages]
+                abc = "*"
+                def = { version = "*", groups = ["release", "dev"] }
+                [groups]
+                heavy = ["abc", "def"]
+                dev = ["def"]
+            "#
+        ))
+        .unwrap();
+
+        assert_eq!(config.package_groups("abc"), BTreeSet::from(["heavy"]));
+        assert_eq!(
+            config.package_groups("def"),
+            BTreeSet::from(["dev", "heavy", "release"]),
+        );
+        assert!(config.package_groups("ghi").is_empty());
+    }
+
+    #[test]
+    fn test_userconfig_select() {
+        let config = toml::from_str::<UserConfig>(indoc!(
+            r#"
+                [packages]
+                abc = "*"
+                def = { version = "*", groups = ["release"] }
+                [groups]
+                empty = []
+            "#
+        ))
+        .unwrap();
+
+        assert_eq!(
+            config.clone().select(&PackageSelection::default()).unwrap(),
+            config,
+        );
+        assert_eq!(
+            config
+                .clone()
+                .select(&PackageSelection {
+                    groups: vec!["release".to_owned(), "empty".to_owned()],
+                    ..Default::default()
+                })
+                .unwrap()
+                .packages
+                .into_keys()
+                .collect::<Vec<_>>(),
+            ["def"],
+        );
+        assert_eq!(
+            config
+                .clone()
+                .select(&PackageSelection::names(["ghi"]))
+                .unwrap_err()
+                .to_string(),
+            "Package `ghi` is not configured.",
+        );
+        assert_eq!(
+            config
+                .select(&PackageSelection {
+                    groups: vec!["heavy".to_owned()],
+                    ..Default::default()
+                })
+                .unwrap_err()
+                .to_string(),
+            "Unknown group `heavy`.",
+        );
+    }
+
+    #[test]
+    fn test_userconfig_withoverlays() {
+        let config = toml::from_str::<UserConfig>(indoc!(
+            r#"
+                [packages]
+                abc = "1"
+                def = "2"
+                [target.'cfg(unix)'.packages]
+                def = "3"
+                ghi = "4"
+                [target.'cfg(windows)'.packages]
+                jkl = "5"
+                [hosts.workstation.packages]
+                ghi = "6"
+                [hosts.ci-runner.packages]
+                mno = "7"
+            "#
+        ))
+        .unwrap();
+        let platform = Platform {
+            cfgs: vec![("unix".to_owned(), None)],
+            hostname: Some("WorkStation".to_owned()),
+        };
+
+        assert_eq!(
+            config.clone().with_overlays(&platform).unwrap(),
+            toml::from_str::<UserConfig>(indoc!(
+                r#"
+                    [packages]
+                    abc = "1"
+                    def = "3"
+                    ghi = "6"
+                "#
+            ))
+            .unwrap(),
+        );
+        assert_eq!(
+            config
+                .with_overlays(&Platform::default())
+                .unwrap()
+                .packages
+                .into_keys()
+                .collect::<Vec<_>>(),
+            ["abc", "def"],
+        );
+        assert_eq!(
+            toml::from_str::<UserConfig>("[packages]\n[target.x86_64-pc-windows-msvc.packages]\n")
+                .unwrap()
+                .with_overlays(&platform)
+                .unwrap_err()
+                .to_string(),
+            "Invalid `target` key `x86_64-pc-windows-msvc`.",
+        );
+    }
 }
@@ -0,0 +1,833 @@
+//! Thorough validation of the user configuration file contents.
+//!
+//! Contrary to the mere deserialization done by [`UserConfig::parse_file`],
+//! which ignores unknown keys and stops at the first error, [`validate`]
+//! collects every issue found, each one located in the file by its line and
+//! column.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use color_eyre::Section;
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use semver::VersionReq;
+use serde::Deserialize;
+use serde::de::{self, Deserializer, Visitor};
+use toml_edit::{Document, Item, Key, TableLike};
+
+use super::layers::{self, Layer};
+use super::platform::CfgExpr;
+use super::template::{self, TemplateNames};
+use super::user_config::DefaultsSection;
+use super::{DetailedPackageReq, PackageDefaults, PackageOverlay, PackageTemplate, UserConfig};
+use crate::cargo;
+use crate::cli::{BinstallChoice, JettisonArgs, ShipArgs};
+
+/// Issue found in a configuration file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    /// Path to the file containing the issue.
+    pub path: PathBuf,
+    /// One-based line number of the issue's location.
+    pub line: usize,
+    /// One-based column number of the issue's location, in characters.
+    pub column: usize,
+    /// Description of the issue.
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.message,
+        )
+    }
+}
+
+impl Error for Issue {}
+
+/// Validates the given configuration layers, reporting all issues found in
+/// any of them as sections of the returned error.
+pub fn validate(layers: &[Layer]) -> Result<()> {
+    // Layers are checked in the context of the others, as far as possible.
+    let merged =
+        layers::merge(layers.iter().filter(|layer| layer.parse().is_ok())).unwrap_or_default();
+    let issues = layers
+        .iter()
+        .flat_map(|layer| find_issues(layer, &merged))
+        .collect::<Vec<_>>();
+    if issues.is_empty() {
+        // Only the missing packages can remain, being spread over the layers.
+        return toml::Value::Table(merged)
+            .try_into::<UserConfig>()
+            .map(|_| ())
+            .wrap_err("The merged configuration is invalid.")
+            .suggestion("Define the `packages` table in at least one configuration file.");
+    }
+
+    let mut report = eyre!("Found {} issue(s) in the configuration file.", issues.len());
+    for issue in issues {
+        report = report.error(issue);
+    }
+    Err(report.suggestion("Fix each of the reported issues in the configuration file."))
+}
+
+/// Returns all the issues found in the given configuration layer, in the
+/// order of their location, using the given merged configuration in order to
+/// resolve what the layer may refer to, such as templates.
+pub fn find_issues(layer: &Layer, merged: &toml::Table) -> Vec<Issue> {
+    let config_str = &layer.contents;
+    let mut checker = Checker::default();
+    checker.check(config_str, merged);
+    checker.issues.sort_by_key(|(offset, _)| *offset);
+    checker
+        .issues
+        .into_iter()
+        .map(|(offset, message)| {
+            let before = &config_str[..offset.min(config_str.len())];
+            let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+            Issue {
+                path: layer.path.clone(),
+                line: before.matches('\n').count() + 1,
+                column: before[line_start..].chars().count() + 1,
+                message,
+            }
+        })
+        .collect()
+}
+
+/// Accumulates issues by their byte offset in the file contents.
+#[derive(Debug, Default)]
+struct Checker {
+    issues: Vec<(usize, String)>,
+}
+
+impl Checker {
+    /// Records an issue at the start of the given span, or at the start of the
+    /// file if there is none.
+    fn report(&mut self, span: Option<Range<usize>>, message: String) {
+        self.issues
+            .push((span.map_or(0, |span| span.start), message));
+    }
+
+    /// Checks the whole file contents in the context of the given merged
+    /// configuration.
+    fn check(&mut self, config_str: &str, merged: &toml::Table) {
+        let doc = match Document::parse(config_str) {
+            Ok(doc) => doc,
+            Err(err) => {
+                self.report(
+                    err.span(),
+                    format!("Invalid TOML: {}", err.message().trim().replace('\n', ": ")),
+                );
+                return;
+            }
+        };
+        let Ok(config_table) = config_str.parse::<toml::Table>() else {
+            self.report(None, "Invalid TOML.".to_owned());
+            return;
+        };
+
+        self.check_unknown_keys(doc.as_table(), "", struct_fields::<UserConfig>());
+        if let Some(defaults) = doc.get("defaults").and_then(Item::as_table_like) {
+            self.check_unknown_keys(defaults, "defaults", struct_fields::<DefaultsSection>());
+            for (section, fields) in [
+                ("ship", struct_fields::<ShipArgs>()),
+                ("jettison", struct_fields::<JettisonArgs>()),
+                ("package", struct_fields::<PackageDefaults>()),
+            ] {
+                if let Some(table) = defaults.get(section).and_then(Item::as_table_like) {
+                    self.check_unknown_keys(table, &format!("defaults.{section}"), fields);
+                }
+            }
+        }
+
+        // Invalid templates are reported by the complete deserialization.
+        let templates = merged
+            .get("templates")
+            .and_then(toml::Value::as_table)
+            .map(|tpls| {
+                tpls.iter()
+                    .map(|(tpl_name, tpl)| {
+                        (
+                            tpl_name.clone(),
+                            tpl.clone()
+                                .try_into::<PackageTemplate>()
+                                .unwrap_or_default(),
+                        )
+                    })
+                    .collect::<BTreeMap<_, _>>()
+            })
+            .unwrap_or_default();
+        if let Some(tpls) = doc.get("templates").and_then(Item::as_table_like) {
+            self.check_templates(tpls, &templates);
+        }
+
+        let defaults = merged
+            .get("defaults")
+            .and_then(|defaults| defaults.clone().try_into::<DefaultsSection>().ok())
+            .unwrap_or_default();
+        let mut invalid_pkgs = false;
+        if let (Some(pkgs), Some(pkg_values)) = (
+            doc.get("packages").and_then(Item::as_table_like),
+            config_table.get("packages").and_then(toml::Value::as_table),
+        ) {
+            invalid_pkgs |=
+                !self.check_packages("packages", pkgs, pkg_values, &templates, &defaults);
+        }
+        for section in ["target", "hosts"] {
+            if let Some(overlays) = doc.get(section).and_then(Item::as_table_like) {
+                invalid_pkgs |= !self.check_overlays(
+                    section,
+                    overlays,
+                    config_table.get(section).and_then(toml::Value::as_table),
+                    &templates,
+                    &defaults,
+                );
+            }
+        }
+
+        // Report the remaining deserialization errors, without repeating the
+        // ones already found in the packages. The packages may be defined by
+        // another layer only: append an empty table to keep the locations.
+        let config_str = if config_table.contains_key("packages") {
+            config_str.to_owned()
+        } else {
+            format!("{config_str}\n[packages]\n")
+        };
+        if !invalid_pkgs && let Err(err) = toml::from_str::<UserConfig>(&config_str) {
+            self.report(
+                err.span(),
+                format!("Invalid configuration: {}", err.message()),
+            );
+        }
+    }
+
+    /// Reports the keys of the given table that are not known fields.
+    fn check_unknown_keys(&mut self, table: &dyn TableLike, path: &str, known: &[&str]) {
+        for (key, _) in table.iter() {
+            if !known.contains(&key) {
+                self.report(
+                    table.key(key).and_then(Key::span),
+                    if path.is_empty() {
+                        format!("Unknown key `{key}`.")
+                    } else {
+                        format!("Unknown key `{key}` in `{path}`.")
+                    },
+                );
+            }
+        }
+    }
+
+    /// Checks the overlays of the given section, reporting their unknown keys
+    /// and, for the `target` section, the keys that cannot be evaluated, then
+    /// checks their packages. Returns whether all of them could be
+    /// deserialized.
+    fn check_overlays(
+        &mut self,
+        section: &str,
+        overlays: &dyn TableLike,
+        overlay_values: Option<&toml::Table>,
+        templates: &BTreeMap<String, PackageTemplate>,
+        defaults: &DefaultsSection,
+    ) -> bool {
+        let mut valid = true;
+        for (key, overlay) in overlays.iter() {
+            if section == "target"
+                && let Err(err) = key.parse::<CfgExpr>()
+            {
+                self.report(
+                    overlays.key(key).and_then(Key::span),
+                    format!("Invalid `target` key `{key}`: {err}"),
+                );
+            }
+            if let Some(overlay) = overlay.as_table_like() {
+                self.check_unknown_keys(
+                    overlay,
+                    &format!("{section}.{key}"),
+                    struct_fields::<PackageOverlay>(),
+                );
+
+                if let (Some(pkgs), Some(pkg_values)) = (
+                    overlay.get("packages").and_then(Item::as_table_like),
+                    overlay_values
+                        .and_then(|overlay_values| overlay_values.get(key))
+                        .and_then(|overlay_value| overlay_value.get("packages"))
+                        .and_then(toml::Value::as_table),
+                ) {
+                    valid &= self.check_packages(
+                        &format!("{section}.{key}.packages"),
+                        pkgs,
+                        pkg_values,
+                        templates,
+                        defaults,
+                    );
+                }
+            }
+        }
+        valid
+    }
+
+    /// Checks the templates of the given table, reporting their unknown keys
+    /// and the inheritances that cannot be resolved.
+    fn check_templates(
+        &mut self,
+        tpls: &dyn TableLike,
+        templates: &BTreeMap<String, PackageTemplate>,
+    ) {
+        for (tpl_name, tpl_item) in tpls.iter() {
+            let tpl_table = tpl_item.as_table_like();
+            if let Some(tpl_table) = tpl_table {
+                self.check_unknown_keys(
+                    tpl_table,
+                    &format!("templates.{tpl_name}"),
+                    struct_fields::<PackageTemplate>(),
+                );
+            }
+
+            if templates.contains_key(tpl_name)
+                && let Err(err) =
+                    template::resolve(templates, &TemplateNames::One(tpl_name.to_owned()))
+            {
+                self.report(
+                    tpl_table
+                        .and_then(|tpl_table| tpl_table.key("template"))
+                        .and_then(Key::span)
+                        .or_else(|| tpls.key(tpl_name).and_then(Key::span)),
+                    format!("Invalid inheritance of template `{tpl_name}`: {err}"),
+                );
+            }
+        }
+    }
+
+    /// Checks the requirements of the given table of packages, found at the
+    /// given key path, and returns whether all of them could be deserialized.
+    fn check_packages(
+        &mut self,
+        pkgs_path: &str,
+        pkgs: &dyn TableLike,
+        pkg_values: &toml::Table,
+        templates: &BTreeMap<String, PackageTemplate>,
+        defaults: &DefaultsSection,
+    ) -> bool {
+        let mut valid = true;
+        for (pkg_name, pkg_item) in pkgs.iter() {
+            if let Some(pkg_value) = pkg_values.get(pkg_name) {
+                valid &= self.check_package(
+                    pkgs_path,
+                    pkg_name,
+                    pkgs.key(pkg_name),
+                    pkg_item,
+                    pkg_value,
+                    templates,
+                    defaults,
+                );
+            }
+        }
+        valid
+    }
+
+    /// Checks the requirement of a single package, found in the table at the
+    /// given key path, and returns whether it could be deserialized.
+    #[expect(
+        clippy::too_many_arguments,
+        reason = "The package is needed both as located and as deserialized."
+    )]
+    fn check_package(
+        &mut self,
+        pkgs_path: &str,
+        pkg_name: &str,
+        pkg_key: Option<&Key>,
+        pkg_item: &Item,
+        pkg_value: &toml::Value,
+        templates: &BTreeMap<String, PackageTemplate>,
+        defaults: &DefaultsSection,
+    ) -> bool {
+        let pkg_span = pkg_key.and_then(Key::span).or_else(|| pkg_item.span());
+        let pkg_table = pkg_item.as_table_like();
+
+        let pkg_req = if let Some(ver_str) = pkg_value.as_str() {
+            match VersionReq::parse(ver_str) {
+                Ok(version) => DetailedPackageReq {
+                    version,
+                    ..Default::default()
+                },
+                Err(err) => {
+                    self.report(
+                        pkg_item.span(),
+                        format!("Invalid version requirement for package `{pkg_name}`: {err}."),
+                    );
+                    return false;
+                }
+            }
+        } else if let Some(pkg_table) = pkg_table {
+            self.check_unknown_keys(
+                pkg_table,
+                &format!("{pkgs_path}.{pkg_name}"),
+                struct_fields::<DetailedPackageReq>(),
+            );
+
+            match pkg_value.clone().try_into::<DetailedPackageReq>() {
+                Ok(pkg_req) => pkg_req,
+                Err(err) => {
+                    let version = pkg_table.get("version");
+                    if let Some(Err(ver_err)) =
+                        version.and_then(Item::as_str).map(VersionReq::parse)
+                    {
+                        self.report(
+                            version.and_then(Item::span),
+                            format!(
+                                "Invalid version requirement for package `{pkg_name}`: {ver_err}."
+                            ),
+                        );
+                    } else {
+                        self.report(
+                            pkg_span,
+                            format!(
+                                "Invalid requirement for package `{pkg_name}`: {}.",
+                                err.message().trim_end_matches('.'),
+                            ),
+                        );
+                    }
+                    return false;
+                }
+            }
+        } else {
+            self.report(
+                pkg_span,
+                format!(
+                    "Invalid requirement for package `{pkg_name}`: expected a version \
+                     requirement string or a table."
+                ),
+            );
+            return false;
+        };
+
+        // Options coming from templates or defaults are located at the package.
+        let key_span = |key: &str| {
+            pkg_table
+                .and_then(|pkg_table| pkg_table.key(key))
+                .and_then(Key::span)
+                .or(pkg_span.clone())
+        };
+        let Some(pkg_req) =
+            self.apply_templates(pkg_name, pkg_req, key_span("template").as_ref(), templates)
+        else {
+            return true;
+        };
+        let pkg_req = pkg_req.with_defaults(&defaults.package);
+
+        for (opt1, opt2) in pkg_req.conflicting_options() {
+            self.report(
+                key_span(opt2),
+                format!("`{opt2}` conflicts with `{opt1}` in package `{pkg_name}`."),
+            );
+        }
+        for opt in pkg_req.git_refs_without_git() {
+            self.report(
+                key_span(opt),
+                format!("`{opt}` requires `git` to be set as well in package `{pkg_name}`."),
+            );
+        }
+        if pkg_req.binstall.or(defaults.ship_cmd.binstall) == Some(BinstallChoice::Always) {
+            let unsupported = cargo::binstall_unsupported_options(&pkg_req);
+            if !unsupported.is_empty() {
+                self.report(
+                    key_span("binstall"),
+                    format!(
+                        "`binstall = \"always\"` applies to package `{pkg_name}`, but \
+                         `cargo-binstall` does not support its {} option(s).",
+                        unsupported
+                            .iter()
+                            .map(|opt| format!("`{opt}`"))
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    ),
+                );
+            }
+        }
+
+        true
+    }
+
+    /// Applies the templates the given package inherits from, reporting the
+    /// unknown ones at the given span, or returns `None` if they cannot be.
+    fn apply_templates(
+        &mut self,
+        pkg_name: &str,
+        pkg_req: DetailedPackageReq,
+        tpl_span: Option<&Range<usize>>,
+        templates: &BTreeMap<String, PackageTemplate>,
+    ) -> Option<DetailedPackageReq> {
+        let mut unknown_tpls = false;
+        for tpl_name in pkg_req.template.as_slice() {
+            if !templates.contains_key(tpl_name) {
+                unknown_tpls = true;
+                self.report(
+                    tpl_span.cloned(),
+                    format!("Unknown template `{tpl_name}` in package `{pkg_name}`."),
+                );
+            }
+        }
+        // Other inheritance issues are reported in the templates themselves.
+        (!unknown_tpls)
+            .then(|| pkg_req.with_templates(templates).ok())
+            .flatten()
+    }
+}
+
+/// Returns the names of the fields of the given structure as known by its
+/// derived [`Deserialize`] implementation, i.e. with renames applied.
+fn struct_fields<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    /// Deserializer only recording the fields it is asked for.
+    struct FieldsDeserializer<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> Deserializer<'de> for FieldsDeserializer<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("only structures are supported"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(de::Error::custom("fields recorded"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    // The error is expected: only the recorded fields matter.
+    let _ = T::deserialize(FieldsDeserializer(&mut fields));
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    /// Returns the issues found in the given contents as strings.
+    fn issues(config_str: &str) -> Vec<String> {
+        let layer = Layer {
+            path: PathBuf::from("liner.toml"),
+            contents: config_str.to_owned(),
+        };
+        find_issues(&layer, &layers::merge([&layer]).unwrap_or_default())
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_structfields() {
+        assert_eq!(
+            struct_fields::<UserConfig>(),
+            [
+                "version",
+                "strict",
+                "include",
+                "exclude",
+                "packages",
+                "templates",
+                "groups",
+                "target",
+                "hosts",
+                "defaults"
+            ]
+        );
+        assert_eq!(
+            struct_fields::<DefaultsSection>(),
+            ["ship", "jettison", "package"]
+        );
+        assert!(struct_fields::<DetailedPackageReq>().contains(&"default-features"));
+        assert!(struct_fields::<ShipArgs>().contains(&"no-self"));
+        assert!(!struct_fields::<ShipArgs>().contains(&"packages"));
+    }
+
+    #[test]
+    fn test_findissues_valid_isempty() {
+        assert_eq!(
+            issues(indoc!(
+                r#"
+                    strict = true
+                    [packages]
+                    abc = "1.2"
+                    def = { version = "*", git = "https://example.com/def", tag = "v1" }
+                    [packages.ghi]
+                    version = "*"
+                    all-features = true
+                    environment = { ANY = "thing" }
+                    [defaults.ship]
+                    binstall = "never"
+                    [defaults.jettison]
+                    no-confirm = true
+                "#
+            )),
+            Vec::<String>::new(),
+        );
+    }
+
+    #[test]
+    fn test_findissues_unknownkeys() {
+        assert_eq!(
+            issues(indoc!(
+                r#"
+                    strikt = true
+                    [packages]
+                    abc = { version = "1", lockde = true }
+                    [packages.def]
+                    version = "1"
+                    fetures = ["a"]
+                    [defaults]
+                    shp = {}
+                    [defaults.jettison]
+                    no-confirms = true
+                "#
+            )),
+            [
+                "liner.toml:1:1: Unknown key `strikt`.",
+                "liner.toml:3:24: Unknown key `lockde` in `packages.abc`.",
+                "liner.toml:6:1: Unknown key `fetures` in `packages.def`.",
+                "liner.toml:8:1: Unknown key `shp` in `defaults`.",
+                "liner.toml:10:1: Unknown key `no-confirms` in `defaults.jettison`.",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_findissues_conflicts() {
+        assert_eq!(
+            issues(indoc!(
+                r#"
+                    [packages]
+                    abc = { version = "1", git = "g", path = "p", tag = "t", rev = "r" }
+                    def = { version = "1", branch = "b", all-bins = true, bins = ["x"] }
+                "#
+            )),
+            [
+                "liner.toml:2:35: `path` conflicts with `git` in package `abc`.",
+                "liner.toml:2:58: `rev` conflicts with `tag` in package `abc`.",
+                "liner.toml:3:24: `branch` requires `git` to be set as well in package `def`.",
+                "liner.toml:3:55: `bins` conflicts with `all-bins` in package `def`.",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_findissues_binstallalways() {
+        assert_eq!(
+            issues(indoc!(
+                r#"
+                    [packages]
+                    abc = { version = "1", frozen = true }
+                    def = { version = "1", frozen = true, binstall = "auto" }
+                    ghi = { version = "1", offline = true, binstall = "always" }
+                    [defaults.ship]
+                    binstall = "always"
+                "#
+            )),
+            [
+                "liner.toml:2:1: `binstall = \"always\"` applies to package `abc`, but \
+                 `cargo-binstall` does not support its `frozen` option(s).",
+                "liner.toml:4:40: `binstall = \"always\"` applies to package `ghi`, but \
+                 `cargo-binstall` does not support its `offline` option(s).",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_findissues_packagedefaults() {
+        assert_eq!(
+            issues(indoc!(
+                r#"
+                    [packages]
+                    abc = "1"
+                    def = { version = "1", binstall = "never" }
+                    [defaults.package]
+                    frozen = true
+                    binstall = "always"
+                    lockd = true
+                "#
+            )),
+            [
+                "liner.toml:2:1: `binstall = \"always\"` applies to package `abc`, but \
+                 `cargo-binstall` does not support its `frozen` option(s).",
+                "liner.toml:7:1: Unknown key `lockd` in `defaults.package`.",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_findissues_templates() {
+        assert_eq!(
+            issues(indoc!(
+                r#"
+                    [packages]
+                    abc = { version = "1", template = "base" }
+                    def = { version = "1", template = ["base", "nope"] }
+                    ghi = { version = "1", template = "loop1" }
+                    jkl = { version = "1", template = "git", tag = "v1" }
+                    [templates.base]
+                    frozen = true
+                    binstall = "always"
+                    lockd = true
+                    [templates.git]
+                    git = "https://example.com/git"
+                    path = "/a/b/c"
+                    [templates.loop1]
+                    template = "loop2"
+                    [templates.loop2]
+                    template = ["base", "loop1"]
+                "#
+            )),
+            [
+                "liner.toml:2:1: `binstall = \"always\"` applies to package `abc`, but \
+                 `cargo-binstall` does not support its `frozen` option(s).",
+                "liner.toml:3:24: Unknown template `nope` in package `def`.",
+                "liner.toml:5:1: `path` conflicts with `git` in package `jkl`.",
+                "liner.toml:9:1: Unknown key `lockd` in `templates.base`.",
+                "liner.toml:14:1: Invalid inheritance of template `loop1`: Template `loop1` \
+                 inherits from itself: `loop1` -> `loop2` -> `loop1`.",
+                "liner.toml:16:1: Invalid inheritance of template `loop2`: Template `loop2` \
+                 inherits from itself: `loop2` -> `loop1` -> `loop2`.",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_findissues_overlays() {
+        assert_eq!(
+            issues(indoc!(
+                r#"
+                    [packages]
+                    abc = "1"
+                    [target.'cfg(unix)'.packages]
+                    def = "2"
+                    [target.'cfg(unix'.packages]
+                    ghi = "3"
+                    [target.x86_64-unknown-linux-gnu]
+                    pakages = { jkl = "4" }
+                    [hosts.workstation]
+                    packages = { mno = "5" }
+                    template = "base"
+                "#
+            )),
+            [
+                "liner.toml:5:9: Invalid `target` key `cfg(unix`: Expected `)`, found the end.",
+                "liner.toml:7:9: Invalid `target` key `x86_64-unknown-linux-gnu`: Only \
+                 `cfg(...)` expressions are supported, got `x86_64-unknown-linux-gnu`.",
+                "liner.toml:8:1: Unknown key `pakages` in `target.x86_64-unknown-linux-gnu`.",
+                "liner.toml:11:1: Unknown key `template` in `hosts.workstation`.",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_findissues_overlaypackages() {
+        assert_eq!(
+            issues(indoc!(
+                r#"
+                    [templates.base]
+                    tag = "v1"
+                    [target.'cfg(unix)'.packages]
+                    abc = { version = "1", lockde = true }
+                    def = { version = "1", all-features = true, features = ["a"] }
+                    ghi = "1.x.2"
+                    [hosts.workstation.packages]
+                    jkl = { version = "1", fetures = ["a"] }
+                    mno = { version = "1", template = ["base", "nope"] }
+                    pqr = { version = "1", template = "base" }
+                "#
+            )),
+            [
+                "liner.toml:4:24: Unknown key `lockde` in `target.cfg(unix).packages.abc`.",
+                "liner.toml:5:45: `features` conflicts with `all-features` in package `def`.",
+                "liner.toml:6:7: Invalid version requirement for package `ghi`: unexpected \
+                 character after wildcard in version req.",
+                "liner.toml:8:24: Unknown key `fetures` in `hosts.workstation.packages.jkl`.",
+                "liner.toml:9:24: Unknown template `nope` in package `mno`.",
+                "liner.toml:10:1: `tag` requires `git` to be set as well in package `pqr`.",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_findissues_invalidrequirements() {
+        assert_eq!(
+            issues(indoc!(
+                r#"
+                    [packages]
+                    abc = "1.x.2"
+                    def = { version = "=>1" }
+                    ghi = 123
+                    jkl = { version = "1", locked = "yes" }
+                "#
+            )),
+            [
+                "liner.toml:2:7: Invalid version requirement for package `abc`: unexpected \
+                 character after wildcard in version req.",
+                "liner.toml:3:19: Invalid version requirement for package `def`: unexpected \
+                 character '>' while parsing major version number.",
+                "liner.toml:4:1: Invalid requirement for package `ghi`: expected a version \
+                 requirement string or a table.",
+                "liner.toml:5:1: Invalid requirement for package `jkl`: invalid type: string \
+                 \"yes\", expected a boolean.",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_findissues_invalidconfig() {
+        assert_eq!(
+            issues("strict = 1\n[packages]\n"),
+            [
+                "liner.toml:1:10: Invalid configuration: invalid type: integer `1`, expected a boolean"
+            ],
+        );
+    }
+
+    #[test]
+    fn test_validate_packagesinanylayer() {
+        let layer = |contents: &str| Layer {
+            path: PathBuf::from("liner.toml"),
+            contents: contents.to_owned(),
+        };
+        assert!(issues("[defaults.ship]\nno-self = true\n").is_empty());
+        assert!(validate(&[layer("[defaults.ship]\nno-self = true\n")]).is_err());
+        assert!(
+            validate(&[
+                layer("[templates.a]\n"),
+                layer("[packages.abc]\nversion = '1'\ntemplate = 'a'\n")
+            ])
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_findissues_invalidtoml() {
+        assert_eq!(
+            issues("[packages]\nabc = '*\n"),
+            ["liner.toml:2:9: Invalid TOML: invalid literal string, expected `'`"],
+        );
+    }
+}
@@ -0,0 +1,330 @@
+//! Resolution of the commits that Git-sourced packages would be installed from,
+//! using `git ls-remote` in order to avoid cloning anything.
+
+use std::collections::BTreeMap;
+use std::process::{Child, Command, Stdio};
+
+use color_eyre::Section;
+use color_eyre::eyre::{Result, WrapErr, eyre};
+
+use crate::config::DetailedPackageReq;
+use crate::{cargo, parallel};
+
+/// Number of hexadecimal digits displayed for commits, as Cargo does.
+const SHORT_COMMIT_LEN: usize = 8;
+
+/// Reference of a Git repository that a package is installed from, following
+/// the precedence of `cargo install`'s options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GitReference<'p> {
+    /// `--branch`: a branch name.
+    Branch(&'p str),
+    /// `--tag`: a tag name.
+    Tag(&'p str),
+    /// `--rev`: either a commit hash or any other reference.
+    Rev(&'p str),
+    /// None of the above: the remote's default branch.
+    DefaultBranch,
+}
+
+impl<'p> From<&'p DetailedPackageReq> for GitReference<'p> {
+    fn from(pkg: &'p DetailedPackageReq) -> Self {
+        if let Some(branch) = &pkg.branch {
+            Self::Branch(branch)
+        } else if let Some(tag) = &pkg.tag {
+            Self::Tag(tag)
+        } else if let Some(rev) = &pkg.rev {
+            Self::Rev(rev)
+        } else {
+            Self::DefaultBranch
+        }
+    }
+}
+
+impl GitReference<'_> {
+    /// Returns the commit directly designated by the reference, if any, i.e.
+    /// when it does not need any resolution.
+    fn as_commit(&self) -> Option<&str> {
+        match self {
+            Self::Rev(rev) if looks_like_commit_hash(rev) => Some(rev),
+            _ => None,
+        }
+    }
+
+    /// Returns the pattern to give to `git ls-remote` for the reference.
+    fn pattern(&self) -> String {
+        match self {
+            Self::Branch(branch) => format!("refs/heads/{branch}"),
+            Self::Tag(tag) => format!("refs/tags/{tag}"),
+            Self::Rev(rev) => (*rev).to_owned(),
+            Self::DefaultBranch => "HEAD".to_owned(),
+        }
+    }
+
+    /// Extracts the commit of the reference from the output of `git ls-remote`.
+    fn find_commit<'o>(&self, ls_remote_out: &'o str) -> Option<&'o str> {
+        let mut refs = ls_remote_out
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(commit, name)| (name.trim(), commit.trim()));
+
+        match self {
+            // Annotated tags must be peeled in order to get their commit.
+            Self::Tag(_) => {
+                let (tag, peeled) = (self.pattern(), format!("{}^{{}}", self.pattern()));
+                refs.clone()
+                    .find(|(name, _)| *name == peeled)
+                    .or_else(|| refs.find(|(name, _)| *name == tag))
+            }
+            Self::Rev(rev) => refs.find(|(name, _)| {
+                *name == *rev || name.strip_suffix(rev).is_some_and(|pre| pre.ends_with('/'))
+            }),
+            Self::Branch(_) | Self::DefaultBranch => {
+                let pattern = self.pattern();
+                refs.find(|(name, _)| *name == pattern)
+            }
+        }
+        .map(|(_, commit)| commit)
+    }
+}
+
+/// Returns whether the given string could be a commit hash, as Cargo does.
+fn looks_like_commit_hash(rev: &str) -> bool {
+    rev.len() >= 7 && rev.chars().all(|chr| chr.is_ascii_hexdigit())
+}
+
+/// Returns whether the given installed commit corresponds to the given
+/// resolved one, the latter being possibly abbreviated if configured so.
+pub fn commit_matches(installed: &str, resolved: &str) -> bool {
+    installed
+        .to_ascii_lowercase()
+        .starts_with(&resolved.to_ascii_lowercase())
+}
+
+/// Returns the abbreviated form of the given commit hash.
+pub fn short_commit(commit: &str) -> &str {
+    commit.get(..SHORT_COMMIT_LEN).unwrap_or(commit)
+}
+
+/// Spawns `git ls-remote` for the given repository and reference with its
+/// outputs piped and returns the corresponding child process handle to be used
+/// with [`finish_ls_remote`].
+fn spawn_ls_remote(repo: &str, git_ref: &GitReference<'_>) -> Result<Child> {
+    let mut cmd = Command::new("git");
+    cmd.stdin(Stdio::null());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    // Fail instead of blocking on credentials that cannot be entered.
+    cmd.env("GIT_TERMINAL_PROMPT", "0");
+    cmd.args(["ls-remote", "--", repo, &git_ref.pattern()]);
+
+    cargo::log_cmd(&cmd);
+    cmd.spawn()
+        .wrap_err("Failed to spawn Git.")
+        .note("This can happen for many reasons, but mostly if Git is not installed.")
+        .suggestion("Check that `git` is available in the PATH.")
+}
+
+/// Waits for the given child process as spawned by [`spawn_ls_remote`] to
+/// finish and extracts the commit of the given reference from the output.
+fn finish_ls_remote(pkg: &str, git_ref: &GitReference<'_>, proc: Child) -> Result<String> {
+    let out = proc
+        .wait_with_output()
+        .wrap_err_with(|| format!("Failed to wait for the Git child process for {pkg:?}."))
+        .note("This can happen for many reasons, but it should not happen easily at this point.")
+        .suggestion("Read the underlying error message.")?;
+
+    if !out.status.success() {
+        return Err(eyre!(
+            "Listing the remote references for {pkg:?} failed on {:?} with stderr: {:?}",
+            out.status.code(),
+            String::from_utf8_lossy(&out.stderr),
+        ))
+        .suggestion("Check the repository URL and your network connection.");
+    }
+
+    let stdout = String::from_utf8(out.stdout)
+        .wrap_err("Failed to decode the standard output.")
+        .note("This really should not happen.")
+        .suggestion(crate::OPEN_ISSUE_MSG)?;
+    log::trace!("Listing for {pkg:?} got: {stdout:?}");
+
+    let commit = git_ref
+        .find_commit(&stdout)
+        .ok_or_else(|| eyre!("Reference {:?} not found for {pkg:?}.", git_ref.pattern()))
+        .suggestion("Check that the configured branch, tag or revision does exist.")?;
+    log::trace!("Resolved commit is: {commit:?}.");
+    Ok(commit.to_owned())
+}
+
+/// Resolves the commit that each of the given Git-sourced packages would
+/// currently be installed from.
+///
+/// Commit hashes given through `rev` are returned as-is, possibly abbreviated,
+/// while other references are listed from their remote in parallel, with at
+/// most `jobs` of them at once. When `no_fail_fast` is set, the packages that
+/// fail to be resolved are warned about and left out instead of failing
+/// entirely.
+pub fn resolve_all(
+    pkgs: &BTreeMap<String, DetailedPackageReq>,
+    jobs: usize,
+    no_fail_fast: bool,
+) -> Result<BTreeMap<String, String>> {
+    let mut commits = BTreeMap::new();
+    let mut to_list = Vec::new();
+
+    if pkgs.is_empty() {
+        return Ok(commits);
+    }
+
+    log::info!("Fetching latest Git commits...");
+    for (pkg_name, pkg) in pkgs {
+        let repo = pkg
+            .git
+            .as_deref()
+            .ok_or_else(|| eyre!("No Git repository configured for {pkg_name:?}."))
+            .suggestion(crate::OPEN_ISSUE_MSG)?;
+        let git_ref = GitReference::from(pkg);
+
+        if let Some(commit) = git_ref.as_commit() {
+            log::debug!("Using the configured commit for {pkg_name:?}.");
+            commits.insert(pkg_name.clone(), commit.to_owned());
+        } else {
+            to_list.push((pkg_name, repo, git_ref));
+        }
+    }
+
+    for (pkg_name, res) in parallel::map(to_list, jobs, |(pkg_name, repo, git_ref)| {
+        (
+            pkg_name,
+            spawn_ls_remote(repo, &git_ref)
+                .and_then(|proc| finish_ls_remote(pkg_name, &git_ref, proc)),
+        )
+    }) {
+        match res
+            .wrap_err_with(|| format!("Failed to resolve the latest Git commit of {pkg_name:?}."))
+        {
+            Ok(commit) => {
+                commits.insert(pkg_name.clone(), commit);
+            }
+            Err(err) if no_fail_fast => {
+                // Only the cause is relevant after the package name.
+                log::warn!(
+                    "Ignoring the latest commit of {pkg_name:?}: {}",
+                    err.root_cause()
+                );
+            }
+            Err(err) => {
+                return Err(err.suggestion(
+                    "Use `ship --no-fail-fast` to ignore this and continue on with other packages.",
+                ));
+            }
+        }
+    }
+
+    Ok(commits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LS_REMOTE_OUT: &str = "\
+        1111111111111111111111111111111111111111\tHEAD\n\
+        2222222222222222222222222222222222222222\trefs/heads/main\n\
+        3333333333333333333333333333333333333333\trefs/heads/feat/main\n\
+        4444444444444444444444444444444444444444\trefs/tags/v1\n\
+        5555555555555555555555555555555555555555\trefs/tags/v1^{}\n\
+        6666666666666666666666666666666666666666\trefs/tags/v2\n\
+        7777777777777777777777777777777777777777\trefs/pull/1/head\n";
+
+    #[test]
+    fn test_gitreference_frompkg() {
+        assert_eq!(
+            GitReference::from(&DetailedPackageReq::default()),
+            GitReference::DefaultBranch,
+        );
+        assert_eq!(
+            GitReference::from(&DetailedPackageReq {
+                branch: Some("main".to_owned()),
+                ..Default::default()
+            }),
+            GitReference::Branch("main"),
+        );
+        assert_eq!(
+            GitReference::from(&DetailedPackageReq {
+                tag: Some("v1".to_owned()),
+                ..Default::default()
+            }),
+            GitReference::Tag("v1"),
+        );
+        assert_eq!(
+            GitReference::from(&DetailedPackageReq {
+                rev: Some("abcdef0".to_owned()),
+                ..Default::default()
+            }),
+            GitReference::Rev("abcdef0"),
+        );
+    }
+
+    #[test]
+    fn test_gitreference_ascommit() {
+        assert_eq!(GitReference::Rev("abcdef0").as_commit(), Some("abcdef0"));
+        assert_eq!(GitReference::Rev("abcdef").as_commit(), None);
+        assert_eq!(GitReference::Rev("refs/pull/1/head").as_commit(), None);
+        assert_eq!(GitReference::Branch("abcdef0").as_commit(), None);
+        assert_eq!(GitReference::DefaultBranch.as_commit(), None);
+    }
+
+    #[test]
+    fn test_gitreference_findcommit() {
+        assert_eq!(
+            GitReference::DefaultBranch.find_commit(LS_REMOTE_OUT),
+            Some("1111111111111111111111111111111111111111"),
+        );
+        assert_eq!(
+            GitReference::Branch("main").find_commit(LS_REMOTE_OUT),
+            Some("2222222222222222222222222222222222222222"),
+        );
+        assert_eq!(
+            GitReference::Tag("v1").find_commit(LS_REMOTE_OUT),
+            Some("5555555555555555555555555555555555555555"),
+        );
+        assert_eq!(
+            GitReference::Tag("v2").find_commit(LS_REMOTE_OUT),
+            Some("6666666666666666666666666666666666666666"),
+        );
+        assert_eq!(
+            GitReference::Rev("refs/pull/1/head").find_commit(LS_REMOTE_OUT),
+            Some("7777777777777777777777777777777777777777"),
+        );
+        assert_eq!(
+            GitReference::Rev("main").find_commit(LS_REMOTE_OUT),
+            Some("2222222222222222222222222222222222222222"),
+        );
+        assert_eq!(
+            GitReference::Branch("none").find_commit(LS_REMOTE_OUT),
+            None
+        );
+        assert_eq!(GitReference::Tag("v3").find_commit(""), None);
+    }
+
+    #[test]
+    fn test_commitmatches() {
+        let commit = "0123456789abcdef0123456789abcdef01234567";
+        assert!(commit_matches(commit, commit));
+        assert!(commit_matches(commit, "0123456"));
+        assert!(commit_matches(commit, "0123456789ABCDEF"));
+        assert!(!commit_matches(commit, "1234567"));
+        assert!(!commit_matches("0123456", commit));
+    }
+
+    #[test]
+    fn test_shortcommit() {
+        assert_eq!(
+            short_commit("0123456789abcdef0123456789abcdef01234567"),
+            "01234567",
+        );
+        assert_eq!(short_commit("0123456"), "0123456");
+    }
+}
@@ -0,0 +1,299 @@
+//! Version checking of packages installed from a local path, done by reading
+//! their manifest instead of rebuilding them.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::Section;
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use semver::Version;
+use serde::Deserialize;
+
+use crate::config::DetailedPackageReq;
+
+/// Name of the manifest file of a package.
+const MANIFEST_FILE_NAME: &str = "Cargo.toml";
+
+/// Minimal representation of a `Cargo.toml` manifest.
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    package: Option<ManifestPackage>,
+    workspace: Option<ManifestWorkspace>,
+}
+
+/// Minimal representation of a manifest's `[package]` table.
+#[derive(Debug, Deserialize)]
+struct ManifestPackage {
+    name: String,
+    version: Option<MaybeInherited>,
+}
+
+/// A manifest field that may be inherited from the workspace.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum MaybeInherited {
+    Value(Version),
+    Inherited { workspace: bool },
+}
+
+/// Minimal representation of a manifest's `[workspace]` table.
+#[derive(Debug, Deserialize)]
+struct ManifestWorkspace {
+    package: Option<WorkspacePackage>,
+}
+
+/// Minimal representation of a manifest's `[workspace.package]` table.
+#[derive(Debug, Deserialize)]
+struct WorkspacePackage {
+    version: Option<Version>,
+}
+
+/// A package found locally at some path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalPackage {
+    /// Canonical path of the package's directory.
+    pub path: PathBuf,
+    /// Version declared by the package's manifest.
+    pub version: Version,
+}
+
+/// Reads and deserializes the manifest located in the given directory.
+fn read_manifest(dir: &Path) -> Result<Manifest> {
+    let path = dir.join(MANIFEST_FILE_NAME);
+    log::debug!("Reading manifest at {path:#?}...");
+    let manifest_str = fs::read_to_string(&path)
+        .wrap_err_with(|| format!("Failed to read the manifest at {path:#?}."))
+        .suggestion(
+            "Check that the configured path points to a directory containing a Cargo.toml file.",
+        )?;
+    toml::from_str(&manifest_str)
+        .wrap_err_with(|| format!("Failed to deserialize the manifest at {path:#?}."))
+        .suggestion("Check that the manifest is valid, for example by building the package.")
+}
+
+/// Returns the version inherited from the closest workspace root above the
+/// given package directory.
+fn workspace_version(pkg_dir: &Path) -> Result<Version> {
+    for dir in pkg_dir.ancestors().skip(1) {
+        if !dir.join(MANIFEST_FILE_NAME).is_file() {
+            continue;
+        }
+
+        if let Some(ws) = read_manifest(dir)?.workspace {
+            return ws
+                .package
+                .and_then(|ws_pkg| ws_pkg.version)
+                .ok_or_else(|| eyre!("No `workspace.package.version` found in {dir:#?}."))
+                .suggestion("Check that the workspace does define the inherited version.");
+        }
+    }
+
+    Err(eyre!("No workspace root found above {pkg_dir:#?}."))
+        .suggestion("Check that the package is indeed a member of a workspace.")
+}
+
+/// Reads the package located in the given directory.
+///
+/// The version is resolved the same way Cargo does: inherited from the
+/// workspace if requested so, and defaulting to `0.0.0` when unspecified.
+fn read_package(pkg_name: &str, dir: &Path) -> Result<LocalPackage> {
+    let path = dir
+        .canonicalize()
+        .wrap_err_with(|| format!("Failed to resolve the path {dir:#?}."))
+        .suggestion("Check that the configured path exists.")?;
+    let pkg = read_manifest(&path)?
+        .package
+        .ok_or_else(|| eyre!("No `[package]` found in the manifest of {path:#?}."))
+        .note("Virtual manifests of workspaces are not supported.")
+        .suggestion("Point the path to the package's directory instead.")?;
+
+    if pkg.name != pkg_name {
+        return Err(eyre!(
+            "The package at {path:#?} is named {:?} instead of {pkg_name:?}.",
+            pkg.name,
+        ))
+        .suggestion("Check the configured path or the package's name.");
+    }
+
+    let version = match pkg.version {
+        Some(MaybeInherited::Value(ver)) => ver,
+        Some(MaybeInherited::Inherited { workspace: true }) => workspace_version(&path)?,
+        Some(MaybeInherited::Inherited { workspace: false }) => {
+            return Err(eyre!("Invalid `package.version` found in {path:#?}.")).suggestion(
+                "Check that the manifest is valid, for example by building the package.",
+            );
+        }
+        None => Version::new(0, 0, 0),
+    };
+
+    log::trace!("Got version {version} for {pkg_name:?}.");
+    Ok(LocalPackage { path, version })
+}
+
+/// Reads the local packages pointed to by the paths of the given packages.
+///
+/// When `no_fail_fast` is `true`, the packages that cannot be read are only
+/// warned about and left out of the result instead of failing the whole.
+pub fn read_all(
+    pkgs: &BTreeMap<String, DetailedPackageReq>,
+    no_fail_fast: bool,
+) -> Result<BTreeMap<String, LocalPackage>> {
+    let mut local_pkgs = BTreeMap::new();
+
+    if pkgs.is_empty() {
+        return Ok(local_pkgs);
+    }
+
+    log::info!("Reading local package versions...");
+    for (pkg_name, path) in pkgs
+        .iter()
+        .filter_map(|(pkg_name, pkg)| pkg.path.as_ref().map(|path| (pkg_name, path)))
+    {
+        match read_package(pkg_name, Path::new(path))
+            .wrap_err_with(|| format!("Failed to read the local package {pkg_name:?}."))
+        {
+            Ok(local_pkg) => {
+                local_pkgs.insert(pkg_name.clone(), local_pkg);
+            }
+            Err(err) if no_fail_fast => {
+                // Only the cause is relevant after the package name.
+                log::warn!(
+                    "Ignoring the local version of {pkg_name:?}: {}",
+                    err.root_cause()
+                );
+            }
+            Err(err) => {
+                return Err(err.suggestion(
+                    "Use `ship --no-fail-fast` to ignore this and continue on with other packages.",
+                ));
+            }
+        }
+    }
+
+    Ok(local_pkgs)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// Writes the given manifest contents in the given directory, creating it.
+    fn write_manifest(dir: &Path, contents: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(MANIFEST_FILE_NAME), contents).unwrap();
+    }
+
+    #[test]
+    fn test_readpackage_version_isok() {
+        let tmp_dir = TempDir::new().unwrap();
+        write_manifest(
+            tmp_dir.path(),
+            "[package]\nname = \"abc\"\nversion = \"1.2.3\"\n",
+        );
+        assert_eq!(
+            read_package("abc", tmp_dir.path()).unwrap(),
+            LocalPackage {
+                path: tmp_dir.path().canonicalize().unwrap(),
+                version: Version::new(1, 2, 3),
+            },
+        );
+    }
+
+    #[test]
+    fn test_readpackage_noversion_isdefault() {
+        let tmp_dir = TempDir::new().unwrap();
+        write_manifest(tmp_dir.path(), "[package]\nname = \"abc\"\n");
+        assert_eq!(
+            read_package("abc", tmp_dir.path()).unwrap().version,
+            Version::new(0, 0, 0),
+        );
+    }
+
+    #[test]
+    fn test_readpackage_workspaceversion_isok() {
+        let tmp_dir = TempDir::new().unwrap();
+        write_manifest(
+            tmp_dir.path(),
+            "[workspace]\nmembers = [\"abc\"]\n[workspace.package]\nversion = \"4.5.6\"\n",
+        );
+        write_manifest(
+            &tmp_dir.path().join("abc"),
+            "[package]\nname = \"abc\"\nversion.workspace = true\n",
+        );
+        assert_eq!(
+            read_package("abc", &tmp_dir.path().join("abc"))
+                .unwrap()
+                .version,
+            Version::new(4, 5, 6),
+        );
+    }
+
+    #[test]
+    fn test_readpackage_noworkspaceversion_iserr() {
+        let tmp_dir = TempDir::new().unwrap();
+        write_manifest(tmp_dir.path(), "[workspace]\nmembers = [\"abc\"]\n");
+        write_manifest(
+            &tmp_dir.path().join("abc"),
+            "[package]\nname = \"abc\"\nversion.workspace = true\n",
+        );
+        assert!(read_package("abc", &tmp_dir.path().join("abc")).is_err());
+    }
+
+    #[test]
+    fn test_readpackage_virtualmanifest_iserr() {
+        let tmp_dir = TempDir::new().unwrap();
+        write_manifest(tmp_dir.path(), "[workspace]\nmembers = [\"abc\"]\n");
+        assert!(read_package("abc", tmp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_readpackage_othername_iserr() {
+        let tmp_dir = TempDir::new().unwrap();
+        write_manifest(
+            tmp_dir.path(),
+            "[package]\nname = \"def\"\nversion = \"1.2.3\"\n",
+        );
+        assert!(read_package("abc", tmp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_readpackage_nomanifest_iserr() {
+        let tmp_dir = TempDir::new().unwrap();
+        assert!(read_package("abc", tmp_dir.path()).is_err());
+        assert!(read_package("abc", &tmp_dir.path().join("none")).is_err());
+    }
+
+    #[test]
+    fn test_readall_brokenmanifest_nofailfast_isskipped() {
+        let tmp_dir = TempDir::new().unwrap();
+        write_manifest(&tmp_dir.path().join("abc"), "[package");
+        write_manifest(
+            &tmp_dir.path().join("def"),
+            "[package]\nname = \"def\"\nversion = \"1.2.3\"\n",
+        );
+        let pkgs = ["abc", "def"]
+            .into_iter()
+            .map(|pkg_name| {
+                (
+                    pkg_name.to_owned(),
+                    DetailedPackageReq {
+                        path: Some(tmp_dir.path().join(pkg_name).display().to_string()),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        assert!(read_all(&pkgs, false).is_err());
+        assert_eq!(
+            read_all(&pkgs, true)
+                .unwrap()
+                .into_keys()
+                .collect::<Vec<_>>(),
+            ["def"],
+        );
+    }
+}
@@ -2,7 +2,9 @@
 //! and execution of `cargo install` with the required settings.
 #![warn(unused_crate_dependencies)]
 
-use std::{env, process};
+use std::env;
+use std::path::PathBuf;
+use std::process::{self, ExitCode};
 
 use clap::ColorChoice;
 use color_eyre::Section;
@@ -15,12 +17,18 @@ use {snapbox as _, tempfile as _, trycmd as _};
 
 mod cargo;
 mod cli;
-use cli::{LinerArgs, LinerCommands, ShipArgs};
+use cli::{JettisonArgs, LinerArgs, LinerCommands, MessageFormat, ShipArgs};
 mod config;
 use config::{EffectiveJettisonConfig, EffectiveShipConfig, UserConfig};
 mod coloring;
 use coloring::Colorizer;
 mod commands;
+mod git;
+mod local;
+mod message;
+use message::Message;
+mod parallel;
+mod registry;
 #[cfg(test)]
 #[path = "../tests/common/mod.rs"]
 mod testing;
@@ -29,7 +37,7 @@ pub const OPEN_ISSUE_MSG: &str =
     "Open an issue using https://github.com/PaulDance/cargo-liner/issues/new/choose.";
 
 /// Wrap the desired main and let `color-eyre` display errors.
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     // More user-guiding panics in release builds.
     human_panic::setup_panic!();
     // Logging and error reporting are controlled by the CLI arguments, so they
@@ -53,7 +61,11 @@ fn main() -> Result<()> {
     install_error_hook(&args)?;
     // HACK: reproduce the previous behavior by directly exiting: don't display
     // anything, but only report an error code when verbosity is low enough.
-    try_main(&args).inspect_err(|_| {
+    try_main(&args).inspect_err(|err| {
+        if args.message_format == MessageFormat::Json {
+            // Ignore failures here: the original error is more important.
+            let _ = Message::from_report(err).emit();
+        }
         if args.verbosity() <= -3 {
             process::exit(1);
         }
@@ -75,41 +87,113 @@ fn install_error_hook(args: &LinerArgs) -> Result<()> {
     .suggestion(OPEN_ISSUE_MSG)
 }
 
-/// Actual main operation.
-fn try_main(args: &LinerArgs) -> Result<()> {
-    init_logger(args)?;
-    let colorizer = Colorizer::new(&std::io::stderr(), args.color);
-    let cargo_verbosity = match args.verbosity() {
+/// Returns the verbosity to pass onto Cargo calls, offset from the one of the
+/// current tool so that Cargo is only made more verbose or quieter than usual
+/// at the upper levels.
+fn cargo_verbosity(args: &LinerArgs) -> i8 {
+    match args.verbosity() {
         -2..=1 => 0,
         v if v > 1 => v - 1,
         q if q < -2 => q + 2,
         _ => unreachable!(),
-    };
+    }
+}
+
+/// Actual main operation.
+#[expect(
+    clippy::too_many_lines,
+    reason = "This is just the dispatch of all the commands."
+)]
+fn try_main(args: &LinerArgs) -> Result<ExitCode> {
+    init_logger(args)?;
+    let colorizer = Colorizer::new(&std::io::stderr(), args.color);
+    let cargo_verbosity = cargo_verbosity(args);
+    let config_path = config_path(args)?;
+    let config_path = config_path.as_deref();
+
+    let mut exit_code = ExitCode::SUCCESS;
 
     // CLI command dispatch.
     match &args.command {
         Some(LinerCommands::Completions(comp_args)) => {
             commands::completions::run(comp_args);
         }
+        Some(LinerCommands::Check) => {
+            commands::check::run(config_path)?;
+        }
+        Some(LinerCommands::Schema) => {
+            commands::schema::run()?;
+        }
+        Some(LinerCommands::Path) => {
+            commands::path::run(config_path)?;
+        }
+        Some(LinerCommands::Add(add_args)) => {
+            commands::add::run(
+                add_args,
+                config_path,
+                config::env::ship_env_args()
+                    .wrap_err("Failed to get one of the environment variables.")?,
+                &colorizer,
+                cargo_verbosity,
+                args.message_format,
+            )?;
+        }
+        Some(LinerCommands::Remove(remove_args)) => {
+            commands::remove::run(
+                remove_args,
+                config_path,
+                *colorizer.color(),
+                cargo_verbosity,
+            )?;
+        }
         Some(LinerCommands::Import(import_args)) => {
-            commands::import::run(import_args)?;
+            commands::import::run(import_args, config_path, args.message_format)?;
+        }
+        Some(LinerCommands::Migrate(migrate_args)) => {
+            commands::migrate::run(migrate_args, config_path)?;
         }
         Some(LinerCommands::Jettison(jettison_args)) => {
             commands::jettison::run(
                 &EffectiveJettisonConfig::new(
-                    UserConfig::parse_file().wrap_err("Failed to parse the user configuration.")?,
+                    UserConfig::parse_file(config_path)
+                        .wrap_err("Failed to parse the user configuration.")?,
                     config::env::jettison_env_args()
                         .wrap_err("Failed to get one of the environment variables.")?,
                     jettison_args.as_ref().to_owned(),
-                ),
+                )?,
+                config_path,
                 *colorizer.color(),
                 cargo_verbosity,
+                args.message_format,
             )?;
         }
+        Some(LinerCommands::Status) => {
+            let user_config = UserConfig::parse_file(config_path)
+                .wrap_err("Failed to parse the user configuration.")?;
+            if !commands::status::run(
+                &EffectiveShipConfig::new(
+                    user_config.clone(),
+                    config::env::ship_env_args()
+                        .wrap_err("Failed to get one of the environment variables.")?,
+                    ShipArgs::default(),
+                )?,
+                &EffectiveJettisonConfig::new(
+                    user_config,
+                    config::env::jettison_env_args()
+                        .wrap_err("Failed to get one of the environment variables.")?,
+                    JettisonArgs::default(),
+                )?,
+                &colorizer,
+                args.message_format,
+            )? {
+                exit_code = ExitCode::from(commands::status::OUTDATED_EXIT_CODE);
+            }
+        }
         cmd @ (None | Some(LinerCommands::Ship(_))) => {
             commands::ship::run(
-                &EffectiveShipConfig::new(
-                    UserConfig::parse_file().wrap_err("Failed to parse the user configuration.")?,
+                EffectiveShipConfig::new(
+                    UserConfig::parse_file(config_path)
+                        .wrap_err("Failed to parse the user configuration.")?,
                     config::env::ship_env_args()
                         .wrap_err("Failed to get one of the environment variables.")?,
                     if let Some(LinerCommands::Ship(ship_args)) = cmd {
@@ -117,15 +201,27 @@ fn try_main(args: &LinerArgs) -> Result<()> {
                     } else {
                         ShipArgs::default()
                     },
-                ),
+                )?,
+                config_path,
                 &colorizer,
                 cargo_verbosity,
+                args.message_format,
             )?;
         }
     }
 
     log::info!("Done.");
-    Ok(())
+    Ok(exit_code)
+}
+
+/// Returns the path to the configuration file given by the CLI arguments or
+/// the environment, the former taking precedence.
+fn config_path(args: &LinerArgs) -> Result<Option<PathBuf>> {
+    match &args.config {
+        Some(path) => Ok(Some(path.clone())),
+        None => config::env::config_env_path()
+            .wrap_err("Failed to get the configuration file path from the environment."),
+    }
 }
 
 /// Initializes the logger machinery form the passed CLI arguments.
@@ -0,0 +1,125 @@
+//! Machine-readable messages printed to the standard output when requested
+//! with `--message-format json`.
+//!
+//! Each message is a JSON object written on its own line, with a `reason`
+//! field identifying its kind, in a way similar to Cargo's own messages.
+
+use std::io::{self, Write};
+
+use color_eyre::Section;
+use color_eyre::eyre::{self, Result, WrapErr};
+use semver::Version;
+use serde::Serialize;
+
+use crate::cargo::InstallStatus;
+use crate::config::PackageRequirement;
+
+/// A single machine-readable message.
+#[derive(Serialize, Debug)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+pub enum Message<'a> {
+    /// Result of the version check of a configured package.
+    VersionCheck {
+        package: &'a str,
+        old_version: Option<&'a Version>,
+        new_version: Option<&'a Version>,
+        latest_version: Option<&'a Version>,
+        old_commit: Option<&'a str>,
+        new_commit: Option<&'a str>,
+        changed_options: &'a [&'static str],
+        up_to_date: bool,
+    },
+    /// Outcome of the installation or update of a package.
+    InstallStatus {
+        package: &'a str,
+        old_version: Option<&'a Version>,
+        new_version: Option<&'a Version>,
+        old_commit: Option<&'a str>,
+        new_commit: Option<&'a str>,
+        status: &'a InstallStatus,
+        dry_run: bool,
+    },
+    /// Installed package that is not configured and therefore planned to be
+    /// uninstalled.
+    UninstallPlan {
+        package: &'a str,
+        version: &'a Version,
+    },
+    /// Package written to the configuration by an import.
+    Import {
+        package: &'a str,
+        requirement: &'a PackageRequirement,
+    },
+    /// Error report ending the current operation.
+    Error {
+        message: String,
+        causes: Vec<String>,
+    },
+}
+
+impl Message<'_> {
+    /// Builds the message corresponding to the given error report.
+    pub fn from_report(err: &eyre::Report) -> Message<'static> {
+        Message::Error {
+            message: err.to_string(),
+            causes: err.chain().skip(1).map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Serializes the message and prints it on its own line to the standard
+    /// output.
+    pub fn emit(&self) -> Result<()> {
+        let mut stdout = io::stdout().lock();
+        serde_json::to_writer(&mut stdout, self)
+            .wrap_err("Failed to serialize a message.")
+            .note("This really should not happen.")
+            .suggestion(crate::OPEN_ISSUE_MSG)?;
+        writeln!(stdout)
+            .and_then(|()| stdout.flush())
+            .wrap_err("Failed to write a message to the standard output.")
+            .suggestion("Check that the standard output is still open.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ser_message_uninstallplan() {
+        assert_eq!(
+            serde_json::to_string(&Message::UninstallPlan {
+                package: "abc",
+                version: &Version::new(1, 2, 3),
+            })
+            .unwrap(),
+            r#"{"reason":"uninstall-plan","package":"abc","version":"1.2.3"}"#,
+        );
+    }
+
+    #[test]
+    fn test_ser_message_installstatus() {
+        assert_eq!(
+            serde_json::to_string(&Message::InstallStatus {
+                package: "abc",
+                old_version: None,
+                new_version: Some(&Version::new(1, 2, 3)),
+                old_commit: None,
+                new_commit: None,
+                status: &InstallStatus::Installed,
+                dry_run: false,
+            })
+            .unwrap(),
+            r#"{"reason":"install-status","package":"abc","old_version":null,"new_version":"1.2.3","old_commit":null,"new_commit":null,"status":"installed","dry_run":false}"#,
+        );
+    }
+
+    #[test]
+    fn test_ser_message_error() {
+        let err = eyre::eyre!("inner").wrap_err("outer");
+        assert_eq!(
+            serde_json::to_string(&Message::from_report(&err)).unwrap(),
+            r#"{"reason":"error","message":"outer","causes":["inner"]}"#,
+        );
+    }
+}
@@ -0,0 +1,71 @@
+//! Bounded parallel execution of independent tasks.
+
+use std::sync::Mutex;
+use std::thread;
+
+/// Applies the given function to each of the given items using at most `jobs`
+/// threads at once and returns the results in the same order as the items.
+///
+/// A `jobs` of zero is considered as one.
+pub fn map<T, R, F>(items: Vec<T>, jobs: usize, func: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let workers = jobs.clamp(1, items.len().max(1));
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let results = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    // Release the lock before running anything.
+                    let Some((idx, item)) = queue.lock().unwrap().next() else {
+                        break;
+                    };
+                    let res = func(item);
+                    results.lock().unwrap().push((idx, res));
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_unstable_by_key(|(idx, _)| *idx);
+    results.into_iter().map(|(_, res)| res).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_map_keepsorder() {
+        assert_eq!(
+            map((0..100).collect(), 7, |x| x * 2),
+            (0..100).map(|x| x * 2).collect::<Vec<_>>(),
+        );
+        assert_eq!(map(vec![1, 2, 3], 0, |x| x + 1), [2, 3, 4]);
+        assert!(map(Vec::<u8>::new(), 4, |x| x).is_empty());
+    }
+
+    #[test]
+    fn test_map_isbounded() {
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+
+        map((0..20).collect(), 3, |_: u8| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(5));
+            running.fetch_sub(1, Ordering::SeqCst);
+        });
+
+        assert!((1..=3).contains(&max_running.into_inner()));
+    }
+}
@@ -0,0 +1,749 @@
+//! Native client for Cargo's [sparse registry index protocol], used in order to
+//! fetch the versions published for the configured packages.
+//!
+//! [sparse registry index protocol]: https://doc.rust-lang.org/cargo/reference/registry-index.html#sparse-protocol
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+use color_eyre::Section;
+use color_eyre::eyre::{self, Result, WrapErr};
+use semver::Version;
+use serde::Deserialize;
+use url::Url;
+
+use crate::config::DetailedPackageReq;
+use crate::{cargo, parallel};
+
+/// URL of the official crates.io sparse index.
+const CRATES_IO_INDEX: &str = "https://index.crates.io/";
+/// URL of the official crates.io Git index, as used in Cargo's configuration
+/// and in its `.crates.toml` file.
+const CRATES_IO_GIT_INDEX: &str = "https://github.com/rust-lang/crates.io-index";
+/// Name of the official registry in Cargo's configuration.
+const CRATES_IO_NAME: &str = "crates-io";
+
+/// A single version of a package as published in a registry index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// Name of the package.
+    pub name: String,
+    /// Version of the package described by this entry.
+    pub version: Version,
+    /// Whether this version has been yanked and should thus not be selected.
+    pub yanked: bool,
+    /// Minimum version of Rust required by this version of the package, if
+    /// specified by its manifest.
+    pub rust_version: Option<Version>,
+    /// Features defined by this version of the package, including the
+    /// implicit ones of optional dependencies.
+    pub features: BTreeMap<String, Vec<String>>,
+}
+
+impl From<RawIndexEntry> for IndexEntry {
+    fn from(raw: RawIndexEntry) -> Self {
+        let mut features = raw.features;
+        features.extend(raw.features2);
+
+        // Optional dependencies define an implicit feature unless referenced
+        // using the `dep:` syntax somewhere in the explicit ones.
+        let implicit_deps = raw
+            .deps
+            .into_iter()
+            .filter(|dep| dep.optional)
+            .map(|dep| dep.name)
+            .filter(|dep_name| {
+                !features.values().flatten().any(|feat| {
+                    feat.strip_prefix("dep:")
+                        .is_some_and(|feat_dep| feat_dep == dep_name)
+                })
+            })
+            .collect::<Vec<_>>();
+        for dep_name in implicit_deps {
+            features
+                .entry(dep_name.clone())
+                .or_insert_with(|| vec![format!("dep:{dep_name}")]);
+        }
+
+        Self {
+            name: raw.name,
+            version: raw.vers,
+            yanked: raw.yanked,
+            rust_version: raw.rust_version.as_deref().and_then(parse_rust_version),
+            features,
+        }
+    }
+}
+
+/// Line of an index file, as documented in the [Cargo reference].
+///
+/// [Cargo reference]: https://doc.rust-lang.org/cargo/reference/registry-index.html#json-schema
+#[derive(Deserialize, Debug)]
+struct RawIndexEntry {
+    name: String,
+    vers: Version,
+    #[serde(default)]
+    deps: Vec<RawIndexDependency>,
+    #[serde(default)]
+    features: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    features2: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    yanked: bool,
+    #[serde(default)]
+    rust_version: Option<String>,
+}
+
+/// Dependency of an index entry: only what is required to compute features.
+#[derive(Deserialize, Debug)]
+struct RawIndexDependency {
+    name: String,
+    #[serde(default)]
+    optional: bool,
+}
+
+/// Parses a `rust-version` value as found in the index, for example `1.70`, by
+/// completing the missing components with zeros.
+fn parse_rust_version(rust_ver: &str) -> Option<Version> {
+    let mut comps = rust_ver.trim().split('.');
+    let ver = Version::new(
+        comps.next()?.parse().ok()?,
+        comps.next().map_or(Some(0), |comp| comp.parse().ok())?,
+        comps.next().map_or(Some(0), |comp| comp.parse().ok())?,
+    );
+    comps.next().is_none().then_some(ver)
+}
+
+/// A registry index as configured by the user for a given package.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum IndexSource {
+    /// The registry used by default by Cargo.
+    Default,
+    /// A registry named in Cargo's configuration.
+    Named(String),
+    /// A registry given directly through its index URL.
+    Url(String),
+}
+
+impl From<&DetailedPackageReq> for IndexSource {
+    /// Mirrors the options `cargo install` receives, `index` taking priority.
+    fn from(pkg_req: &DetailedPackageReq) -> Self {
+        if let Some(index) = &pkg_req.index {
+            Self::Url(index.clone())
+        } else if let Some(registry) = &pkg_req.registry {
+            Self::Named(registry.clone())
+        } else {
+            Self::Default
+        }
+    }
+}
+
+/// Registry index resolved from an [`IndexSource`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ResolvedIndex {
+    /// Sparse index URL.
+    url: Url,
+    /// Name of the registry in Cargo's configuration, if any, used in order to
+    /// retrieve authentication tokens.
+    name: Option<String>,
+}
+
+/// Outcome of the resolution of an [`IndexSource`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Resolution {
+    /// The source is backed by a sparse index that can be fetched.
+    Sparse(ResolvedIndex),
+    /// The source cannot be checked natively, for the given reason, such as
+    /// Git-protocol registries or vendored sources.
+    Unsupported(String),
+}
+
+impl IndexSource {
+    /// Resolves the source to its sparse index using Cargo's configuration.
+    fn resolve(&self) -> Result<Resolution> {
+        match self {
+            Self::Default => resolve_named_index(CRATES_IO_NAME, 0),
+            Self::Named(name) => resolve_named_index(name, 0)
+                .wrap_err_with(|| format!("Failed to get the index of the `{name}` registry.")),
+            Self::Url(index) => parse_index_url(index, None),
+        }
+    }
+}
+
+/// Returns the sparse index of the given source or registry name as configured
+/// through Cargo's `[source]` or `[registries]` tables, following the chain of
+/// source replacements.
+fn resolve_named_index(src_name: &str, depth: usize) -> Result<Resolution> {
+    // Arbitrary, just to avoid looping endlessly on misconfigurations.
+    const MAX_REPLACEMENT_DEPTH: usize = 16;
+
+    if depth > MAX_REPLACEMENT_DEPTH {
+        return Err(eyre::eyre!(
+            "Too many source replacements starting from `{src_name}`."
+        ))
+        .suggestion("Check the `replace-with` settings of Cargo's configuration for cycles.");
+    }
+
+    if let Some(repl_name) = config_get_opt(&format!("source.{src_name}.replace-with")) {
+        log::debug!("Source `{src_name}` is replaced with `{repl_name}`.");
+        return resolve_named_index(&repl_name, depth + 1).wrap_err_with(|| {
+            format!("Failed to get the index of the `{repl_name}` source replacing `{src_name}`.")
+        });
+    }
+
+    if src_name == CRATES_IO_NAME {
+        return Ok(Resolution::Sparse(ResolvedIndex {
+            url: Url::parse(CRATES_IO_INDEX)?,
+            name: None,
+        }));
+    }
+
+    if let Some(index) = config_get_opt(&format!("source.{src_name}.registry"))
+        .or_else(|| config_get_opt(&format!("registries.{src_name}.index")))
+    {
+        return parse_index_url(&index, Some(src_name));
+    }
+
+    // Sources that are not registries at all.
+    for (key, kind) in [
+        ("directory", "a vendored directory"),
+        ("local-registry", "a local registry"),
+        ("git", "a Git repository"),
+    ] {
+        if config_get_opt(&format!("source.{src_name}.{key}")).is_some() {
+            return Ok(Resolution::Unsupported(format!(
+                "the `{src_name}` source is {kind}"
+            )));
+        }
+    }
+
+    Err(eyre::eyre!("No index is configured for `{src_name}`.")).suggestion(
+        "Check that the name is defined in the `[source]` or `[registries]` tables of Cargo's \
+         configuration.",
+    )
+}
+
+/// Parses the given registry index URL, as found in Cargo's configuration, to
+/// a sparse index URL, given the name of the registry it is configured for, if
+/// any.
+///
+/// Indexes that do not use the sparse protocol are unsupported, except for the
+/// crates.io one that has a sparse equivalent.
+fn parse_index_url(index: &str, name: Option<&str>) -> Result<Resolution> {
+    if let Some(sparse_index) = index.strip_prefix("sparse+") {
+        let mut url = Url::parse(sparse_index)
+            .wrap_err_with(|| format!("Failed to parse the sparse index URL {sparse_index:?}."))?;
+
+        // Joining paths requires a trailing slash in order to keep the last
+        // segment of the base URL.
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+
+        Ok(Resolution::Sparse(ResolvedIndex {
+            url,
+            name: name.map(ToOwned::to_owned),
+        }))
+    } else if index.trim_end_matches('/') == CRATES_IO_GIT_INDEX {
+        Ok(Resolution::Sparse(ResolvedIndex {
+            url: Url::parse(CRATES_IO_INDEX)?,
+            name: None,
+        }))
+    } else {
+        Ok(Resolution::Unsupported(format!(
+            "the registry index {index:?} does not use the sparse protocol"
+        )))
+    }
+}
+
+/// Calls [`cargo::config_get`] and considers errors as the absence of value.
+fn config_get_opt(key: &str) -> Option<String> {
+    cargo::config_get(key)
+        .inspect_err(|err| log::debug!("Could not get `{key}` from Cargo's configuration: {err}"))
+        .ok()
+}
+
+/// Returns the token configured for the given registry name, either in Cargo's
+/// configuration or in its credentials file, as `cargo config get` does not
+/// read the latter.
+fn registry_token(reg_name: &str) -> Option<String> {
+    config_get_opt(&format!("registries.{reg_name}.token")).or_else(|| {
+        let path = cargo::home().ok()?.join("credentials.toml");
+        log::debug!("Reading the token of `{reg_name}` from {path:#?}...");
+        fs::read_to_string(&path)
+            .inspect_err(|err| log::debug!("Could not read {path:#?}: {err}"))
+            .ok()?
+            .parse::<toml::Table>()
+            .inspect_err(|err| log::debug!("Could not parse {path:#?}: {err}"))
+            .ok()?
+            .get("registries")?
+            .get(reg_name)?
+            .get("token")?
+            .as_str()
+            .map(ToOwned::to_owned)
+    })
+}
+
+/// Returns the path of the given package's file relative to the index root.
+///
+/// See the [Cargo reference] for more details.
+///
+/// [Cargo reference]: https://doc.rust-lang.org/cargo/reference/registry-index.html#index-files
+///
+/// It fails if the name is not a valid crate name, i.e. if it is empty or
+/// contains anything else than ASCII alphanumeric characters, `-` and `_`.
+fn index_file_path(pkg: &str) -> Result<String> {
+    if pkg.is_empty()
+        || !pkg
+            .chars()
+            .all(|chr| chr.is_ascii_alphanumeric() || chr == '-' || chr == '_')
+    {
+        eyre::bail!(
+            "Invalid package name {pkg:?}: only ASCII alphanumeric characters, `-` and `_` are \
+             allowed."
+        );
+    }
+    let pkg = pkg.to_ascii_lowercase();
+
+    Ok(match pkg.len() {
+        1 => format!("1/{pkg}"),
+        2 => format!("2/{pkg}"),
+        3 => format!("3/{}/{pkg}", &pkg[..1]),
+        _ => format!("{}/{}/{pkg}", &pkg[..2], &pkg[2..4]),
+    })
+}
+
+/// Fetches and parses the index file of the given package from the given
+/// sparse index.
+fn fetch_entries(agent: &ureq::Agent, index: &ResolvedIndex, pkg: &str) -> Result<Vec<IndexEntry>> {
+    let file_url = index
+        .url
+        .join(&index_file_path(pkg)?)
+        .wrap_err_with(|| format!("Failed to build the index file URL for {pkg:?}."))?;
+    log::debug!("Fetching {file_url}...");
+
+    let mut res = agent.get(file_url.as_str()).call();
+
+    // Private registries require authentication: only send the token when
+    // asked to, similarly to Cargo.
+    if let Err(ureq::Error::StatusCode(401)) = res
+        && let Some(reg_name) = &index.name
+        && let Some(token) = registry_token(reg_name)
+    {
+        log::debug!("Authenticating to the `{reg_name}` registry...");
+        res = agent
+            .get(file_url.as_str())
+            .header("Authorization", token)
+            .call();
+    }
+
+    let body = match res {
+        Ok(mut resp) => resp
+            .body_mut()
+            .read_to_string()
+            .wrap_err("Failed to read the response body.")?,
+        Err(ureq::Error::StatusCode(401)) => {
+            return Err(eyre::eyre!("Unauthorized access to {}.", index.url))
+                .suggestion("Configure a token for the registry in Cargo's configuration.");
+        }
+        // See the Cargo reference for these.
+        Err(ureq::Error::StatusCode(404 | 410 | 451)) => {
+            return Err(eyre::eyre!("Package {pkg:?} not found in {}.", index.url))
+                .suggestion("Check that the package does indeed exist.");
+        }
+        Err(err) => {
+            return Err(err)
+                .wrap_err_with(|| format!("Failed to fetch {file_url}."))
+                .suggestion("Check your network connection and the registry's availability.");
+        }
+    };
+    log::trace!("Index file for {pkg:?} is: {body:?}");
+
+    let entries = body
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            serde_json::from_str::<RawIndexEntry>(line)
+                .inspect_err(|err| log::warn!("Skipping an index entry of {pkg:?}: {err}"))
+                .ok()
+        })
+        .map(IndexEntry::from)
+        .collect::<Vec<_>>();
+
+    if entries.is_empty() {
+        eyre::bail!("No version of {pkg:?} could be found in {}.", index.url);
+    }
+
+    Ok(entries)
+}
+
+/// Results of [`fetch_all`].
+#[derive(Debug, Default)]
+pub struct FetchedEntries {
+    /// Index entries of the packages whose registry could be looked up.
+    pub entries: BTreeMap<String, Vec<IndexEntry>>,
+    /// Packages whose source does not support looking their versions up.
+    pub unsupported: BTreeSet<String>,
+}
+
+/// Fetches the index entries of all packages in the given map from their
+/// respective registry, using at most `jobs` parallel requests, and returns
+/// them in the collected map.
+///
+/// With `no_fail_fast`, the packages whose entries could not be fetched are
+/// warned about and left out of the map instead of failing the whole lookup.
+/// The packages whose source is not supported are always left out of it and
+/// listed separately.
+pub fn fetch_all(
+    pkgs: &BTreeMap<String, DetailedPackageReq>,
+    jobs: usize,
+    no_fail_fast: bool,
+) -> Result<FetchedEntries> {
+    log::info!("Fetching latest package versions...");
+    let mut resolutions = BTreeMap::<IndexSource, Resolution>::new();
+    let mut pkg_indexes = Vec::new();
+    let mut fetched = FetchedEntries::default();
+
+    log::debug!("Resolving registry indexes...");
+    for (pkg_name, pkg_req) in pkgs {
+        let src = IndexSource::from(pkg_req);
+        let res = match resolutions.get(&src) {
+            Some(resolution) => Ok(resolution.clone()),
+            None => src.resolve().inspect(|resolution| {
+                log::debug!("Resolved {src:?} to {resolution:?}.");
+                resolutions.insert(src, resolution.clone());
+            }),
+        };
+
+        match res
+            .wrap_err_with(|| format!("Failed to determine the registry index of {pkg_name:?}."))
+        {
+            Ok(Resolution::Sparse(index)) => pkg_indexes.push((pkg_name.as_str(), index)),
+            Ok(Resolution::Unsupported(reason)) => {
+                log::warn!(
+                    "Unable to check the latest version of {pkg_name:?}, installing it regardless: \
+                     {reason}. Use its `skip-check` option in order to silence this."
+                );
+                fetched.unsupported.insert(pkg_name.clone());
+            }
+            Err(err) => skip_or_fail(pkg_name, err, no_fail_fast)?,
+        }
+    }
+
+    let agent = ureq::Agent::new_with_defaults();
+    log::debug!("Fetching index files with {jobs} job(s)...");
+
+    for (pkg, res) in parallel::map(pkg_indexes, jobs, |(pkg, index)| {
+        (pkg, fetch_entries(&agent, &index, pkg))
+    }) {
+        match res.wrap_err_with(|| format!("Failed to fetch the index entries of {pkg:?}.")) {
+            Ok(entries) => {
+                fetched.entries.insert(pkg.to_owned(), entries);
+            }
+            Err(err) => skip_or_fail(pkg, err, no_fail_fast)?,
+        }
+    }
+
+    Ok(fetched)
+}
+
+/// Handles the given failure to look the given package up: only warned about
+/// when not failing fast, returned otherwise.
+fn skip_or_fail(pkg: &str, err: eyre::Report, no_fail_fast: bool) -> Result<()> {
+    if no_fail_fast {
+        // Only the cause is relevant after the package name.
+        log::warn!(
+            "Ignoring the latest version of {pkg:?}: {}",
+            err.root_cause()
+        );
+        Ok(())
+    } else {
+        Err(err.suggestion(
+            "Use `ship --no-fail-fast` to ignore this and continue on with other packages.",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::{LazyLock, Mutex};
+
+    use cargo_test_macro::cargo_test;
+    use cargo_test_support::registry::Package;
+
+    use super::*;
+    use crate::testing;
+
+    const SELF: &str = clap::crate_name!();
+    const NONE: &str = "azertyuiop-qsdfghjklm_wxcvbn";
+    static LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+    /// Builds a map of the given packages using default requirements.
+    fn default_reqs(pkgs: &[&str]) -> BTreeMap<String, DetailedPackageReq> {
+        pkgs.iter()
+            .map(|pkg| ((*pkg).to_owned(), DetailedPackageReq::default()))
+            .collect()
+    }
+
+    #[test]
+    fn test_indexfilepath() {
+        assert_eq!(index_file_path("a").unwrap(), "1/a");
+        assert_eq!(index_file_path("ab").unwrap(), "2/ab");
+        assert_eq!(index_file_path("abc").unwrap(), "3/a/abc");
+        assert_eq!(index_file_path("abcd").unwrap(), "ab/cd/abcd");
+        assert_eq!(index_file_path("Cargo-Liner").unwrap(), "ca/rg/cargo-liner");
+        assert_eq!(index_file_path("a_b-c").unwrap(), "a_/b-/a_b-c");
+    }
+
+    #[test]
+    fn test_indexfilepath_invalid_iserr() {
+        for pkg in ["", "é", "aé", "abé", "abcé", "a/b", "a b", "../abc"] {
+            assert!(index_file_path(pkg).is_err(), "{pkg:?}");
+        }
+    }
+
+    #[test]
+    fn test_parserustversion() {
+        assert_eq!(parse_rust_version("1"), Some(Version::new(1, 0, 0)));
+        assert_eq!(parse_rust_version("1.70"), Some(Version::new(1, 70, 0)));
+        assert_eq!(parse_rust_version("1.70.1"), Some(Version::new(1, 70, 1)));
+        assert_eq!(parse_rust_version("1.70.1.0"), None);
+        assert_eq!(parse_rust_version("abc"), None);
+    }
+
+    #[test]
+    fn test_parseindexurl() -> Result<()> {
+        let url = |index| match parse_index_url(index, None)? {
+            Resolution::Sparse(index) => Ok(index.url.to_string()),
+            Resolution::Unsupported(reason) => Err(eyre::eyre!(reason)),
+        };
+
+        assert_eq!(
+            url("sparse+http://127.0.0.1:1234/index")?,
+            "http://127.0.0.1:1234/index/",
+        );
+        assert_eq!(url("sparse+https://index.crates.io/")?, CRATES_IO_INDEX);
+        assert_eq!(url(CRATES_IO_GIT_INDEX)?, CRATES_IO_INDEX);
+        assert!(matches!(
+            parse_index_url("https://example.com/git-index", None)?,
+            Resolution::Unsupported(_),
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_indexsource_fromreq() {
+        assert_eq!(
+            IndexSource::from(&DetailedPackageReq::default()),
+            IndexSource::Default,
+        );
+        assert_eq!(
+            IndexSource::from(&DetailedPackageReq {
+                registry: Some("my-registry".to_owned()),
+                ..Default::default()
+            }),
+            IndexSource::Named("my-registry".to_owned()),
+        );
+        assert_eq!(
+            IndexSource::from(&DetailedPackageReq {
+                index: Some("sparse+https://example.com/index/".to_owned()),
+                registry: Some("my-registry".to_owned()),
+                ..Default::default()
+            }),
+            IndexSource::Url("sparse+https://example.com/index/".to_owned()),
+        );
+    }
+
+    #[test]
+    fn test_indexentry_fromraw_features() -> Result<()> {
+        let entry = IndexEntry::from(serde_json::from_str::<RawIndexEntry>(
+            r#"{
+                "name": "abc",
+                "vers": "1.2.3",
+                "deps": [
+                    {"name": "def", "optional": true},
+                    {"name": "ghi", "optional": true},
+                    {"name": "jkl", "optional": false}
+                ],
+                "features": {"a": []},
+                "features2": {"b": ["dep:ghi"]},
+                "cksum": "whatever",
+                "yanked": true,
+                "rust_version": "1.70",
+                "v": 2
+            }"#,
+        )?);
+
+        assert_eq!(
+            entry,
+            IndexEntry {
+                name: "abc".to_owned(),
+                version: Version::new(1, 2, 3),
+                yanked: true,
+                rust_version: Some(Version::new(1, 70, 0)),
+                features: [
+                    ("a".to_owned(), vec![]),
+                    ("b".to_owned(), vec!["dep:ghi".to_owned()]),
+                    ("def".to_owned(), vec!["dep:def".to_owned()]),
+                ]
+                .into_iter()
+                .collect(),
+            },
+        );
+        Ok(())
+    }
+
+    #[cargo_test]
+    fn test_singlethreaded_fetchall_selfandothers_isok() -> Result<()> {
+        let _lk = LOCK.lock();
+        let _reg = testing::init_registry();
+        testing::fake_publish_all([
+            (SELF, clap::crate_version!()),
+            ("cargo-expand", "1.0.79"),
+            ("cargo-expand", "1.0.80"),
+            ("bat", "0.24.0"),
+        ]);
+        Package::new("cargo-tarpaulin", "0.27.3")
+            .feature("abc", &[])
+            .rust_version("1.70")
+            .yanked(true)
+            .publish();
+        testing::set_env();
+
+        let entries = fetch_all(
+            &default_reqs(&[SELF, "cargo-expand", "cargo-tarpaulin", "bat"]),
+            2,
+            false,
+        )?
+        .entries;
+        let versions = |pkg: &str| {
+            entries[pkg]
+                .iter()
+                .map(|entry| entry.version.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(entries.len(), 4);
+        assert_eq!(versions(SELF), [clap::crate_version!()]);
+        assert_eq!(versions("cargo-expand"), ["1.0.79", "1.0.80"]);
+        assert_eq!(versions("bat"), ["0.24.0"]);
+        assert_eq!(
+            entries["cargo-tarpaulin"],
+            [IndexEntry {
+                name: "cargo-tarpaulin".to_owned(),
+                version: Version::new(0, 27, 3),
+                yanked: true,
+                rust_version: Some(Version::new(1, 70, 0)),
+                features: [("abc".to_owned(), vec![])].into_iter().collect(),
+            }],
+        );
+        Ok(())
+    }
+
+    #[cargo_test]
+    fn test_singlethreaded_fetchall_alternative_isok() -> Result<()> {
+        let _lk = LOCK.lock();
+        let _reg = testing::init_registry();
+        let _alt_reg = testing::init_alt_registry();
+        testing::fake_publish("abc", "0.0.1");
+        testing::fake_publish_alt("abc", "0.0.2");
+        testing::set_env();
+
+        let mut pkgs = default_reqs(&["abc"]);
+        assert_eq!(
+            fetch_all(&pkgs, 1, false)?.entries["abc"][0].version,
+            Version::new(0, 0, 1)
+        );
+        pkgs.get_mut("abc").unwrap().registry = Some("alternative".to_owned());
+        assert_eq!(
+            fetch_all(&pkgs, 1, false)?.entries["abc"][0].version,
+            Version::new(0, 0, 2)
+        );
+        pkgs.get_mut("abc").unwrap().registry = Some("unknown".to_owned());
+        assert!(fetch_all(&pkgs, 1, false).is_err());
+        Ok(())
+    }
+
+    #[cargo_test]
+    fn test_singlethreaded_fetchall_unknownregistry_nofailfast_isskipped() -> Result<()> {
+        let _lk = LOCK.lock();
+        let _reg = testing::init_registry();
+        testing::fake_publish("abc", "0.0.1");
+        testing::set_env();
+
+        let mut pkgs = default_reqs(&["abc", "def"]);
+        pkgs.get_mut("def").unwrap().registry = Some("unknown".to_owned());
+        let fetched = fetch_all(&pkgs, 1, true)?;
+        assert_eq!(fetched.entries.keys().collect::<Vec<_>>(), ["abc"]);
+        assert!(fetched.unsupported.is_empty());
+        Ok(())
+    }
+
+    #[cargo_test]
+    fn test_singlethreaded_fetchall_unsupported_islisted() -> Result<()> {
+        let _lk = LOCK.lock();
+        let _reg = testing::init_registry();
+        testing::fake_publish("abc", "0.0.1");
+        let mut config = fs::OpenOptions::new()
+            .append(true)
+            .open(cargo_test_support::paths::cargo_home().join("config.toml"))?;
+        writeln!(
+            config,
+            "\n[registries.git-registry]\nindex = \"https://example.com/git-index\"\n\n\
+             [source.vendored]\ndirectory = \"vendor\"\n\n\
+             [source.replaced]\nreplace-with = \"vendored\"",
+        )?;
+        testing::set_env();
+
+        let mut pkgs = default_reqs(&["abc", "def", "ghi", "jkl"]);
+        pkgs.get_mut("def").unwrap().index = Some("https://example.com/git-index".to_owned());
+        pkgs.get_mut("ghi").unwrap().registry = Some("git-registry".to_owned());
+        pkgs.get_mut("jkl").unwrap().registry = Some("replaced".to_owned());
+        let fetched = fetch_all(&pkgs, 1, false)?;
+        assert_eq!(fetched.entries.keys().collect::<Vec<_>>(), ["abc"]);
+        assert_eq!(
+            fetched.unsupported.iter().collect::<Vec<_>>(),
+            ["def", "ghi", "jkl"],
+        );
+        Ok(())
+    }
+
+    #[cargo_test]
+    fn test_singlethreaded_fetchall_authrequired_isok() -> Result<()> {
+        let _lk = LOCK.lock();
+        let _reg = testing::init_registry();
+        let _alt_reg = testing::init_alt_registry_auth();
+        testing::fake_publish_alt("abc", "0.0.2");
+        testing::set_env();
+
+        let mut pkgs = default_reqs(&["abc"]);
+        pkgs.get_mut("abc").unwrap().registry = Some("alternative".to_owned());
+        assert_eq!(
+            fetch_all(&pkgs, 1, false)?.entries["abc"][0].version,
+            Version::new(0, 0, 2)
+        );
+        Ok(())
+    }
+
+    #[cargo_test]
+    fn test_singlethreaded_fetchall_none_iserr() {
+        let _lk = LOCK.lock();
+        let _reg = testing::init_registry();
+        testing::set_env();
+
+        assert!(fetch_all(&default_reqs(&[NONE]), 1, false).is_err());
+    }
+
+    #[cargo_test]
+    fn test_singlethreaded_fetchall_none_nofailfast_isskipped() -> Result<()> {
+        let _lk = LOCK.lock();
+        let _reg = testing::init_registry();
+        testing::fake_publish("abc", "0.0.1");
+        testing::set_env();
+
+        let fetched = fetch_all(&default_reqs(&[NONE, "abc"]), 4, true)?;
+        assert_eq!(fetched.entries.keys().collect::<Vec<_>>(), ["abc"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use std::fs;
+
+use cargo_test_macro::cargo_test;
+use indoc::indoc;
+use snapbox::IntoData;
+
+mod common;
+use common::*;
+
+#[cargo_test]
+fn validate_add_noinstall_keepsformat() {
+    write_user_config(&[
+        "# Tools.",
+        "[packages]",
+        "abc = '*' # Pinned later.",
+        "ghi = { version = '1', locked = true }",
+        "",
+        "# Defaults.",
+        "[defaults.ship]",
+        "no-self = true",
+    ]);
+
+    cargo_liner!()
+        .args([
+            "add",
+            "def@^1.2",
+            "-F",
+            "a,b",
+            "--no-default-features",
+            "--no-install",
+        ])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file!["fixtures/add/validate_add_noinstall_keepsformat.stderr"].raw());
+    assert_user_config_eq(indoc!(
+        r#"
+        # Tools.
+        [packages]
+        abc = '*' # Pinned later.
+        def = { version = "^1.2", default-features = false, features = ["a", "b"] }
+        ghi = { version = '1', locked = true }
+
+        # Defaults.
+        [defaults.ship]
+        no-self = true
+        "#
+    ));
+}
+
+#[cargo_test]
+fn validate_add_noinstall_unsorted_appends() {
+    write_user_config(&["[packages]", "def = '*'", "abc = '*'"]);
+
+    cargo_liner!()
+        .args(["add", "ghi", "--no-install"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw());
+    assert_user_config_eq("[packages]\ndef = '*'\nabc = '*'\nghi = \"*\"\n");
+}
+
+#[cargo_test]
+fn validate_add_noinstall_existing_replaces() {
+    write_user_config(&["[packages]", "abc = '*'", "def = '*'"]);
+
+    cargo_liner!()
+        .args(["add", "abc@=1.0.0", "--locked", "--no-install"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(
+            snapbox::file!["fixtures/add/validate_add_noinstall_existing_replaces.stderr"].raw(),
+        );
+    assert_user_config_eq(indoc!(
+        r#"
+        [packages]
+        abc = { version = "=1.0.0", locked = true }
+        def = '*'
+        "#
+    ));
+}
+
+#[cargo_test]
+fn validate_add_noinstall_nofile_creates() {
+    fs::create_dir_all(cargo_test_support::paths::cargo_home()).unwrap();
+    assert_user_config_absent();
+
+    cargo_liner!()
+        .args(["add", "abc", "--no-install"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw());
+    assert_user_config_eq("[packages]\nabc = \"*\"\n");
+}
+
+#[cargo_test]
+fn validate_add_installs_onlyit() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.1"), ("def", "0.0.1")]);
+    write_user_config(&["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .args(["add", "def"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/add/validate_add_installs_onlyit.stderr"
+        ]);
+    assert_user_config_eq("[packages]\nabc = '*'\ndef = \"*\"\n");
+    assert_installed("def");
+    assert_not_installed("abc");
+}
+
+#[cargo_test]
+fn validate_add_badspec_iserr() {
+    write_user_config(&["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .args(["add", "def@nope", "--no-install"])
+        .assert()
+        .code(2)
+        .stdout_eq("".into_data().raw());
+    assert_user_config_eq("[packages]\nabc = '*'");
+}
+
+#[cargo_test]
+fn validate_add_project_cargo_manifest_iserr() {
+    let path = write_config_file(
+        "ws/Cargo.toml",
+        &["[workspace]", "[workspace.metadata.liner.packages]"],
+    );
+
+    cargo_liner!()
+        .current_dir(path.parent().unwrap())
+        .args(["add", "abc", "--no-install"])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/add/validate_add_project_cargo_manifest_iserr.stderr"
+        ]);
+    assert_user_config_absent();
+}
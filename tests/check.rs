@@ -0,0 +1,143 @@
+use cargo_test_macro::cargo_test;
+use snapbox::IntoData;
+
+mod common;
+use common::*;
+
+#[cargo_test]
+fn validate_check_valid_isok() {
+    write_user_config(&[
+        "strict = true",
+        "[packages]",
+        "abc = '*'",
+        "def = { version = '1.2', git = 'https://example.com/def', branch = 'main', binstall = 'auto' }",
+        "[defaults.ship]",
+        "binstall = 'always'",
+    ]);
+
+    cargo_liner!()
+        .arg("check")
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file!["fixtures/check/validate_check_valid_isok.stderr"].raw());
+}
+
+#[cargo_test]
+fn validate_check_config_stdin_isok() {
+    cargo_liner!()
+        .args(["check", "--config", "-"])
+        .stdin("[packages]\nabc = '*'\n")
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file!["fixtures/check/validate_check_valid_isok.stderr"].raw());
+    assert_user_config_absent();
+}
+
+#[cargo_test]
+fn validate_check_issues_iserr() {
+    write_user_config(&[
+        "[packages]",
+        "abc = 'x.y'",
+        "def = { version = '1', defualt-features = false }",
+        "ghi = { version = '1', branch = 'main', binstall = 'auto' }",
+        "jkl = { version = '1', all-features = true, features = ['a'] }",
+        "mno = { version = '1', path = '/a/b/c', binstall = 'always' }",
+        "[defaults.ship]",
+        "no-selfs = true",
+        "[unknown-section]",
+    ]);
+
+    cargo_liner!()
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/check/validate_check_issues_iserr.stderr"
+        ]);
+}
+
+#[cargo_test]
+fn validate_check_invalidtoml_iserr() {
+    write_user_config(&["[packages", "abc = '*'"]);
+
+    cargo_liner!()
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/check/validate_check_invalidtoml_iserr.stderr"
+        ]);
+}
+
+#[cargo_test]
+fn validate_check_nofile_iserr() {
+    cargo_liner!()
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/check/validate_check_nofile_iserr.stderr"
+        ]);
+}
+
+#[cargo_test]
+fn validate_check_strict_ship_iserr() {
+    let _reg = init_registry();
+    fixture_fake_publish();
+    write_user_config(&[
+        "strict = true",
+        "[packages]",
+        "abc = { version = '*', lockde = true }",
+    ]);
+
+    cargo_liner!()
+        .arg("ship")
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/check/validate_check_strict_ship_iserr.stderr"
+        ]);
+    assert_not_installed("abc");
+}
+
+#[cargo_test]
+fn validate_check_notstrict_ship_isok() {
+    let _reg = init_registry();
+    fixture_fake_publish();
+    fake_install_self();
+    write_user_config(&["[packages]", "abc = { version = '*', lockde = true }"]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success();
+    assert_installed("abc");
+}
+
+#[cargo_test]
+fn validate_check_layers_iserr() {
+    write_user_config(&["[packages]", "abc = { version = '*', template = 'base' }"]);
+    write_user_config_layer(
+        "liner.d/fragment.toml",
+        &["[templates.base]", "locked = true", "lockd = true"],
+    );
+    write_user_config_layer(
+        "liner.d/other.toml",
+        &["[packages]", "def = { version = '*', template = 'nope' }"],
+    );
+
+    cargo_liner!()
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/check/validate_check_layers_iserr.stderr"
+        ]);
+}
@@ -3,7 +3,6 @@
     reason = "This is testing-only code, so is allowed to be saved \"just in case\"."
 )]
 
-use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fs::{self, File};
 use std::io::Write;
@@ -11,9 +10,7 @@ use std::path::{Path, PathBuf};
 use std::{env, io, iter};
 
 use cargo_test_support::TestEnvCommandExt;
-use cargo_test_support::registry::{
-    HttpServer, Package, RegistryBuilder, Request, Response, TestRegistry,
-};
+use cargo_test_support::registry::{Package, RegistryBuilder, TestRegistry};
 use semver::Version;
 
 /// List of example packages, their respective versions and if they are locally
@@ -39,100 +36,60 @@ macro_rules! cargo_liner {
     }};
 }
 
-/// Initializes the registry without a token, with an HTTP index and API, and
-/// with a custom API responder that mimics `cargo search`'s endpoint.
+/// Initializes the registry without a token and with an HTTP index and API.
 ///
 /// Returns a test registry handle that must be saved in order for the test
 /// server to be kept alive.
 #[must_use]
 pub fn init_registry() -> TestRegistry {
-    // Must be fetched ahead of time, otherwise the underlying global state is
-    // uninitialized for some reason, very probably because the request handler
-    // below is ran in another thread and the global state is thread-local as
-    // each thread normally corresponds to a different test ran in parallel of
-    // others.
-    let test_root = cargo_test_support::paths::root();
     RegistryBuilder::new()
         .no_configure_token()
         .http_api()
         .http_index()
-        .add_responder("/api/v1/crates", move |req: &Request, _srv: &HttpServer| {
-            let req_params = req
-                .url
-                .query_pairs()
-                .map(|(k, v)| (k.into_owned(), v.into_owned()))
-                .collect::<HashMap<_, _>>();
-            // Search mandatory parameter: string query.
-            let req_pkg = req_params.get("q").unwrap();
-            let dl_path = test_root.join("dl");
-
-            // Use the `dl` directory instead of the `registry` one in order to
-            // easily test whether a packaged has previously been published or
-            // not: test if a sub-directory of the package's name exists.
-            #[expect(
-                clippy::allow_attributes,
-                reason = "Unable to make `expect` work here."
-            )]
-            #[allow(clippy::print_stderr, reason = "Testing-only module.")]
-            let (res_len, pkg_res) = dl_path
-                .join(req_pkg)
-                .read_dir()
-                .inspect_err(|err| {
-                    eprintln!(
-                        "Error when reading directory `{req_pkg}` under `{}`: {err:#?}",
-                        dl_path.display(),
-                    );
-                })
-                // Sub-directories have versions as names: take the max.
-                .map(|mut dir_itr| {
-                    dir_itr
-                        .map(|subdir| {
-                            subdir
-                                .unwrap()
-                                .file_name()
-                                .into_string()
-                                .unwrap()
-                                .parse::<Version>()
-                                .unwrap()
-                        })
-                        .max()
-                        .unwrap()
-                })
-                .map_or((0, String::new()), |pkg_ver| {
-                    (
-                        1,
-                        format!(
-                            r#"{{
-                                "name": "{req_pkg}",
-                                "description": "whatever",
-                                "newest_version": "{pkg_ver}",
-                                "max_version": "{pkg_ver}"
-                            }}"#,
-                        ),
-                    )
-                });
-
-            // Return only the information actually required by the `cargo
-            // search` command. Might need to be extended in the future if more
-            // gets required.
-            Response {
-                code: 200,
-                headers: Vec::new(),
-                body: format!(
-                    r#"{{
-                        "crates": [{pkg_res}],
-                        "meta": {{
-                            "next_page": "?q={}&page=2",
-                            "prev_page": null,
-                            "total": {res_len}
-                        }}
-                    }}"#,
-                    req.url.query().unwrap(),
-                )
-                .as_bytes()
-                .to_vec(),
-            }
-        })
+        .build()
+}
+
+/// Initializes an alternative registry named `alternative` without a token and
+/// with an HTTP index and API.
+///
+/// Returns a test registry handle that must be saved in order for the test
+/// server to be kept alive.
+#[must_use]
+pub fn init_alt_registry() -> TestRegistry {
+    RegistryBuilder::new()
+        .alternative()
+        .no_configure_token()
+        .http_api()
+        .http_index()
+        .build()
+}
+
+/// Initializes an alternative registry named `alternative` without a token and
+/// with a Git index and an HTTP API.
+///
+/// Returns a test registry handle that must be saved in order for the test
+/// server to be kept alive.
+#[must_use]
+pub fn init_alt_registry_git() -> TestRegistry {
+    RegistryBuilder::new()
+        .alternative()
+        .no_configure_token()
+        .http_api()
+        .build()
+}
+
+/// Initializes an alternative registry named `alternative` that requires
+/// authentication, with its token configured, and with an HTTP index and API.
+///
+/// Returns a test registry handle that must be saved in order for the test
+/// server to be kept alive.
+#[must_use]
+pub fn init_alt_registry_auth() -> TestRegistry {
+    RegistryBuilder::new()
+        .alternative()
+        .auth_required()
+        .http_api()
+        .http_index()
         .build()
 }
 
@@ -183,6 +140,24 @@ pub fn write_user_config(content_lines: &[&str]) {
     fs::write(user_config_path(), content_lines.join("\n")).unwrap();
 }
 
+/// Writes the given lines to the configuration file found at the given path
+/// relative to Cargo's home, such as a fragment or an included file.
+pub fn write_user_config_layer(rel_path: &str, content_lines: &[&str]) {
+    let path = cargo_test_support::paths::cargo_home().join(rel_path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content_lines.join("\n")).unwrap();
+}
+
+/// Writes the given lines to a configuration file found at the given path
+/// relative to the test root, outside of Cargo's home, and returns its full
+/// path.
+pub fn write_config_file(rel_path: &str, content_lines: &[&str]) -> PathBuf {
+    let path = cargo_test_support::paths::root().join(rel_path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, content_lines.join("\n")).unwrap();
+    path
+}
+
 /// Runs [`write_user_config`] with an example configuration excluding self.
 pub fn fixture_write_user_config() {
     let cfg_pkg_lines = FIXTURE_PACKAGES
@@ -216,6 +191,31 @@ pub fn assert_user_config_eq_path(test_path: impl AsRef<Path>) {
     assert_user_config_eq(&fs::read_to_string(test_path).unwrap());
 }
 
+/// Returns the filesystem path pointing to the lock file next to the user
+/// configuration.
+pub fn user_lock_path() -> PathBuf {
+    user_config_path().with_file_name("liner.lock")
+}
+
+/// Writes the given lines of content to the `$CARGO_HOME/liner.lock` file.
+pub fn write_user_lock(content_lines: &[&str]) {
+    fs::write(user_lock_path(), content_lines.join("\n")).unwrap();
+}
+
+/// Asserts the lock file does not exist.
+#[track_caller]
+pub fn assert_user_lock_absent() {
+    assert!(!user_lock_path().exists());
+}
+
+/// Asserts the lock file's contents match the given expected ones, with the
+/// usual redactions applied.
+#[track_caller]
+pub fn assert_user_lock_eq(expected: impl snapbox::IntoData) {
+    cargo_test_support::compare::assert_ui()
+        .eq(fs::read_to_string(user_lock_path()).unwrap(), expected);
+}
+
 /// Fakes the result of a `cargo install` run for the given package name and
 /// version.
 ///
@@ -224,6 +224,35 @@ pub fn assert_user_config_eq_path(test_path: impl AsRef<Path>) {
 /// package's name and version to the `$CARGO_HOME/.crates.toml` file, creating
 /// it if it does not exist.
 pub fn fake_install(pkg: &str, ver: &str, locally_installed: bool) {
+    fake_install_from(
+        pkg,
+        ver,
+        if locally_installed {
+            "path+file:///a/b/c"
+        } else {
+            "registry+https://github.com/rust-lang/crates.io-index"
+        },
+    );
+}
+
+/// Runs [`fake_install`] but with the package installed from the given Git
+/// repository URL at the given commit.
+pub fn fake_install_git(pkg: &str, ver: &str, repo_url: &str, commit: &str) {
+    fake_install_from(pkg, ver, &format!("git+{repo_url}#{commit}"));
+}
+
+/// Runs [`fake_install`] but with the package installed from the given local
+/// directory.
+pub fn fake_install_path(pkg: &str, ver: &str, path: &Path) {
+    fake_install_from(
+        pkg,
+        ver,
+        &format!("path+{}", url::Url::from_directory_path(path).unwrap()),
+    );
+}
+
+/// Runs [`fake_install`] with the given raw source of `.crates.toml`.
+fn fake_install_from(pkg: &str, ver: &str, source: &str) {
     let pkg_bin = cargo_test_support::install::exe(pkg);
     let tmp_home = cargo_test_support::paths::home();
     let tmp_cargo_home = tmp_home.join(".cargo");
@@ -254,15 +283,18 @@ pub fn fake_install(pkg: &str, ver: &str, locally_installed: bool) {
             .open(tmp_cargo_home_crates)
             .unwrap(),
         "\"{pkg} {ver} ({source})\" = [\"{pkg_bin}\"]",
-        source = if locally_installed {
-            "path+file:///a/b/c"
-        } else {
-            "registry+https://github.com/rust-lang/crates.io-index"
-        }
     )
     .unwrap();
 }
 
+/// Writes the given contents to the `$CARGO_HOME/.crates2.json` file, where
+/// Cargo records the options the packages were installed with.
+pub fn write_cargo_crates2(content: &str) {
+    let tmp_cargo_home = cargo_test_support::paths::cargo_home();
+    fs::create_dir_all(&tmp_cargo_home).unwrap();
+    fs::write(tmp_cargo_home.join(".crates2.json"), content).unwrap();
+}
+
 /// Runs [`fake_install`] and [`assert_installed`] with the current crate as
 /// the package and a default version.
 pub fn fake_install_self() {
@@ -348,6 +380,35 @@ pub fn break_fake_installation_all(pkgs: impl IntoIterator<Item = &'static str>)
     }
 }
 
+/// Creates a local Git repository containing a minimal package of the given
+/// name and version.
+///
+/// Returns the URL of the repository and the hash of its only commit.
+pub fn fake_git_repo(pkg: &str, ver: &str) -> (String, String) {
+    let (project, repo) = cargo_test_support::git::new_repo(pkg, |project| {
+        project
+            .file("Cargo.toml", &cargo_test_support::basic_manifest(pkg, ver))
+            .file("src/main.rs", "fn main() {}")
+    });
+    (
+        project.url().to_string(),
+        repo.head().unwrap().target().unwrap().to_string(),
+    )
+}
+
+/// Creates a local directory containing a minimal package of the given name
+/// and version.
+///
+/// Returns the path of the package's directory.
+pub fn fake_local_package(pkg: &str, ver: &str) -> PathBuf {
+    cargo_test_support::project()
+        .at(pkg)
+        .file("Cargo.toml", &cargo_test_support::basic_manifest(pkg, ver))
+        .file("src/main.rs", "fn main() {}")
+        .build()
+        .root()
+}
+
 /// Publishes the given package name and version to the local fake registry
 /// with minimal contents.
 pub fn fake_publish(pkg: &str, ver: &str) {
@@ -358,6 +419,14 @@ pub fn fake_publish(pkg: &str, ver: &str) {
 
 /// Runs [`fake_publish`] for each package name and version pair yielded by the
 /// given iterator.
+/// Publishes a dummy package in the alternative registry.
+pub fn fake_publish_alt(pkg: &str, ver: &str) {
+    Package::new(pkg, ver)
+        .file("src/main.rs", "fn main() {}")
+        .alternative(true)
+        .publish();
+}
+
 pub fn fake_publish_all<'p, 'v>(pkg_vers: impl IntoIterator<Item = (&'p str, &'v str)>) {
     for (pkg, ver) in pkg_vers {
         fake_publish(pkg, ver);
@@ -1,4 +1,5 @@
 use cargo_test_macro::cargo_test;
+use indoc::indoc;
 use snapbox::IntoData;
 
 mod common;
@@ -128,6 +129,91 @@ fn validate_import_patch_keepself() {
     assert_user_config_eq_path("tests/fixtures/import/validate_import_patch_keepself.outconfig");
 }
 
+#[cargo_test]
+fn validate_import_crates2json_options() {
+    fixture_fake_install();
+    write_cargo_crates2(&format!(
+        r#"{{
+            "installs": {{
+                "abc 0.0.1 (registry+https://github.com/rust-lang/crates.io-index)": {{
+                    "bins": ["abc"],
+                    "features": ["b", "a"],
+                    "all_features": false,
+                    "no_default_features": true,
+                    "profile": "dev",
+                    "target": "{host}"
+                }},
+                "def 0.0.2 (path+file:///a/b/c)": {{
+                    "bins": ["def"],
+                    "features": [],
+                    "all_features": true,
+                    "no_default_features": false,
+                    "profile": "release",
+                    "target": "example-target-triple-string"
+                }},
+                "cargo-liner 0.0.3 (registry+https://github.com/rust-lang/crates.io-index)": {{
+                    "bins": ["cargo-liner"],
+                    "features": [],
+                    "all_features": false,
+                    "no_default_features": false,
+                    "profile": "release",
+                    "target": "{host}"
+                }}
+            }}
+        }}"#,
+        host = cargo_test_support::rustc_host(),
+    ));
+
+    cargo_liner!()
+        .args(["import", "--exact", "--keep-self", "--keep-local"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file!["fixtures/import/validate_import.stderr"].raw());
+    assert_user_config_eq(indoc!(
+        r#"
+        [packages]
+        abc = { version = "=0.0.1", default-features = false, features = ["a", "b"], extra-arguments = ["--profile", "dev"] }
+        cargo-liner = "=0.0.3"
+        def = { version = "=0.0.2", all-features = true, target = "example-target-triple-string" }
+        "#
+    ));
+}
+
+#[cargo_test]
+fn validate_import_crates2json_bins_dropped() {
+    fixture_fake_install();
+    write_cargo_crates2(&format!(
+        r#"{{
+            "installs": {{
+                "abc 0.0.1 (registry+https://github.com/rust-lang/crates.io-index)": {{
+                    "bins": ["abc-cli{exe}", "abc-gui{exe}"],
+                    "features": [],
+                    "all_features": false,
+                    "no_default_features": false,
+                    "profile": "release",
+                    "target": "{host}"
+                }}
+            }}
+        }}"#,
+        host = cargo_test_support::rustc_host(),
+        exe = std::env::consts::EXE_SUFFIX,
+    ));
+
+    cargo_liner!()
+        .args(["import", "--exact"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file!["fixtures/import/validate_import.stderr"].raw());
+    assert_user_config_eq(indoc!(
+        r#"
+        [packages]
+        abc = "=0.0.1"
+        "#
+    ));
+}
+
 #[cargo_test]
 fn validate_import_force_nofile_isok() {
     fixture_fake_install();
@@ -154,6 +240,57 @@ fn validate_import_force_withfile_iswarn() {
     assert_user_config_eq_path("tests/fixtures/import/validate_import.outconfig");
 }
 
+#[cargo_test]
+fn validate_import_force_withfile_keepsformat() {
+    fixture_fake_install();
+    write_user_config(&[
+        "# My tools.",
+        "[packages]",
+        "# Searching.",
+        "abc = '*'   # Always the latest.",
+        "ghi = { version = '1', locked = true }",
+        "",
+        "# Defaults.",
+        "[defaults.ship]",
+        "no-self = true",
+    ]);
+
+    cargo_liner!()
+        .args(["import", "--force", "--exact", "--keep-local"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file!["fixtures/import/validate_import_force_warn.stderr"].raw());
+    assert_user_config_eq(indoc!(
+        r#"
+        # My tools.
+        [packages]
+        # Searching.
+        abc = "=0.0.1"   # Always the latest.
+        def = "=0.0.2"
+        "#
+    ));
+}
+
+#[cargo_test]
+fn validate_import_force_withinvalidfile_overwrites() {
+    fixture_fake_install();
+    write_user_config(&["# Broken.", "[packages", "abc = '*'"]);
+
+    cargo_liner!()
+        .args(["import", "--force"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(
+            snapbox::file![
+                "fixtures/import/validate_import_force_withinvalidfile_overwrites.stderr"
+            ]
+            .raw(),
+        );
+    assert_user_config_eq_path("tests/fixtures/import/validate_import.outconfig");
+}
+
 #[cargo_test]
 fn validate_import_noforce_withfile_iserr() {
     fixture_fake_install();
@@ -170,6 +307,38 @@ fn validate_import_noforce_withfile_iserr() {
     assert_user_config_eq("[packages]");
 }
 
+#[cargo_test]
+fn validate_import_config_path() {
+    fixture_fake_install();
+    let path = cargo_test_support::paths::root().join("ci-tools.toml");
+    cargo_liner!()
+        .args(["import", "--config"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file!["fixtures/import/validate_import.stderr"].raw());
+    assert_eq!(
+        std::fs::read_to_string(path).unwrap(),
+        std::fs::read_to_string("tests/fixtures/import/validate_import.outconfig").unwrap(),
+    );
+    assert_user_config_absent();
+}
+
+#[cargo_test]
+fn validate_import_config_stdin_iserr() {
+    fixture_fake_install();
+    cargo_liner!()
+        .args(["import", "--config", "-"])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/import/validate_import_config_stdin_iserr.stderr"
+        ]);
+    assert_user_config_absent();
+}
+
 #[cargo_test]
 fn validate_import_nofile_iserr() {
     cargo_liner!()
@@ -327,3 +496,15 @@ fn validate_import_verbosity_qqq_noforce() {
         .stderr_eq("".into_data().raw());
     assert_user_config_eq("[packages]");
 }
+
+#[cargo_test]
+fn validate_import_messageformatjson() {
+    fixture_fake_install();
+    cargo_liner!()
+        .args(["import", "--message-format", "json"])
+        .assert()
+        .success()
+        .stdout_eq(snapbox::file!["fixtures/import/validate_import_messageformatjson.stdout"].raw())
+        .stderr_eq(snapbox::file!["fixtures/import/validate_import.stderr"].raw());
+    assert_user_config_eq_path("tests/fixtures/import/validate_import.outconfig");
+}
@@ -110,6 +110,44 @@ fn validate_jettison_uninstall_some() {
     assert_installed_all(["jkl", "mno", "pqr"]);
 }
 
+#[cargo_test]
+fn validate_jettison_overlays_applied() {
+    fake_install_self();
+    fake_install_all([
+        ("abc", "0.0.0", false),
+        ("def", "0.0.0", false),
+        ("ghi", "0.0.0", false),
+        ("jkl", "0.0.0", false),
+    ]);
+    assert_installed_all(["abc", "def", "ghi", "jkl"]);
+    write_user_config(&[
+        "[packages]",
+        "def = '*'",
+        "[target.'cfg(all())'.packages]",
+        "ghi = '*'",
+        "[target.'cfg(any())'.packages]",
+        "abc = '*'",
+        &format!(
+            "[hosts.'{}'.packages]",
+            gethostname::gethostname().to_string_lossy()
+        ),
+        "jkl = '*'",
+        "[hosts.other-host.packages]",
+        "abc = '*'",
+    ]);
+
+    cargo_liner!()
+        .arg("jettison")
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/jettison/validate_jettison_uninstall_one.stderr"
+        ]);
+    assert_not_installed("abc");
+    assert_installed_all(["def", "ghi", "jkl"]);
+}
+
 #[cargo_test]
 fn validate_jettison_verbosity_and_color() {
     fake_install_self();
@@ -610,3 +648,64 @@ fn validate_jettison_confignodryrun_envnodryrun_clinodryrun_isnodryrun() {
         ]);
     assert_not_installed_all(["abc", "def", "ghi"]);
 }
+
+#[cargo_test]
+fn validate_jettison_messageformatjson_dryrun() {
+    fake_install_self();
+    fake_install_all([
+        ("abc", "0.0.0", false),
+        ("def", "0.0.0", false),
+        ("ghi", "0.0.0", false),
+    ]);
+    write_user_config(&["[packages]"]);
+
+    cargo_liner!()
+        .args(["jettison", "--dry-run", "--message-format", "json"])
+        .assert()
+        .success()
+        .stdout_eq(snapbox::file![
+            "fixtures/jettison/validate_jettison_messageformatjson_dryrun.stdout"
+        ])
+        .stderr_eq(snapbox::file![
+            "fixtures/jettison/validate_jettison_dryrun.stderr"
+        ]);
+    assert_installed_all(["abc", "def", "ghi"]);
+}
+
+#[cargo_test]
+fn validate_jettison_config_path() {
+    fake_install_self();
+    fake_install_all([("abc", "0.0.0", false), ("def", "0.0.0", false)]);
+    write_user_config(&["[packages]", "abc = '*'"]);
+    let path = write_config_file("ci-tools.toml", &["[packages]", "def = '*'"]);
+
+    cargo_liner!()
+        .args(["jettison", "--no-confirm", "--config"])
+        .arg(path)
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/jettison/validate_jettison_config_path.stderr"
+        ]);
+    assert_not_installed("abc");
+    assert_installed("def");
+}
+
+#[cargo_test]
+fn validate_jettison_config_stdin_confirm_iserr() {
+    fake_install_self();
+    fake_install("abc", "0.0.0", false);
+    write_user_config(&["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .args(["jettison", "--config", "-"])
+        .stdin("[packages]\n")
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/jettison/validate_jettison_config_stdin_confirm_iserr.stderr"
+        ]);
+    assert_installed("abc");
+}
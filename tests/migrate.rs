@@ -0,0 +1,61 @@
+use cargo_test_macro::cargo_test;
+use snapbox::IntoData;
+
+mod common;
+use common::*;
+
+#[cargo_test]
+fn validate_migrate_uptodate() {
+    for config_lines in [
+        &["[packages]", "abc = '*' # Comment."][..],
+        &["version = 1", "[packages]", "abc = '*'"],
+    ] {
+        write_user_config(config_lines);
+        cargo_liner!()
+            .arg("migrate")
+            .assert()
+            .success()
+            .stdout_eq("".into_data().raw())
+            .stderr_eq(snapbox::file!["fixtures/migrate/validate_migrate_uptodate.stderr"].raw());
+        assert_user_config_eq(&config_lines.join("\n"));
+    }
+}
+
+#[cargo_test]
+fn validate_migrate_uptodate_dryrun() {
+    write_user_config(&["[packages]", "abc = '*'"]);
+    cargo_liner!()
+        .args(["migrate", "--dry-run"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file!["fixtures/migrate/validate_migrate_uptodate.stderr"].raw());
+    assert_user_config_eq("[packages]\nabc = '*'");
+}
+
+#[cargo_test]
+fn validate_migrate_newer_iserr() {
+    write_user_config(&["version = 2", "[packages]", "abc = '*'"]);
+    cargo_liner!()
+        .arg("migrate")
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/migrate/validate_migrate_newer_iserr.stderr"
+        ]);
+    assert_user_config_eq("version = 2\n[packages]\nabc = '*'");
+}
+
+#[cargo_test]
+fn validate_migrate_nofile_iserr() {
+    cargo_liner!()
+        .arg("migrate")
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/migrate/validate_migrate_nofile_iserr.stderr"
+        ]);
+    assert_user_config_absent();
+}
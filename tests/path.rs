@@ -0,0 +1,49 @@
+use std::env;
+use std::path::PathBuf;
+
+use cargo_test_macro::cargo_test;
+use snapbox::IntoData;
+
+mod common;
+use common::*;
+
+/// Returns the expected output for the given binaries directory prepended to
+/// a fixed `PATH`.
+fn expected_path(bin_dir: PathBuf) -> String {
+    let mut path = env::join_paths([bin_dir, PathBuf::from("/usr/bin")])
+        .unwrap()
+        .into_string()
+        .unwrap();
+    path.push('\n');
+    path
+}
+
+#[cargo_test]
+fn validate_path_global() {
+    write_user_config(&["[packages]"]);
+
+    cargo_liner!()
+        .arg("path")
+        .env("PATH", "/usr/bin")
+        .assert()
+        .success()
+        .stdout_eq(expected_path(cargo_test_support::paths::cargo_home().join("bin")).raw())
+        .stderr_eq(" INFO  cargo_liner > Done.\n".into_data().raw());
+}
+
+#[cargo_test]
+fn validate_path_project() {
+    let path = write_config_file("proj/liner.toml", &["[packages]"]);
+    let bin_dir = path.with_file_name(".cargo-liner").join("bin");
+    let cur_path = env::join_paths([bin_dir.clone(), PathBuf::from("/usr/bin")]).unwrap();
+
+    // Already present: not repeated.
+    cargo_liner!()
+        .current_dir(path.parent().unwrap())
+        .arg("path")
+        .env("PATH", cur_path)
+        .assert()
+        .success()
+        .stdout_eq(expected_path(bin_dir).raw())
+        .stderr_eq(" INFO  cargo_liner > Done.\n".into_data().raw());
+}
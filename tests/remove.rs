@@ -0,0 +1,110 @@
+use cargo_test_macro::cargo_test;
+use indoc::indoc;
+use snapbox::IntoData;
+
+mod common;
+use common::*;
+
+#[cargo_test]
+fn validate_remove_keepsformat() {
+    write_user_config(&[
+        "# Tools.",
+        "[packages]",
+        "abc = '*' # Pinned later.",
+        "def = { version = '1', locked = true }",
+        "",
+        "[packages.ghi]",
+        "version = '*'",
+        "",
+        "# Defaults.",
+        "[defaults.ship]",
+        "no-self = true",
+    ]);
+
+    cargo_liner!()
+        .args(["remove", "def"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file!["fixtures/remove/validate_remove_keepsformat.stderr"].raw());
+    cargo_liner!()
+        .args(["remove", "ghi"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw());
+    assert_user_config_eq(indoc!(
+        "
+        # Tools.
+        [packages]
+        abc = '*' # Pinned later.
+
+        # Defaults.
+        [defaults.ship]
+        no-self = true
+        "
+    ));
+}
+
+#[cargo_test]
+fn validate_remove_notconfigured_iserr() {
+    write_user_config(&["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .args(["remove", "def"])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(
+            snapbox::file!["fixtures/remove/validate_remove_notconfigured_iserr.stderr"].raw(),
+        );
+    assert_user_config_eq("[packages]\nabc = '*'");
+}
+
+#[cargo_test]
+fn validate_remove_keepsinstalled() {
+    fake_install("abc", "0.0.1", false);
+    write_user_config(&["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .args(["remove", "abc"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw());
+    assert_user_config_eq("[packages]\n");
+    assert_installed("abc");
+}
+
+#[cargo_test]
+fn validate_remove_uninstall() {
+    fake_install("abc", "0.0.1", false);
+    write_user_config(&["[packages]", "abc = '*'", "def = '*'"]);
+
+    cargo_liner!()
+        .args(["remove", "abc", "--uninstall"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/remove/validate_remove_uninstall.stderr"
+        ]);
+    assert_user_config_eq("[packages]\ndef = '*'\n");
+    assert_not_installed("abc");
+}
+
+#[cargo_test]
+fn validate_remove_uninstall_notinstalled_warns() {
+    fake_install("def", "0.0.1", false);
+    write_user_config(&["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .args(["remove", "abc", "--uninstall"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(
+            snapbox::file!["fixtures/remove/validate_remove_uninstall_notinstalled_warns.stderr"]
+                .raw(),
+        );
+    assert_user_config_eq("[packages]\n");
+    assert_installed("def");
+}
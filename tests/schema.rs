@@ -0,0 +1,15 @@
+use cargo_test_macro::cargo_test;
+
+mod common;
+
+/// Fails whenever the configuration changes, such as a field being added to
+/// the package requirements, without this snapshot being updated as well.
+#[cargo_test]
+fn validate_schema() {
+    cargo_liner!()
+        .arg("schema")
+        .assert()
+        .success()
+        .stdout_eq(snapbox::file!["fixtures/schema/validate_schema.stdout"].raw())
+        .stderr_eq(snapbox::file!["fixtures/schema/validate_schema.stderr"].raw());
+}
@@ -1,6 +1,5 @@
 use cargo_test_macro::cargo_test;
 use cargo_test_support::registry::Package;
-use indoc::indoc;
 use snapbox::IntoData;
 use tempfile::TempDir;
 
@@ -155,7 +154,7 @@ fn validate_ship_noself_force_nothing() {
     write_user_config(&["[packages]"]);
 
     cargo_liner!()
-        .args(["ship", "--no-self", "--force"])
+        .args(["ship", "--no-self"])
         .assert()
         .success()
         .stdout_eq("".into_data().raw())
@@ -209,7 +208,7 @@ fn validate_ship_skipcheck_noself_force_nothing() {
     write_user_config(&["[packages]"]);
 
     cargo_liner!()
-        .args(["ship", "--skip-check", "--no-self", "--force"])
+        .args(["ship", "--skip-check", "--no-self"])
         .assert()
         .success()
         .stdout_eq("".into_data().raw())
@@ -940,15 +939,7 @@ fn validate_ship_verbosity_qqq() {
         .assert()
         .success()
         .stdout_eq("".into_data().raw())
-        .stderr_eq(
-            indoc!(
-                "
-                    Updating `dummy-registry` index
-                note: to learn more about a package, run `cargo info <name>`
-                "
-            )
-            .raw(),
-        );
+        .stderr_eq("".into_data().raw());
     assert_installed("pkg");
 }
 
@@ -1008,9 +999,11 @@ fn validate_ship_cargoinstallroot_supported() {
     write_user_config(&["[packages]", "pkg = '*'"]);
 
     let tmp_dir = TempDir::new().unwrap();
+    // The install root is now correctly detected under testing as well.
+    std::fs::write(tmp_dir.path().join(".crates.toml"), "[v1]\n").unwrap();
     cargo_liner!()
         .env("CARGO_INSTALL_ROOT", tmp_dir.path())
-        .args(["ship", "--no-self", "--force"])
+        .args(["ship", "--no-self"])
         .assert()
         .success()
         .stdout_eq("".into_data().raw())
@@ -1487,6 +1480,214 @@ fn validate_ship_locked() {
     assert_installed("abc");
 }
 
+#[cargo_test]
+fn validate_ship_defaultspackage_locked() {
+    let _reg = init_registry();
+    fake_install_self();
+    Package::new("abc", "0.0.0")
+        .file("src/main.rs", "fn main() {}")
+        .dep("def", "0.0.0")
+        .publish();
+    Package::new("def", "0.0.0")
+        .file("src/lib.rs", "")
+        .publish();
+    // HACK: observe the warning to confirm the argument is passed.
+    write_user_config(&[
+        "[packages]",
+        "abc = '*'",
+        "[defaults.package]",
+        "locked = true",
+    ]);
+
+    cargo_liner!()
+        .args(["-q", "ship", "--skip-check", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file!["fixtures/ship/validate_ship_locked.stderr"]);
+    assert_installed("abc");
+}
+
+#[cargo_test]
+fn validate_ship_defaultspackage_environment_merged() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish("abc", "0.0.0");
+    write_user_config(&[
+        "[packages]",
+        "abc = { version = '*', environment = { CARGO_TERM_QUIET = 'true' } }",
+        "[defaults.package]",
+        // HACK: the package's value must win for the output to stay quiet.
+        "environment = { CARGO_TERM_QUIET = 'false', CARGO_TERM_COLOR = 'never' }",
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--skip-check", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file!["fixtures/ship/validate_ship_extrargs_list.stderr"].raw());
+    assert_installed("abc");
+}
+
+#[cargo_test]
+fn validate_ship_template_locked() {
+    let _reg = init_registry();
+    fake_install_self();
+    Package::new("abc", "0.0.0")
+        .file("src/main.rs", "fn main() {}")
+        .dep("def", "0.0.0")
+        .publish();
+    Package::new("def", "0.0.0")
+        .file("src/lib.rs", "")
+        .publish();
+    // HACK: observe the warning to confirm the argument is passed.
+    write_user_config(&[
+        "[packages]",
+        "abc = { version = '*', template = 'strict' }",
+        "[templates.base]",
+        "locked = true",
+        "[templates.strict]",
+        "template = ['base']",
+    ]);
+
+    cargo_liner!()
+        .args(["-q", "ship", "--skip-check", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file!["fixtures/ship/validate_ship_locked.stderr"]);
+    assert_installed("abc");
+}
+
+#[cargo_test]
+fn validate_ship_template_cycle_iserr() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish("abc", "0.0.0");
+    write_user_config(&[
+        "[packages]",
+        "abc = { version = '*', template = 'a' }",
+        "[templates]",
+        "a = { template = 'b' }",
+        "b = { template = 'a' }",
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--skip-check", "--no-self"])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_template_cycle_iserr.stderr"
+        ]);
+    assert_not_installed("abc");
+}
+
+#[cargo_test]
+fn validate_ship_layers_merged() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.0"), ("def", "0.0.0"), ("ghi", "0.0.0")]);
+    write_user_config(&[
+        "include = ['other/included.toml']",
+        "[packages]",
+        "abc = '*'",
+        "def = '*'",
+    ]);
+    write_user_config_layer("liner.d/fragment.toml", &["[packages]", "ghi = '*'"]);
+    write_user_config_layer("other/included.toml", &["exclude = ['def']"]);
+
+    cargo_liner!()
+        .args(["-q", "ship", "--skip-check", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_layers_merged.stderr"
+        ]);
+    assert_installed_all(["abc", "ghi"]);
+    assert_not_installed("def");
+}
+
+#[cargo_test]
+fn validate_ship_config_path() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.0"), ("def", "0.0.0")]);
+    write_user_config(&["[packages]", "def = '*'"]);
+    let path = write_config_file("ci-tools.toml", &["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .args(["-q", "ship", "--skip-check", "--no-self", "--config"])
+        .arg(path)
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_config_path.stderr"
+        ]);
+    assert_installed("abc");
+    assert_not_installed("def");
+}
+
+#[cargo_test]
+fn validate_ship_config_env() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.0"), ("def", "0.0.0")]);
+    write_user_config(&["[packages]", "def = '*'"]);
+    let path = write_config_file("ci-tools.toml", &["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .args(["-q", "ship", "--skip-check", "--no-self"])
+        .env("CARGO_LINER_CONFIG", path)
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_config_path.stderr"
+        ]);
+    assert_installed("abc");
+    assert_not_installed("def");
+}
+
+#[cargo_test]
+fn validate_ship_config_stdin() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.0"), ("def", "0.0.0")]);
+    write_user_config(&["[packages]", "def = '*'"]);
+
+    cargo_liner!()
+        .args(["-q", "ship", "--skip-check", "--no-self", "--config", "-"])
+        .stdin("[packages]\nabc = '*'\n")
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_config_path.stderr"
+        ]);
+    assert_installed("abc");
+    assert_not_installed("def");
+}
+
+#[cargo_test]
+fn validate_ship_config_missing_iserr() {
+    let _reg = init_registry();
+    fake_install_self();
+    write_user_config(&["[packages]"]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self", "--config", "missing.toml"])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_config_missing_iserr.stderr"
+        ]);
+}
+
 #[cargo_test]
 fn validate_ship_offline() {
     let _reg = init_registry();
@@ -2038,139 +2239,118 @@ fn validate_ship_install_target_config() {
 
 /// See #27.
 #[cargo_test]
-fn validate_ship_path_is_skipcheck() {
+fn validate_ship_path_sameversion_noupdate() {
     let _reg = init_registry();
-    fake_install("abc", "0.0.0", true);
-    fake_publish("abc", "0.0.1");
-    write_user_config(&["[packages]", "abc = { version = '*', path = '/a' }"]);
+    let pkg_path = fake_local_package("abc", "0.0.0");
+    fake_install_path("abc", "0.0.0", &pkg_path);
+    write_user_config(&[
+        "[packages]",
+        &format!("abc = {{ version = '*', path = '{}' }}", pkg_path.display()),
+    ]);
 
-    // HACK: validate that the error reveals the intended behavior.
     cargo_liner!()
         .args(["ship", "--no-self"])
         .assert()
-        .failure()
+        .success()
         .stdout_eq("".into_data().raw())
         .stderr_eq(snapbox::file![
-            "fixtures/ship/validate_ship_path_is_skipcheck.stderr"
+            "fixtures/ship/validate_ship_path_sameversion_noupdate.stderr"
         ]);
     assert_installed("abc");
 }
 
-/// See #27.
 #[cargo_test]
-fn validate_ship_git_is_skipcheck() {
+fn validate_ship_path_newversion_update() {
     let _reg = init_registry();
-    fake_install("abc", "0.0.0", true);
-    fake_publish("abc", "0.0.1");
+    let pkg_path = fake_local_package("abc", "0.0.1");
+    fake_install_path("abc", "0.0.0", &pkg_path);
     write_user_config(&[
         "[packages]",
-        "abc = { version = '*', git = 'git@example.com:user/repo.git' }",
+        &format!("abc = {{ version = '*', path = '{}' }}", pkg_path.display()),
     ]);
 
-    // HACK: validate that the error reveals the intended behavior.
     cargo_liner!()
         .args(["ship", "--no-self"])
         .assert()
-        .failure()
+        .success()
         .stdout_eq("".into_data().raw())
         .stderr_eq(snapbox::file![
-            "fixtures/ship/validate_ship_git_is_skipcheck.stderr"
+            "fixtures/ship/validate_ship_path_newversion_update.stderr"
         ]);
     assert_installed("abc");
 }
 
-/// Test that the tool is at least called when using `always` globally.
-///
-/// Binstall seems to outright refuse http URLs, so it is rather incompatible
-/// with the execution context used here. Just at least validating the tool is
-/// indeed called seems to be the only possibility here.
-#[cargo_test(requires = "cargo-binstall")]
-fn validate_ship_binstall_globalalways_isused() {
+#[cargo_test]
+fn validate_ship_path_otherpath_update() {
     let _reg = init_registry();
-    fake_install_self();
-    fake_publish("abc", "0.0.0");
+    let pkg_path = fake_local_package("abc", "0.0.0");
+    fake_install("abc", "0.0.0", true);
     write_user_config(&[
-        "[defaults]",
-        "ship.binstall = 'always'",
         "[packages]",
-        // Explicitly passing the registry is necessary as otherwise crates.io
-        // is implicitly used by the tool that does not have the same hacks.
-        "abc = { version = '*', registry = 'dummy-registry' }",
+        &format!("abc = {{ version = '*', path = '{}' }}", pkg_path.display()),
     ]);
 
     cargo_liner!()
         .args(["ship", "--no-self"])
         .assert()
-        .failure()
+        .success()
         .stdout_eq("".into_data().raw())
         .stderr_eq(snapbox::file![
-            "fixtures/ship/validate_ship_binstall_globalalways_isused.stderr"
+            "fixtures/ship/validate_ship_path_otherpath_update.stderr"
         ]);
-    assert_not_installed("abc");
+    assert_installed("abc");
 }
 
-/// Test that the tool is not used when using `never` globally.
-///
-/// This is the same as if only letting `auto` since it is disabled under tests.
-#[cargo_test(requires = "cargo-binstall")]
-fn validate_ship_binstall_globalnever_isunused() {
+#[cargo_test]
+fn validate_ship_path_nomanifest_iserr() {
     let _reg = init_registry();
-    fake_install_self();
-    fake_publish("abc", "0.0.0");
-    write_user_config(&[
-        "[defaults]",
-        "ship.binstall = 'never'",
-        "[packages]",
-        "abc = { version = '*', registry = 'dummy-registry' }",
-    ]);
+    fake_install("abc", "0.0.0", true);
+    write_user_config(&["[packages]", "abc = { version = '*', path = '/a' }"]);
 
     cargo_liner!()
         .args(["ship", "--no-self"])
         .assert()
-        .success()
+        .failure()
         .stdout_eq("".into_data().raw())
         .stderr_eq(snapbox::file![
-            "fixtures/ship/validate_ship_binstall_globalnever_isunused.stderr"
+            "fixtures/ship/validate_ship_path_nomanifest_iserr.stderr"
         ]);
     assert_installed("abc");
 }
 
-/// Test that the per-package configuration has precedence over the global one.
-#[cargo_test(requires = "cargo-binstall")]
-fn validate_ship_binstall_globalnever_localalways_isused() {
+#[cargo_test]
+fn validate_ship_path_brokenmanifest_nofailfast_isskipped() {
     let _reg = init_registry();
-    fake_install_self();
-    fake_publish("abc", "0.0.0");
+    fake_publish("def", "0.0.0");
+    let pkg_path = fake_local_package("abc", "0.0.0");
+    fake_install_path("abc", "0.0.0", &pkg_path);
+    std::fs::write(pkg_path.join("Cargo.toml"), "[package").unwrap();
     write_user_config(&[
-        "[defaults]",
-        "ship.binstall = 'never'",
         "[packages]",
-        "abc = { version = '*', binstall = 'always', registry = 'dummy-registry' }",
+        &format!("abc = {{ version = '*', path = '{}' }}", pkg_path.display()),
+        "def = '*'",
     ]);
 
     cargo_liner!()
-        .args(["ship", "--no-self"])
+        .args(["ship", "--no-self", "--no-fail-fast"])
         .assert()
-        .failure()
+        .success()
         .stdout_eq("".into_data().raw())
-        // Same as above.
         .stderr_eq(snapbox::file![
-            "fixtures/ship/validate_ship_binstall_globalalways_isused.stderr"
+            "fixtures/ship/validate_ship_path_brokenmanifest_nofailfast_isskipped.stderr"
         ]);
-    assert_not_installed("abc");
+    assert_installed_all(["abc", "def"]);
 }
 
-/// Test that the per-package configuration has precedence over the global one.
-#[cargo_test(requires = "cargo-binstall")]
-fn validate_ship_binstall_globalalways_localnever_isunused() {
+/// See #27.
+#[cargo_test]
+fn validate_ship_git_uptodate_noupdate() {
     let _reg = init_registry();
-    fake_install_self();
-    fake_publish("abc", "0.0.0");
+    let (repo_url, commit) = fake_git_repo("abc", "0.0.0");
+    fake_install_git("abc", "0.0.0", &repo_url, &commit);
     write_user_config(&[
-        "[defaults]",
-        "ship.binstall = 'always'",
         "[packages]",
-        "abc = { version = '*', binstall = 'never', registry = 'dummy-registry' }",
+        &format!("abc = {{ version = '*', git = '{repo_url}' }}"),
     ]);
 
     cargo_liner!()
@@ -2178,29 +2358,228 @@ fn validate_ship_binstall_globalalways_localnever_isunused() {
         .assert()
         .success()
         .stdout_eq("".into_data().raw())
-        // Same as above.
         .stderr_eq(snapbox::file![
-            "fixtures/ship/validate_ship_binstall_globalnever_isunused.stderr"
+            "fixtures/ship/validate_ship_git_uptodate_noupdate.stderr"
         ]);
     assert_installed("abc");
 }
 
-/// Test `--dry-run` for the Binstall method.
-#[cargo_test(requires = "cargo-binstall")]
-fn validate_ship_binstall_dryrun() {
+#[cargo_test]
+fn validate_ship_git_newcommit_update() {
     let _reg = init_registry();
-    fake_install_self();
-    fake_publish("abc", "0.0.0");
+    let (repo_url, _) = fake_git_repo("abc", "0.0.0");
+    fake_install_git(
+        "abc",
+        "0.0.0",
+        &repo_url,
+        "0123456789abcdef0123456789abcdef01234567",
+    );
     write_user_config(&[
-        "[defaults]",
-        "ship.binstall = 'always'",
         "[packages]",
-        "abc = { version = '*', registry = 'dummy-registry' }",
+        &format!("abc = {{ version = '*', git = '{repo_url}' }}"),
     ]);
 
-    // It fails here, but the logs show the option is passed.
     cargo_liner!()
-        .args(["ship", "--no-self", "--dry-run"])
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_git_newcommit_update.stderr"
+        ]);
+    assert_installed("abc");
+}
+
+#[cargo_test]
+fn validate_ship_git_notinstalled_install() {
+    let _reg = init_registry();
+    fake_install_self();
+    let (repo_url, _) = fake_git_repo("abc", "0.0.0");
+    write_user_config(&[
+        "[packages]",
+        &format!("abc = {{ version = '*', git = '{repo_url}', branch = 'master' }}"),
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_git_notinstalled_install.stderr"
+        ]);
+    assert_installed("abc");
+}
+
+#[cargo_test]
+fn validate_ship_git_unknownbranch_iserr() {
+    let _reg = init_registry();
+    let (repo_url, commit) = fake_git_repo("abc", "0.0.0");
+    fake_install_git("abc", "0.0.0", &repo_url, &commit);
+    write_user_config(&[
+        "[packages]",
+        &format!("abc = {{ version = '*', git = '{repo_url}', branch = 'none' }}"),
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_git_unknownbranch_iserr.stderr"
+        ]);
+    assert_installed("abc");
+}
+
+/// A full commit hash needs no remote access.
+#[cargo_test]
+fn validate_ship_git_revcommit_noupdate() {
+    let _reg = init_registry();
+    fake_install_git(
+        "abc",
+        "0.0.0",
+        "ssh://git@example.com/user/repo.git?rev=0123456",
+        "0123456789abcdef0123456789abcdef01234567",
+    );
+    write_user_config(&[
+        "[packages]",
+        "abc = { version = '*', git = 'git@example.com:user/repo.git', rev = '0123456' }",
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_git_revcommit_noupdate.stderr"
+        ]);
+    assert_installed("abc");
+}
+
+/// Test that the tool is at least called when using `always` globally.
+///
+/// Binstall seems to outright refuse http URLs, so it is rather incompatible
+/// with the execution context used here. Just at least validating the tool is
+/// indeed called seems to be the only possibility here.
+#[cargo_test(requires = "cargo-binstall")]
+fn validate_ship_binstall_globalalways_isused() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish("abc", "0.0.0");
+    write_user_config(&[
+        "[defaults]",
+        "ship.binstall = 'always'",
+        "[packages]",
+        // Explicitly passing the registry is necessary as otherwise crates.io
+        // is implicitly used by the tool that does not have the same hacks.
+        "abc = { version = '*', registry = 'dummy-registry' }",
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_binstall_globalalways_isused.stderr"
+        ]);
+    assert_not_installed("abc");
+}
+
+/// Test that the tool is not used when using `never` globally.
+///
+/// This is the same as if only letting `auto` since it is disabled under tests.
+#[cargo_test(requires = "cargo-binstall")]
+fn validate_ship_binstall_globalnever_isunused() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish("abc", "0.0.0");
+    write_user_config(&[
+        "[defaults]",
+        "ship.binstall = 'never'",
+        "[packages]",
+        "abc = { version = '*', registry = 'dummy-registry' }",
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_binstall_globalnever_isunused.stderr"
+        ]);
+    assert_installed("abc");
+}
+
+/// Test that the per-package configuration has precedence over the global one.
+#[cargo_test(requires = "cargo-binstall")]
+fn validate_ship_binstall_globalnever_localalways_isused() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish("abc", "0.0.0");
+    write_user_config(&[
+        "[defaults]",
+        "ship.binstall = 'never'",
+        "[packages]",
+        "abc = { version = '*', binstall = 'always', registry = 'dummy-registry' }",
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        // Same as above.
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_binstall_globalalways_isused.stderr"
+        ]);
+    assert_not_installed("abc");
+}
+
+/// Test that the per-package configuration has precedence over the global one.
+#[cargo_test(requires = "cargo-binstall")]
+fn validate_ship_binstall_globalalways_localnever_isunused() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish("abc", "0.0.0");
+    write_user_config(&[
+        "[defaults]",
+        "ship.binstall = 'always'",
+        "[packages]",
+        "abc = { version = '*', binstall = 'never', registry = 'dummy-registry' }",
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        // Same as above.
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_binstall_globalnever_isunused.stderr"
+        ]);
+    assert_installed("abc");
+}
+
+/// Test `--dry-run` for the Binstall method.
+#[cargo_test(requires = "cargo-binstall")]
+fn validate_ship_binstall_dryrun() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish("abc", "0.0.0");
+    write_user_config(&[
+        "[defaults]",
+        "ship.binstall = 'always'",
+        "[packages]",
+        "abc = { version = '*', registry = 'dummy-registry' }",
+    ]);
+
+    // It fails here, but the logs show the option is passed.
+    cargo_liner!()
+        .args(["ship", "--no-self", "--dry-run"])
         .assert()
         .failure()
         .stdout_eq("".into_data().raw())
@@ -2384,3 +2763,1009 @@ fn validate_ship_binstall_always_incompatible_option_nowarning() {
         ]);
     assert_not_installed("abc");
 }
+
+/// The latest version being out of the requirement's reach should not trigger
+/// an update on each run.
+#[cargo_test]
+fn validate_ship_latestoutofreq_noupdate() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.1"), ("abc", "0.1.0")]);
+    fake_install("abc", "0.0.1", false);
+    write_user_config(&["[packages]", "abc = '~0.0.1'"]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(
+            snapbox::file!["fixtures/ship/validate_ship_latestoutofreq_noupdate.stderr"].raw(),
+        );
+    assert_installed("abc");
+}
+
+/// Yanked versions satisfying the requirement should not be selected when the
+/// latest version is out of its reach.
+#[cargo_test]
+fn validate_ship_latestoutofreq_matchingyanked_noupdate() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.1"), ("abc", "0.1.0")]);
+    Package::new("abc", "0.0.2")
+        .file("src/main.rs", "fn main() {}")
+        .yanked(true)
+        .publish();
+    fake_install("abc", "0.0.1", false);
+    write_user_config(&["[packages]", "abc = '~0.0.1'"]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(
+            snapbox::file!["fixtures/ship/validate_ship_latestoutofreq_noupdate.stderr"].raw(),
+        );
+    assert_installed("abc");
+}
+
+/// The latest version being out of the requirement's reach should not prevent
+/// the package from being installed when it is not yet.
+#[cargo_test]
+fn validate_ship_latestoutofreq_notinstalled_install() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.1"), ("abc", "0.1.0")]);
+    write_user_config(&["[packages]", "abc = '~0.0.1'"]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_latestoutofreq_notinstalled_install.stderr"
+        ]);
+    assert_installed("abc");
+}
+
+/// An installed version that does not satisfy the requirement anymore should
+/// be replaced, even when it is newer.
+#[cargo_test]
+fn validate_ship_installedoutofreq_update() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.1"), ("abc", "0.1.0")]);
+    fake_install("abc", "0.1.0", false);
+    write_user_config(&["[packages]", "abc = '~0.0.1'"]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_installedoutofreq_update.stderr"
+        ]);
+    assert_installed("abc");
+}
+
+/// Yanked versions should not be considered as available updates.
+#[cargo_test]
+fn validate_ship_neweryanked_noupdate() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish("abc", "0.0.1");
+    Package::new("abc", "0.0.2")
+        .file("src/main.rs", "fn main() {}")
+        .yanked(true)
+        .publish();
+    fake_install("abc", "0.0.1", false);
+    write_user_config(&["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file!["fixtures/ship/validate_ship_neweryanked_noupdate.stderr"].raw());
+    assert_installed("abc");
+}
+
+/// The version check should use the package's configured registry.
+#[cargo_test]
+fn validate_ship_registry_alternative_update() {
+    let _reg = init_registry();
+    let _alt_reg = init_alt_registry();
+    fake_install_self();
+    fake_publish("abc", "0.0.1");
+    fake_publish_alt("abc", "0.0.2");
+    fake_install("abc", "0.0.1", false);
+    write_user_config(&[
+        "[packages.abc]",
+        "version = '*'",
+        "registry = 'alternative'",
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_registry_alternative_update.stderr"
+        ]);
+    assert_installed("abc");
+}
+
+/// The version check should use the package's configured index.
+#[cargo_test]
+fn validate_ship_index_alternative_update() {
+    let _reg = init_registry();
+    let alt_reg = init_alt_registry();
+    fake_install_self();
+    fake_publish("abc", "0.0.1");
+    fake_publish_alt("abc", "0.0.2");
+    fake_install("abc", "0.0.1", false);
+    write_user_config(&[
+        "[packages.abc]",
+        "version = '*'",
+        &format!("index = '{}'", alt_reg.index_url()),
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_index_alternative_update.stderr"
+        ]);
+    assert_installed("abc");
+}
+
+/// Packages whose registry cannot be checked should always be installed.
+#[cargo_test]
+fn validate_ship_registry_gitindex_alwaysinstalled() {
+    let _reg = init_registry();
+    let _alt_reg = init_alt_registry_git();
+    fake_install_self();
+    fake_publish_alt("abc", "0.0.1");
+    fake_install("abc", "0.0.1", false);
+    write_user_config(&[
+        "[packages.abc]",
+        "version = '*'",
+        "registry = 'alternative'",
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_registry_gitindex_alwaysinstalled.stderr"
+        ]);
+    assert_installed("abc");
+}
+
+#[cargo_test]
+fn validate_ship_sameoptions_noupdate() {
+    let _reg = init_registry();
+    fake_install_self();
+    Package::new("abc", "0.0.0")
+        .feature("feat", &[])
+        .file("src/bin/b1.rs", "fn main() {}")
+        .file("src/bin/b2.rs", "fn main() {}")
+        .publish();
+    write_user_config(&[
+        "[packages]",
+        "abc = { version = '*', features = ['feat'], bins = ['b1'] }",
+    ]);
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success();
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_sameoptions_noupdate.stderr"
+        ]);
+    assert_installed("b1");
+    assert_not_installed("b2");
+}
+
+#[cargo_test]
+fn validate_ship_sameoptions_extraarguments_noupdate() {
+    let _reg = init_registry();
+    fake_install_self();
+    Package::new("abc", "0.0.0")
+        .feature("feat", &[])
+        .file("src/bin/b1.rs", "fn main() {}")
+        .file("src/bin/b2.rs", "fn main() {}")
+        .publish();
+    write_user_config(&[
+        "[packages]",
+        "abc = { version = '*', extra-arguments = ['--features', 'feat', '--bin', 'b1'] }",
+    ]);
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success();
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_sameoptions_noupdate.stderr"
+        ]);
+    assert_installed("b1");
+    assert_not_installed("b2");
+}
+
+#[cargo_test]
+fn validate_ship_changedoptions_update() {
+    let _reg = init_registry();
+    fake_install_self();
+    Package::new("abc", "0.0.0")
+        .feature("feat", &[])
+        .file("src/bin/b1.rs", "fn main() {}")
+        .file("src/bin/b2.rs", "fn main() {}")
+        .publish();
+    write_user_config(&["[packages]", "abc = { version = '*', bins = ['b1'] }"]);
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success();
+    write_user_config(&[
+        "[packages]",
+        "abc = { version = '*', features = ['feat'], bins = ['b1', 'b2'] }",
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_changedoptions_update.stderr"
+        ]);
+    assert_installed_all(["b1", "b2"]);
+}
+
+#[cargo_test]
+fn validate_ship_messageformatjson_install() {
+    let _reg = init_registry();
+    fake_publish_all([("cargo-liner", "0.0.3"), ("abc", "0.0.1"), ("def", "0.0.2")]);
+    fake_install_all([("cargo-liner", "0.0.3", false), ("def", "0.0.1", false)]);
+    write_user_config(&["[packages]", "abc = '*'", "def = '*'"]);
+
+    cargo_liner!()
+        .args(["ship", "--message-format", "json"])
+        .assert()
+        .success()
+        .stdout_eq(snapbox::file![
+            "fixtures/ship/validate_ship_messageformatjson_install.stdout"
+        ])
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_messageformatjson_install.stderr"
+        ]);
+    assert_installed_all(["abc", "def"]);
+}
+
+#[cargo_test]
+fn validate_ship_messageformatjson_nofailfast_err_iserr() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish("def", "0.0.0");
+    write_user_config(&["[packages]", "abc = '*'", "def = '*'"]);
+
+    cargo_liner!()
+        .args([
+            "ship",
+            "--skip-check",
+            "--no-self",
+            "--no-fail-fast",
+            "--message-format",
+            "json",
+        ])
+        .assert()
+        .failure()
+        .stdout_eq(snapbox::file![
+            "fixtures/ship/validate_ship_messageformatjson_nofailfast_err_iserr.stdout"
+        ])
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_install_nofailfast_err_iserr.stderr"
+        ]);
+    assert_not_installed("abc");
+    assert_installed("def");
+}
+
+// Outputs of parallel installations are only displayed once each is done,
+// thus in any order: only check the results here.
+#[cargo_test]
+fn validate_ship_jobs_install() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.0"), ("def", "0.0.0"), ("ghi", "0.0.0")]);
+    write_user_config(&["[packages]", "abc = '*'", "def = '*'", "ghi = '*'"]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self", "--jobs", "3"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw());
+    assert_installed_all(["abc", "def", "ghi"]);
+}
+
+#[cargo_test]
+fn validate_ship_jobs_config_install() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.0"), ("def", "0.0.0")]);
+    write_user_config(&[
+        "[packages]",
+        "abc = '*'",
+        "def = '*'",
+        "[defaults.ship]",
+        "jobs = 2",
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw());
+    assert_installed_all(["abc", "def"]);
+}
+
+#[cargo_test]
+fn validate_ship_jobs_nofailfast_err_iserr() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("def", "0.0.0"), ("ghi", "0.0.0")]);
+    write_user_config(&["[packages]", "abc = '*'", "def = '*'", "ghi = '*'"]);
+
+    cargo_liner!()
+        .args([
+            "ship",
+            "--skip-check",
+            "--no-self",
+            "--no-fail-fast",
+            "--jobs",
+            "2",
+        ])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw());
+    assert_not_installed("abc");
+    assert_installed_all(["def", "ghi"]);
+}
+
+#[cargo_test]
+fn validate_ship_jobs_failfast_iserr() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish("def", "0.0.0");
+    write_user_config(&["[packages]", "abc = '*'", "def = '*'"]);
+
+    cargo_liner!()
+        .args(["ship", "--skip-check", "--no-self", "--jobs", "2"])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw());
+    assert_not_installed("abc");
+}
+
+/// Packages installed by other jobs before a fail-fast error are still
+/// reported and locked.
+#[cargo_test]
+fn validate_ship_jobs_failfast_lock_recordsinstalled() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.1"), ("def", "0.0.1")]);
+    write_user_config(&[
+        "[packages]",
+        "abc = { version = '*', features = ['nonexistent'] }",
+        "def = '*'",
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self", "--jobs", "2"])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw());
+    assert_not_installed("abc");
+    assert_installed("def");
+    assert_user_lock_eq(snapbox::file![
+        "fixtures/ship/validate_ship_jobs_failfast_lock_recordsinstalled.lock"
+    ]);
+}
+
+#[cargo_test]
+fn validate_ship_jobs_zero_iserr() {
+    cargo_liner!()
+        .args(["ship", "--jobs", "0"])
+        .assert()
+        .code(2)
+        .stdout_eq("".into_data().raw());
+}
+
+#[cargo_test]
+fn validate_ship_lookupjobs_install() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.0"), ("def", "0.0.0"), ("ghi", "0.0.0")]);
+    write_user_config(&["[packages]", "abc = '*'", "def = '*'", "ghi = '*'"]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self", "--lookup-jobs", "1"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw());
+    assert_installed_all(["abc", "def", "ghi"]);
+}
+
+#[cargo_test]
+fn validate_ship_lookupjobs_zero_iserr() {
+    cargo_liner!()
+        .args(["ship", "--lookup-jobs", "0"])
+        .assert()
+        .code(2)
+        .stdout_eq("".into_data().raw());
+}
+
+#[cargo_test]
+fn validate_ship_lookup_failfast_iserr() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_install("abc", "0.0.0", false);
+    fake_publish("def", "0.0.0");
+    write_user_config(&["[packages]", "abc = '*'", "def = '*'"]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_lookup_failfast_iserr.stderr"
+        ]);
+    assert_not_installed("def");
+}
+
+/// Failed lookups are only warned about: the other packages are still updated.
+#[cargo_test]
+fn validate_ship_lookup_nofailfast_unknown_isok() {
+    let _reg = init_registry();
+    fake_install_self();
+    let (repo_url, commit) = fake_git_repo("ghi", "0.0.0");
+    fake_install_git("ghi", "0.0.0", &repo_url, &commit);
+    fake_install("abc", "0.0.0", false);
+    fake_publish("def", "0.0.0");
+    write_user_config(&[
+        "[packages]",
+        "abc = '*'",
+        "def = '*'",
+        &format!("ghi = {{ version = '*', git = '{repo_url}', branch = 'none' }}"),
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self", "--no-fail-fast", "--lookup-jobs", "2"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_lookup_nofailfast_unknown_isok.stderr"
+        ]);
+    assert_installed_all(["abc", "def", "ghi"]);
+}
+
+#[cargo_test]
+fn validate_ship_project_liner_toml() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.0"), ("def", "0.0.0")]);
+    write_user_config(&["[packages]", "def = '*'"]);
+    let path = write_config_file("proj/liner.toml", &["[packages]", "abc = '*'"]);
+    let sub_dir = path.with_file_name("src");
+    std::fs::create_dir(&sub_dir).unwrap();
+
+    cargo_liner!()
+        .current_dir(sub_dir)
+        .args(["ship", "--skip-check"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_project_liner_toml.stderr"
+        ]);
+    let root = path.with_file_name(".cargo-liner");
+    cargo_test_support::install::assert_has_installed_exe(&root, "abc");
+    cargo_test_support::install::assert_has_not_installed_exe(&root, "cargo-liner");
+    assert_not_installed_all(["abc", "def"]);
+}
+
+#[cargo_test]
+fn validate_ship_project_fragments_ignored() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.0"), ("def", "0.0.0")]);
+    let path = write_config_file("proj/liner.toml", &["[packages]", "abc = '*'"]);
+    write_config_file("proj/liner.d/extra.toml", &["[packages]", "def = '*'"]);
+
+    cargo_liner!()
+        .current_dir(path.parent().unwrap())
+        .args(["ship", "--skip-check"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_project_liner_toml.stderr"
+        ]);
+    let root = path.with_file_name(".cargo-liner");
+    cargo_test_support::install::assert_has_installed_exe(&root, "abc");
+    cargo_test_support::install::assert_has_not_installed_exe(&root, "def");
+    assert_not_installed_all(["abc", "def"]);
+}
+
+#[cargo_test]
+fn validate_ship_project_relativepath_nested() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_local_package("abc", "0.0.0");
+    let path = write_config_file(
+        "proj/liner.toml",
+        &["[packages]", "abc = { version = '*', path = '../abc' }"],
+    );
+    let sub_dir = path.with_file_name("src/nested");
+    std::fs::create_dir_all(&sub_dir).unwrap();
+
+    cargo_liner!()
+        .current_dir(sub_dir)
+        .args(["ship", "--skip-check"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_project_relativepath_nested.stderr"
+        ]);
+    cargo_test_support::install::assert_has_installed_exe(
+        path.with_file_name(".cargo-liner"),
+        "abc",
+    );
+    assert_not_installed("abc");
+}
+
+#[cargo_test]
+fn validate_ship_project_cargo_manifest() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish("abc", "0.0.0");
+    let path = write_config_file(
+        "ws/Cargo.toml",
+        &[
+            "[workspace]",
+            "members = []",
+            "[workspace.metadata.liner.packages]",
+            "abc = '*'",
+        ],
+    );
+
+    cargo_liner!()
+        .current_dir(path.parent().unwrap())
+        .args(["-q", "ship", "--skip-check"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_project_cargo_manifest.stderr"
+        ]);
+    cargo_test_support::install::assert_has_installed_exe(
+        path.with_file_name(".cargo-liner"),
+        "abc",
+    );
+    assert_not_installed("abc");
+}
+
+#[cargo_test]
+fn validate_ship_project_cargo_manifest_nometadata_isglobal() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish("abc", "0.0.0");
+    write_user_config(&["[packages]", "abc = '*'"]);
+    let path = write_config_file("pkg/Cargo.toml", &["[package]", "name = 'pkg'"]);
+
+    cargo_liner!()
+        .current_dir(path.parent().unwrap())
+        .args(["-q", "ship", "--skip-check", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_project_cargo_manifest_nometadata_isglobal.stderr"
+        ]);
+    assert_installed("abc");
+}
+
+#[cargo_test]
+fn validate_ship_group_selects() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.0"), ("def", "0.0.0"), ("ghi", "0.0.0")]);
+    write_user_config(&[
+        "[packages]",
+        "abc = '*'",
+        "def = { version = '*', groups = ['release'] }",
+        "ghi = '*'",
+        "[groups]",
+        "release = ['ghi']",
+        "heavy = ['ghi']",
+    ]);
+
+    cargo_liner!()
+        .args([
+            "ship",
+            "--skip-check",
+            "--group",
+            "release",
+            "--exclude-group",
+            "heavy",
+        ])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_group_selects.stderr"
+        ]);
+    assert_installed("def");
+    assert_not_installed_all(["abc", "ghi"]);
+}
+
+#[cargo_test]
+fn validate_ship_packages_selects() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.0"), ("def", "0.0.0")]);
+    write_user_config(&["[packages]", "abc = '*'", "def = '*'"]);
+
+    cargo_liner!()
+        .args(["-q", "ship", "--skip-check", "abc"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_packages_selects.stderr"
+        ]);
+    assert_installed("abc");
+    assert_not_installed("def");
+}
+
+#[cargo_test]
+fn validate_ship_packages_notconfigured_iserr() {
+    let _reg = init_registry();
+    fake_install_self();
+    write_user_config(&["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .args(["ship", "def"])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_packages_notconfigured_iserr.stderr"
+        ]);
+}
+
+#[cargo_test]
+fn validate_ship_overlays_applied() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([
+        ("abc", "0.0.0"),
+        ("def", "0.0.0"),
+        ("ghi", "0.0.0"),
+        ("jkl", "0.0.0"),
+    ]);
+    write_user_config(&[
+        "[packages]",
+        "abc = '*'",
+        "[target.'cfg(all())'.packages]",
+        "def = '*'",
+        "[target.'cfg(any())'.packages]",
+        "ghi = '*'",
+        &format!(
+            "[hosts.'{}'.packages]",
+            gethostname::gethostname().to_string_lossy()
+        ),
+        "jkl = '*'",
+        "[hosts.other-host.packages]",
+        "ghi = '*'",
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self", "--skip-check"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_overlays_applied.stderr"
+        ]);
+    assert_installed_all(["abc", "def", "jkl"]);
+    assert_not_installed("ghi");
+}
+
+#[cargo_test]
+fn validate_ship_overlays_invalidtarget_iserr() {
+    let _reg = init_registry();
+    fake_install_self();
+    write_user_config(&[
+        "[packages]",
+        "[target.aarch64-unknown-none.packages]",
+        "abc = '*'",
+    ]);
+
+    cargo_liner!()
+        .arg("ship")
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_overlays_invalidtarget_iserr.stderr"
+        ]);
+}
+
+#[cargo_test]
+fn validate_ship_path_interpolated() {
+    let _reg = init_registry();
+    fake_install_self();
+    let pkg_path = fake_local_package("abc", "0.0.0");
+    write_user_config(&[
+        "[packages]",
+        "abc = { version = '*', path = '${CARGO_LINER_TEST_PKG_DIR}' }",
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .env("CARGO_LINER_TEST_PKG_DIR", &pkg_path)
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_path_interpolated.stderr"
+        ]);
+    assert_installed("abc");
+}
+
+#[cargo_test]
+fn validate_ship_interpolation_undefined_iserr() {
+    let _reg = init_registry();
+    fake_install_self();
+    write_user_config(&[
+        "[packages]",
+        "abc = { version = '*', environment = { ABC = '${CARGO_LINER_TEST_UNDEFINED}' } }",
+    ]);
+
+    cargo_liner!()
+        .arg("ship")
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_interpolation_undefined_iserr.stderr"
+        ]);
+}
+
+#[cargo_test]
+fn validate_ship_newerversion_iserr() {
+    let _reg = init_registry();
+    fake_install_self();
+    write_user_config(&["version = 2", "[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .arg("ship")
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_newerversion_iserr.stderr"
+        ]);
+}
+
+#[cargo_test]
+fn validate_ship_lock_written() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.1"), ("def", "0.0.1")]);
+    write_user_config(&["[packages]", "abc = '*'", "def = '*'"]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_lock_written.stderr"
+        ]);
+    assert_installed_all(["abc", "def"]);
+    assert_user_lock_eq(snapbox::file![
+        "fixtures/ship/validate_ship_lock_written.lock"
+    ]);
+}
+
+#[cargo_test]
+fn validate_ship_lock_dryrun_absent() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish("abc", "0.0.1");
+    write_user_config(&["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self", "--dry-run"])
+        .assert()
+        .success();
+    assert_user_lock_absent();
+}
+
+#[cargo_test]
+fn validate_ship_lock_keptthenupdated() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.1"), ("def", "0.0.1")]);
+    write_user_config(&["[packages]", "abc = '*'", "def = '*'"]);
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success();
+
+    // Updated, but kept locked to the previous version.
+    fake_publish("abc", "0.0.2");
+    cargo_liner!()
+        .args(["ship", "--no-self"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_lock_keptthenupdated_kept.stderr"
+        ]);
+    assert_user_lock_eq(snapbox::file![
+        "fixtures/ship/validate_ship_lock_written.lock"
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self", "--update-lock"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_lock_keptthenupdated_updated.stderr"
+        ]);
+    assert_user_lock_eq(snapbox::file![
+        "fixtures/ship/validate_ship_lock_keptthenupdated.lock"
+    ]);
+}
+
+#[cargo_test]
+fn validate_ship_lock_configpath_stemnamed() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.1"), ("def", "0.0.1")]);
+    let path = write_config_file("ci-tools.toml", &["[packages]", "abc = '*'", "def = '*'"]);
+
+    cargo_liner!()
+        .args(["-q", "ship", "--no-self", "--config"])
+        .arg(&path)
+        .assert()
+        .success();
+    assert_user_lock_absent();
+    cargo_test_support::compare::assert_ui().eq(
+        std::fs::read_to_string(path.with_file_name("ci-tools.lock")).unwrap(),
+        snapbox::file!["fixtures/ship/validate_ship_lock_written.lock"],
+    );
+}
+
+#[cargo_test]
+fn validate_ship_lock_unconfigured_dropped() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.1"), ("def", "0.0.1")]);
+    write_user_config(&["[packages]", "abc = '*'", "def = '*'"]);
+    cargo_liner!()
+        .args(["-q", "ship", "--no-self"])
+        .assert()
+        .success();
+
+    write_user_config(&["[packages]", "abc = '*'"]);
+    cargo_liner!()
+        .args(["-q", "ship", "--no-self"])
+        .assert()
+        .success();
+    assert_user_lock_eq(snapbox::file![
+        "fixtures/ship/validate_ship_lock_unconfigured_dropped.lock"
+    ]);
+}
+
+#[cargo_test]
+fn validate_ship_lockedconfig_installslocked() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish_all([("abc", "0.0.1"), ("abc", "0.0.2")]);
+    write_user_config(&["[packages]", "abc = '*'"]);
+    write_user_lock(&[
+        "version = 1",
+        "[packages.abc]",
+        "version = '0.0.1'",
+        "source = 'registry+https://example.com/index'",
+    ]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self", "--locked-config"])
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_lockedconfig_installslocked.stderr"
+        ]);
+    assert_installed("abc");
+    // Left untouched.
+    assert_user_lock_eq(
+        "version = 1\n[packages.abc]\nversion = '0.0.1'\nsource = \
+         'registry+https://example.com/index'"
+            .into_data()
+            .raw(),
+    );
+}
+
+#[cargo_test]
+fn validate_ship_lockedconfig_notlocked_iserr() {
+    let _reg = init_registry();
+    fake_install_self();
+    fake_publish("abc", "0.0.1");
+    write_user_config(&["[packages]", "abc = '*'"]);
+    write_user_lock(&["version = 1"]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self", "--locked-config"])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_lockedconfig_notlocked_iserr.stderr"
+        ]);
+    assert_not_installed("abc");
+}
+
+#[cargo_test]
+fn validate_ship_lockedconfig_nolock_iserr() {
+    let _reg = init_registry();
+    fake_install_self();
+    write_user_config(&["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .args(["ship", "--no-self", "--locked-config"])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_lockedconfig_nolock_iserr.stderr"
+        ]);
+}
+
+#[cargo_test]
+fn validate_ship_lockedconfig_updatelock_iserr() {
+    cargo_liner!()
+        .args(["ship", "--locked-config", "--update-lock"])
+        .assert()
+        .failure()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file![
+            "fixtures/ship/validate_ship_lockedconfig_updatelock_iserr.stderr"
+        ]);
+}
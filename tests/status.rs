@@ -0,0 +1,134 @@
+use cargo_test_macro::cargo_test;
+use snapbox::IntoData;
+
+mod common;
+use common::*;
+
+#[cargo_test]
+fn validate_status_uptodate_isok() {
+    let _reg = init_registry();
+    fake_publish_all([("cargo-liner", "0.0.3"), ("abc", "0.0.1")]);
+    fake_install_all([("cargo-liner", "0.0.3", false), ("abc", "0.0.1", false)]);
+    write_user_config(&["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .arg("status")
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(snapbox::file!["fixtures/status/validate_status_uptodate_isok.stderr"].raw());
+}
+
+#[cargo_test]
+fn validate_status_newversion_iscode2() {
+    let _reg = init_registry();
+    fake_publish_all([("cargo-liner", "0.0.3"), ("abc", "0.0.2")]);
+    fake_install_all([("cargo-liner", "0.0.3", false), ("abc", "0.0.1", false)]);
+    write_user_config(&["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .arg("status")
+        .assert()
+        .code(2)
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(
+            snapbox::file!["fixtures/status/validate_status_newversion_iscode2.stderr"].raw(),
+        );
+    assert_installed("abc");
+}
+
+#[cargo_test]
+fn validate_status_notinstalled_iscode2() {
+    let _reg = init_registry();
+    fake_publish_all([("cargo-liner", "0.0.3"), ("abc", "0.0.1")]);
+    fake_install("cargo-liner", "0.0.3", false);
+    write_user_config(&["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .arg("status")
+        .assert()
+        .code(2)
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(
+            snapbox::file!["fixtures/status/validate_status_notinstalled_iscode2.stderr"].raw(),
+        );
+    assert_not_installed("abc");
+}
+
+#[cargo_test]
+fn validate_status_notconfigured_iscode2() {
+    let _reg = init_registry();
+    fake_publish("cargo-liner", "0.0.3");
+    fake_install_all([("cargo-liner", "0.0.3", false), ("abc", "0.0.1", false)]);
+    write_user_config(&["[packages]"]);
+
+    cargo_liner!()
+        .arg("status")
+        .assert()
+        .code(2)
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(
+            snapbox::file!["fixtures/status/validate_status_notconfigured_iscode2.stderr"].raw(),
+        );
+    assert_installed("abc");
+}
+
+#[cargo_test]
+fn validate_status_skipcheck_installed_isok() {
+    let _reg = init_registry();
+    fake_publish("cargo-liner", "0.0.3");
+    fake_install_all([("cargo-liner", "0.0.3", false), ("abc", "0.0.1", false)]);
+    write_user_config(&["[packages]", "abc = { version = '*', skip-check = true }"]);
+
+    cargo_liner!()
+        .arg("status")
+        .assert()
+        .success()
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(
+            snapbox::file!["fixtures/status/validate_status_skipcheck_installed_isok.stderr"].raw(),
+        );
+}
+
+#[cargo_test]
+fn validate_status_globalskipcheck_stillchecks() {
+    let _reg = init_registry();
+    fake_publish_all([("cargo-liner", "0.0.3"), ("abc", "0.0.2")]);
+    fake_install_all([("cargo-liner", "0.0.3", false), ("abc", "0.0.1", false)]);
+    write_user_config(&["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .arg("status")
+        .env("CARGO_LINER_SHIP_SKIP_CHECK", "true")
+        .assert()
+        .code(2)
+        .stdout_eq("".into_data().raw())
+        .stderr_eq(
+            snapbox::file!["fixtures/status/validate_status_newversion_iscode2.stderr"].raw(),
+        );
+}
+
+#[cargo_test]
+fn validate_status_messageformatjson_iscode2() {
+    let _reg = init_registry();
+    fake_publish_all([("cargo-liner", "0.0.3"), ("abc", "0.0.2")]);
+    fake_install_all([
+        ("cargo-liner", "0.0.3", false),
+        ("abc", "0.0.1", false),
+        ("def", "0.0.1", false),
+    ]);
+    write_user_config(&["[packages]", "abc = '*'"]);
+
+    cargo_liner!()
+        .args(["status", "--message-format", "json"])
+        .assert()
+        .code(2)
+        .stdout_eq(
+            snapbox::file!["fixtures/status/validate_status_messageformatjson_iscode2.stdout"]
+                .raw(),
+        )
+        .stderr_eq(
+            snapbox::file!["fixtures/status/validate_status_messageformatjson_iscode2.stderr"]
+                .raw(),
+        );
+}